@@ -0,0 +1,162 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Build throughput and query latency of minimal functions, for each encoder, hash
+//! size, and single vs. partitioned functions, and query latency of
+//! [`CachedPhf`]
+//!
+//! Run with `cargo bench --bench phf`; a subset can be selected with a filter, eg.
+//! `cargo bench --bench phf -- query/partitioned`.
+
+use std::collections::HashSet;
+use std::hint::black_box;
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rand::prelude::*;
+
+use pthash::cached::CachedPhf;
+use pthash::*;
+
+const NUM_KEYS: usize = 1_000_000;
+const NUM_PARTITIONS: u64 = 8;
+/// Fixed, so built functions (and their query times) are the same across runs
+const SEED: u64 = 42;
+
+fn random_keys() -> Vec<u64> {
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let mut keys = HashSet::with_capacity(NUM_KEYS);
+    while keys.len() < NUM_KEYS {
+        keys.insert(rng.random::<u64>());
+    }
+    keys.into_iter().collect()
+}
+
+fn configuration(partitioned: bool) -> BuildConfiguration {
+    let mut config = BuildConfiguration::default();
+    config.verbose_output = false;
+    config.seed = SEED;
+    config.check_duplicates = false;
+    if partitioned {
+        config.num_partitions = NUM_PARTITIONS;
+    }
+    config
+}
+
+fn bench_phf<F: Phf>(
+    c: &mut Criterion,
+    keys: &[u64],
+    name: &str,
+    new: fn() -> F,
+    config: &BuildConfiguration,
+) {
+    let mut group = c.benchmark_group("build");
+    group.throughput(Throughput::Elements(keys.len() as u64));
+    group.sample_size(10);
+    group.measurement_time(Duration::from_secs(20));
+    group.bench_function(BenchmarkId::from_parameter(name), |b| {
+        b.iter(|| {
+            let mut f = new();
+            f.build_in_internal_memory_from_bytes(|| keys, config)
+                .expect("Failed to build");
+            f
+        })
+    });
+    group.finish();
+
+    let mut f = new();
+    f.build_in_internal_memory_from_bytes(|| keys, config)
+        .expect("Failed to build");
+
+    let mut group = c.benchmark_group("query");
+    // Throughput in lookups/s, ie. the inverse of the latency in ns/lookup
+    group.throughput(Throughput::Elements(keys.len() as u64));
+    group.bench_function(BenchmarkId::from_parameter(name), |b| {
+        b.iter(|| {
+            for key in keys {
+                black_box(f.hash(black_box(key)));
+            }
+        })
+    });
+    group.finish();
+}
+
+macro_rules! bench_phfs {
+    ($c:expr, $keys:expr, $($name:literal => $phf:ident<$hasher:ty, $encoder:ty>),* $(,)?) => {$(
+        bench_phf(
+            $c,
+            $keys,
+            $name,
+            $phf::<Minimal, $hasher, $encoder>::new,
+            &configuration(stringify!($phf) == "PartitionedPhf"),
+        );
+    )*};
+}
+
+fn bench(c: &mut Criterion) {
+    let keys = random_keys();
+    bench_phfs!(
+        c,
+        &keys,
+        "single/hash64/dictionary_dictionary" => SinglePhf<MurmurHash2_64, DictionaryDictionary>,
+        "single/hash64/partitioned_compact" => SinglePhf<MurmurHash2_64, PartitionedCompact>,
+        "single/hash64/elias_fano" => SinglePhf<MurmurHash2_64, EliasFano>,
+        "single/hash128/dictionary_dictionary" => SinglePhf<MurmurHash2_128, DictionaryDictionary>,
+        "single/hash128/partitioned_compact" => SinglePhf<MurmurHash2_128, PartitionedCompact>,
+        "single/hash128/elias_fano" => SinglePhf<MurmurHash2_128, EliasFano>,
+        "partitioned/hash64/dictionary_dictionary" => PartitionedPhf<MurmurHash2_64, DictionaryDictionary>,
+        "partitioned/hash64/partitioned_compact" => PartitionedPhf<MurmurHash2_64, PartitionedCompact>,
+        "partitioned/hash64/elias_fano" => PartitionedPhf<MurmurHash2_64, EliasFano>,
+        "partitioned/hash128/dictionary_dictionary" => PartitionedPhf<MurmurHash2_128, DictionaryDictionary>,
+        "partitioned/hash128/partitioned_compact" => PartitionedPhf<MurmurHash2_128, PartitionedCompact>,
+        "partitioned/hash128/elias_fano" => PartitionedPhf<MurmurHash2_128, EliasFano>,
+    );
+}
+
+/// Query latency of [`CachedPhf`] compared to the function it wraps, when most
+/// queries are on a few popular keys
+fn bench_cached(c: &mut Criterion) {
+    const NUM_HOT_KEYS: usize = 64;
+    const CACHE_CAPACITY: usize = 1024;
+
+    let keys = random_keys();
+    let mut f = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &configuration(true))
+        .expect("Failed to build");
+
+    // 90% of queries are on hot keys
+    let mut rng = StdRng::seed_from_u64(SEED);
+    let queries: Vec<u64> = (0..NUM_KEYS)
+        .map(|_| {
+            if rng.random_bool(0.9) {
+                keys[rng.random_range(0..NUM_HOT_KEYS)]
+            } else {
+                keys[rng.random_range(0..keys.len())]
+            }
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("cached");
+    group.throughput(Throughput::Elements(queries.len() as u64));
+    group.bench_function(BenchmarkId::from_parameter("uncached"), |b| {
+        b.iter(|| {
+            for key in &queries {
+                black_box(f.hash(black_box(key)));
+            }
+        })
+    });
+    let cached = CachedPhf::new(&f, CACHE_CAPACITY);
+    group.bench_function(BenchmarkId::from_parameter("cached"), |b| {
+        b.iter(|| {
+            for key in &queries {
+                black_box(cached.hash(black_box(key)));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench, bench_cached);
+criterion_main!(benches);
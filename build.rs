@@ -10,6 +10,9 @@ use thiserror::Error;
 
 const BRIDGE_MODULES: [&str; 3] = ["src/hashing.rs", "src/build.rs", "src/utils.rs"];
 
+/// Bridge modules only compiled with the `build` feature
+const BUILD_BRIDGE_MODULES: [&str; 1] = ["src/builders.rs"];
+
 const BACKENDS_BRIDGE_PRELUDE: &str = r#"
 #[cfg_attr(not(all(feature = "hash64", feature = "hash128")), allow(dead_code))]
 #[cxx::bridge]
@@ -18,23 +21,31 @@ mod ffi {
     unsafe extern "C++" {
         include!("pthash.hpp");
 
-        type build_configuration = crate::build::ffi::build_configuration;
         type hash64 = crate::structs::hash64;
         type hash128 = crate::structs::hash128;
     }
+"#;
+
+const BACKENDS_BUILDER_BRIDGE_PRELUDE: &str = r#"
+    #[namespace = "pthash"]
+    unsafe extern "C++" {
+        include!("pthash.hpp");
+
+        type build_configuration = crate::build::ffi::build_configuration;
+    }
 
     #[namespace = "pthash_rs::concrete"]
     unsafe extern "C++" {
         include!("concrete.hpp");
 
         type internal_memory_builder_single_phf_64 =
-            crate::build::ffi::internal_memory_builder_single_phf_64;
+            crate::builders::ffi::internal_memory_builder_single_phf_64;
         type internal_memory_builder_single_phf_128 =
-            crate::build::ffi::internal_memory_builder_single_phf_128;
+            crate::builders::ffi::internal_memory_builder_single_phf_128;
         type internal_memory_builder_partitioned_phf_64 =
-            crate::build::ffi::internal_memory_builder_partitioned_phf_64;
+            crate::builders::ffi::internal_memory_builder_partitioned_phf_64;
         type internal_memory_builder_partitioned_phf_128 =
-            crate::build::ffi::internal_memory_builder_partitioned_phf_128;
+            crate::builders::ffi::internal_memory_builder_partitioned_phf_128;
     }
 "#;
 
@@ -54,17 +65,53 @@ const BACKENDS_BRIDGE_TEMPLATE: &str = r#"
         #[cxx_name = "construct"]
         fn $$STRUCT_NAME$$_new() -> UniquePtr<$$STRUCT_NAME$$>;
 
-        fn build(
-            self: Pin<&mut $$STRUCT_NAME$$>,
-            builder: &$$BUILDER_NAME$$,
-            config: &build_configuration,
-        ) -> Result<f64>;
+        #[cxx_name = "construct_copy"]
+        fn $$STRUCT_NAME$$_copy(
+            data_structure: &UniquePtr<$$STRUCT_NAME$$>,
+        ) -> UniquePtr<$$STRUCT_NAME$$>;
 
         fn position(self: &$$STRUCT_NAME$$, hash: $$HASH_TYPE$$) -> u64;
         fn num_bits(self: &$$STRUCT_NAME$$) -> usize;
         fn num_keys(self: &$$STRUCT_NAME$$) -> u64;
         fn table_size(self: &$$STRUCT_NAME$$) -> u64;
         fn seed(self: &$$STRUCT_NAME$$) -> u64;
+
+        #[cxx_name = "memory_usage"]
+        fn $$STRUCT_NAME$$_memory_usage(data_structure: &$$STRUCT_NAME$$) -> usize;
+
+        #[cxx_name = "num_bits_breakdown"]
+        fn $$STRUCT_NAME$$_num_bits_breakdown(data_structure: &$$STRUCT_NAME$$) -> Vec<u64>;
+
+        #[cxx_name = "partition_offsets"]
+        fn $$STRUCT_NAME$$_partition_offsets(data_structure: &$$STRUCT_NAME$$) -> Vec<u64>;
+
+        #[cxx_name = "positions"]
+        fn $$STRUCT_NAME$$_positions(
+            data_structure: &$$STRUCT_NAME$$,
+            hashes: &[u64],
+            positions: &mut [u64],
+        ) -> Result<()>;
+
+        #[cxx_name = "serialized_size"]
+        fn $$STRUCT_NAME$$_serialized_size(data_structure: &$$STRUCT_NAME$$) -> usize;
+
+        #[cxx_name = "save_bytes"]
+        fn $$STRUCT_NAME$$_save_bytes(
+            data_structure: &$$STRUCT_NAME$$,
+            bytes: &mut [u8],
+        ) -> Result<()>;
+
+        #[cxx_name = "load_bytes"]
+        fn $$STRUCT_NAME$$_load_bytes(
+            data_structure: Pin<&mut $$STRUCT_NAME$$>,
+            bytes: &[u8],
+        ) -> Result<usize>;
+
+        #[cxx_name = "load_file"]
+        unsafe fn $$STRUCT_NAME$$_load(
+            data_structure: Pin<&mut $$STRUCT_NAME$$>,
+            filename: *const c_char,
+        ) -> Result<usize>;
     }
 
     #[namespace = "essentials"]
@@ -76,24 +123,31 @@ const BACKENDS_BRIDGE_TEMPLATE: &str = r#"
             data_structure: Pin<&mut $$STRUCT_NAME$$>,
             filename: *const c_char,
         ) -> Result<usize>;
+    }
+"#;
 
-        #[cxx_name = "load"]
-        unsafe fn $$STRUCT_NAME$$_load(
-            data_structure: Pin<&mut $$STRUCT_NAME$$>,
-            filename: *const c_char,
-        ) -> Result<usize>;
+const BACKENDS_BUILDER_BRIDGE_TEMPLATE: &str = r#"
+    #[namespace = "pthash_rs::concrete"]
+    unsafe extern "C++" {
+        include!("concrete.hpp");
+
+        fn build(
+            self: Pin<&mut $$STRUCT_NAME$$>,
+            builder: &$$BUILDER_NAME$$,
+            config: &build_configuration,
+        ) -> Result<f64>;
     }
 "#;
 
 const BACKENDS_BRIDGE_POSTLUDE: &str = r#"
 }
 
-#[cfg(feature = "hash64")]
+#[cfg(all(feature = "build", feature = "hash64"))]
 pub(crate) use ffi::{
     internal_memory_builder_partitioned_phf_64, internal_memory_builder_single_phf_64,
 };
 
-#[cfg(feature = "hash128")]
+#[cfg(all(feature = "build", feature = "hash128"))]
 pub(crate) use ffi::{
     internal_memory_builder_partitioned_phf_128, internal_memory_builder_single_phf_128,
 };
@@ -105,14 +159,28 @@ pub(crate) use ffi::$$STRUCT_NAME$$;
 impl BackendPhf for $$STRUCT_NAME$$ {
     type Hash = ffi::$$HASH_TYPE$$;
     type Encoder = $$ENCODER_NAME$$;
+    #[cfg(feature = "build")]
     type Builder = $$BUILDER_NAME$$;
 
     fn new() -> UniquePtr<Self> {
         ffi::$$STRUCT_NAME$$_new()
     }
+    fn copy(this: &UniquePtr<Self>) -> UniquePtr<Self> {
+        ffi::$$STRUCT_NAME$$_copy(this)
+    }
     fn position(&self, hash: Self::Hash) -> u64 {
         <$$STRUCT_NAME$$>::position(self, hash)
     }
+    fn positions(&self, hashes: &[Self::Hash], positions: &mut [u64]) -> Result<()> {
+        // SAFETY: hash types are repr(C) structures of u64 words
+        let words = unsafe {
+            std::slice::from_raw_parts(
+                hashes.as_ptr() as *const u64,
+                std::mem::size_of_val(hashes) / std::mem::size_of::<u64>(),
+            )
+        };
+        ffi::$$STRUCT_NAME$$_positions(self, words, positions)
+    }
     fn num_bits(&self) -> usize {
         <$$STRUCT_NAME$$>::num_bits(self)
     }
@@ -125,6 +193,16 @@ impl BackendPhf for $$STRUCT_NAME$$ {
     fn seed(&self) -> u64 {
         <$$STRUCT_NAME$$>::seed(self)
     }
+    fn memory_usage(&self) -> usize {
+        ffi::$$STRUCT_NAME$$_memory_usage(self)
+    }
+    fn num_bits_breakdown(&self) -> Vec<u64> {
+        ffi::$$STRUCT_NAME$$_num_bits_breakdown(self)
+    }
+    fn partition_offsets(&self) -> Vec<u64> {
+        ffi::$$STRUCT_NAME$$_partition_offsets(self)
+    }
+    #[cfg(feature = "build")]
     fn build(
         self: Pin<&mut Self>,
         builder: &Self::Builder,
@@ -139,13 +217,59 @@ impl BackendPhf for $$STRUCT_NAME$$ {
     unsafe fn load(self: Pin<&mut Self>, filename: *const i8) -> Result<usize> {
         ffi::$$STRUCT_NAME$$_load(self, filename)
     }
+    fn save_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = vec![0; ffi::$$STRUCT_NAME$$_serialized_size(self)];
+        ffi::$$STRUCT_NAME$$_save_bytes(self, &mut bytes)?;
+        Ok(bytes)
+    }
+    fn load_bytes(self: Pin<&mut Self>, bytes: &[u8]) -> Result<usize> {
+        ffi::$$STRUCT_NAME$$_load_bytes(self, bytes)
+    }
+}
+"#;
+
+const ENCODER_TEMPLATE: &str = r#"
+/// Upstream `pthash::$$ENCODER_SNAKECASE$$` encoder, enabled by the
+/// `PTHASH_EXTRA_ENCODERS` environment variable at build time
+pub struct $$ENCODER_NAME$$;
+impl Encoder for $$ENCODER_NAME$$ {
+    const NAME: &'static str = "$$ENCODER_SNAKECASE$$";
+}
+"#;
+
+const ENCODER_BY_HASH_TEMPLATE: &str = r#"
+#[cfg(feature = "hash$$HASH_SIZE$$")]
+impl BackendForEncoderByHash<hash$$HASH_SIZE$$> for $$ENCODER_NAME$$ {
+    #[cfg(feature = "minimal")]
+    type MinimalSinglePhfBackend =
+        crate::backends::singlephf_$$HASH_SIZE$$_$$ENCODER_SNAKECASE$$_minimal;
+    #[cfg(feature = "nonminimal")]
+    type NonminimalSinglePhfBackend =
+        crate::backends::singlephf_$$HASH_SIZE$$_$$ENCODER_SNAKECASE$$_nonminimal;
+    #[cfg(feature = "minimal")]
+    type MinimalPartitionedPhfBackend =
+        crate::backends::partitionedphf_$$HASH_SIZE$$_$$ENCODER_SNAKECASE$$_minimal;
+    #[cfg(feature = "nonminimal")]
+    type NonminimalPartitionedPhfBackend =
+        crate::backends::partitionedphf_$$HASH_SIZE$$_$$ENCODER_SNAKECASE$$_nonminimal;
 }
 "#;
 
+/// `(snakecase, camelcase)` names of the encoders selected by features
+const BUILTIN_ENCODERS: [(&str, &str); 5] = [
+    ("dictionary_dictionary", "DictionaryDictionary"),
+    ("partitioned_compact", "PartitionedCompact"),
+    ("elias_fano", "EliasFano"),
+    ("compact", "Compact"),
+    ("dictionary", "Dictionary"),
+];
+
+/// Comma-separated list of names of upstream encoders (eg. `compact_compact`) to
+/// instantiate, in addition to the ones selected by features
+const EXTRA_ENCODERS_ENV: &str = "PTHASH_EXTRA_ENCODERS";
+
 #[derive(Error, Debug)]
 pub enum BuildError {
-    #[error("autocxx engine error: {0}")]
-    AutoCxxBuilder(#[from] autocxx_engine::BuilderError),
     #[error("could not create {0}: {1}")]
     CreateFile(PathBuf, std::io::Error),
     #[error("could not write to {0}: {1}")]
@@ -156,6 +280,16 @@ pub enum BuildError {
     NoHashSize,
     #[error("at least one of 'minimal' and 'nonminimal' features must be enabled")]
     NoMinimality,
+    #[error("invalid encoder name in {EXTRA_ENCODERS_ENV}: {0:?}")]
+    InvalidEncoderName(String),
+    #[error("encoder {0:?} is listed more than once in {EXTRA_ENCODERS_ENV}")]
+    DuplicateEncoderName(String),
+    #[error("encoder {0:?} in {EXTRA_ENCODERS_ENV} is built in, enable its feature instead")]
+    BuiltinEncoderName(String),
+    #[error("could not run {0}: {1}")]
+    RunCompiler(PathBuf, std::io::Error),
+    #[error("{0} could not find the static C++ standard library (lib{1}.a)")]
+    NoStaticLibstdcxx(PathBuf, &'static str),
 }
 
 // see https://github.com/dtolnay/cxx/issues/1004
@@ -183,66 +317,105 @@ fn main_() -> Result<(), BuildError> {
     let pthash_src_dir = pthash_src_dir.as_path();
     let out_dir = Path::new(&std::env::var("OUT_DIR").expect("Missing OUT_DIR")).to_owned();
 
-    let mut b = autocxx_build::Builder::new(
-        "src/structs.rs",
-        [
-            &manifest_dir.join("src"),
-            pthash_src_dir,
-            &pthash_src_dir.join("include/"),
-            &pthash_src_dir.join("external/essentials/include/"),
-        ],
-    )
-    .extra_clang_args(&["-std=c++17"])
-    .build()?;
-    b.flag("-std=c++17").compile("pthash-ffi");
-
     let backends_path = out_dir.join("backends_codegen.rs.inc");
 
     let mut fd = std::fs::File::create(&backends_path)
         .map_err(|e| BuildError::CreateFile(backends_path.clone(), e))?;
 
     // Write bridge
+    let with_builders = has_feature("build");
     fd.write_all(BACKENDS_BRIDGE_PRELUDE.as_bytes())
         .map_err(|e| BuildError::WriteFile(backends_path.clone(), e))?;
+    if with_builders {
+        fd.write_all(BACKENDS_BUILDER_BRIDGE_PRELUDE.as_bytes())
+            .map_err(|e| BuildError::WriteFile(backends_path.clone(), e))?;
+    }
     for concrete_struct in concrete_structs()? {
-        fd.write_all(&subst(concrete_struct, BACKENDS_BRIDGE_TEMPLATE))
+        fd.write_all(&subst(&concrete_struct, BACKENDS_BRIDGE_TEMPLATE))
             .map_err(|e| BuildError::WriteFile(backends_path.clone(), e))?;
+        if with_builders {
+            // Omitted otherwise, so PTHash's encoding code is not instantiated
+            fd.write_all(&subst(&concrete_struct, BACKENDS_BUILDER_BRIDGE_TEMPLATE))
+                .map_err(|e| BuildError::WriteFile(backends_path.clone(), e))?;
+        }
     }
     fd.write_all(BACKENDS_BRIDGE_POSTLUDE.as_bytes())
         .map_err(|e| BuildError::WriteFile(backends_path.clone(), e))?;
 
     // Write implementations
     for concrete_struct in concrete_structs()? {
-        fd.write_all(&subst(concrete_struct, BACKENDS_IMPL_TEMPLATE))
+        fd.write_all(&subst(&concrete_struct, BACKENDS_IMPL_TEMPLATE))
             .map_err(|e| BuildError::WriteFile(backends_path.clone(), e))?;
     }
 
     drop(fd);
 
+    write_extra_encoders(&out_dir)?;
+
     let mut bridge_modules: Vec<_> = BRIDGE_MODULES.iter().map(ToString::to_string).collect();
+    if with_builders {
+        bridge_modules.extend(BUILD_BRIDGE_MODULES.iter().map(ToString::to_string));
+    }
     bridge_modules.push(backends_path.display().to_string());
 
-    cxx_build::bridges(bridge_modules)
+    let static_libstdcxx = has_feature("static-libstdcxx");
+    let mut build = cxx_build::bridges(bridge_modules);
+    build
         .flag("-std=c++17")
         .include("src")
+        .include(&out_dir)
         .include(pthash_src_dir)
         .include(pthash_src_dir.join("include/"))
-        .include(pthash_src_dir.join("external/essentials/include/"))
-        .compile("pthash");
+        .include(pthash_src_dir.join("external/essentials/include/"));
+    if static_libstdcxx {
+        // Linked by link_libstdcxx_statically instead
+        build.cpp_link_stdlib(None::<&str>);
+    }
+    build.compile("pthash");
+    if static_libstdcxx {
+        link_libstdcxx_statically(build.get_compiler().path())?;
+    }
 
     remove_cxxbridge_symlink("pthash");
 
-    for module in BRIDGE_MODULES {
+    for module in BRIDGE_MODULES.iter().chain(BUILD_BRIDGE_MODULES.iter()) {
         println!("cargo:rerun-if-changed={module}");
     }
-    println!("cargo:rerun-if-changed=src/structs.rs");
     println!("cargo:rerun-if-changed=src/cpp-utils.hpp");
     println!("cargo:rerun-if-changed=src/concrete.hpp");
+    println!("cargo:rerun-if-env-changed={EXTRA_ENCODERS_ENV}");
+
+    emit_version_info(&manifest_dir);
 
     Ok(())
 }
 
-fn subst(concrete_struct: ConcreteStruct, template: &str) -> Vec<u8> {
+/// Exposes the PTHash commit and enabled features to `src/version.rs`
+fn emit_version_info(manifest_dir: &Path) {
+    let pthash_commit = std::process::Command::new("git")
+        .arg("-C")
+        .arg(manifest_dir.join("pthash"))
+        .args(["rev-parse", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|commit| commit.trim().to_owned())
+        .unwrap_or_else(|| "unknown".to_owned());
+    println!("cargo:rustc-env=PTHASH_COMMIT={pthash_commit}");
+    let submodule_head = manifest_dir.join(".git/modules/pthash/HEAD");
+    if submodule_head.exists() {
+        println!("cargo:rerun-if-changed={}", submodule_head.display());
+    }
+
+    let mut features: Vec<_> = std::env::vars()
+        .filter_map(|(name, _)| name.strip_prefix("CARGO_FEATURE_").map(str::to_lowercase))
+        .collect();
+    features.sort();
+    println!("cargo:rustc-env=PTHASH_RS_FEATURES={}", features.join(","));
+}
+
+fn subst(concrete_struct: &ConcreteStruct, template: &str) -> Vec<u8> {
     template
         .replace("$$STRUCT_NAME$$", &concrete_struct.struct_name)
         .replace("$$ENCODER_NAME$$", &concrete_struct.encoder_name)
@@ -259,18 +432,119 @@ struct ConcreteStruct {
 }
 
 fn has_feature(feature: &str) -> bool {
-    std::env::var(format!("CARGO_FEATURE_{}", feature.to_uppercase())).is_ok()
+    std::env::var(format!(
+        "CARGO_FEATURE_{}",
+        feature.to_uppercase().replace('-', "_")
+    ))
+    .is_ok()
+}
+
+/// Links the C++ standard library statically, for the `static-libstdcxx` feature
+///
+/// Both `cc` and `cxx` (through `link-cplusplus`, unless its `nothing` feature is
+/// enabled, as this feature does) otherwise link it dynamically.
+fn link_libstdcxx_statically(compiler: &Path) -> Result<(), BuildError> {
+    let target = std::env::var("TARGET").expect("Missing TARGET");
+    let lib = if target.contains("apple") || target.contains("bsd") {
+        "c++"
+    } else {
+        "stdc++"
+    };
+    let output = std::process::Command::new(compiler)
+        .arg(format!("-print-file-name=lib{lib}.a"))
+        .output()
+        .map_err(|e| BuildError::RunCompiler(compiler.to_owned(), e))?;
+    // Compilers print the file name alone when they do not find it
+    let path = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    match path.parent() {
+        Some(dir) if path.is_absolute() => {
+            println!("cargo:rustc-link-search=native={}", dir.display())
+        }
+        _ => return Err(BuildError::NoStaticLibstdcxx(compiler.to_owned(), lib)),
+    }
+    println!("cargo:rustc-link-lib=static={lib}");
+    Ok(())
+}
+
+/// Returns the `(snakecase, camelcase)` names of encoders listed in
+/// [`EXTRA_ENCODERS_ENV`]
+///
+/// Names listed twice, or already built in, are rejected as they would generate the
+/// same types twice.
+fn extra_encoders() -> Result<Vec<(String, String)>, BuildError> {
+    let Ok(names) = std::env::var(EXTRA_ENCODERS_ENV) else {
+        return Ok(Vec::new());
+    };
+    let mut seen = std::collections::HashSet::new();
+    names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            if !name.starts_with(|c: char| c.is_ascii_lowercase())
+                || !name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            {
+                return Err(BuildError::InvalidEncoderName(name.to_owned()));
+            }
+            if BUILTIN_ENCODERS.iter().any(|&(builtin, _)| builtin == name) {
+                return Err(BuildError::BuiltinEncoderName(name.to_owned()));
+            }
+            if !seen.insert(name) {
+                return Err(BuildError::DuplicateEncoderName(name.to_owned()));
+            }
+            let camelcase = name
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars
+                        .next()
+                        .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                        .unwrap_or_default()
+                })
+                .collect();
+            Ok((name.to_owned(), camelcase))
+        })
+        .collect()
+}
+
+/// Writes the C++ typedefs and Rust [`Encoder`] implementations for
+/// [`extra_encoders`], respectively included by `concrete.hpp` and `encoders.rs`
+fn write_extra_encoders(out_dir: &Path) -> Result<(), BuildError> {
+    let header_path = out_dir.join("extra_concrete.hpp");
+    let mut header =
+        String::from("#pragma once\n\nnamespace pthash_rs {\n    namespace concrete {\n");
+    let encoders_path = out_dir.join("encoders_codegen.rs.inc");
+    let mut encoders = String::new();
+    for (snakecase, camelcase) in extra_encoders()? {
+        let subst = |template: &str| {
+            template
+                .replace("$$ENCODER_SNAKECASE$$", &snakecase)
+                .replace("$$ENCODER_NAME$$", &camelcase)
+        };
+        encoders.push_str(&subst(ENCODER_TEMPLATE));
+        for hash_size in ["64", "128"] {
+            header.push_str(&format!("        concrete({hash_size}, {snakecase});\n"));
+            encoders.push_str(&subst(ENCODER_BY_HASH_TEMPLATE).replace("$$HASH_SIZE$$", hash_size));
+        }
+    }
+    header.push_str("    }\n}\n");
+
+    std::fs::write(&header_path, header).map_err(|e| BuildError::WriteFile(header_path, e))?;
+    std::fs::write(&encoders_path, encoders)
+        .map_err(|e| BuildError::WriteFile(encoders_path, e))?;
+
+    Ok(())
 }
 
 fn concrete_structs() -> Result<Vec<ConcreteStruct>, BuildError> {
-    let encoders: Vec<_> = [
-        ("dictionary_dictionary", "DictionaryDictionary"),
-        ("partitioned_compact", "PartitionedCompact"),
-        ("elias_fano", "EliasFano"),
-    ]
-    .into_iter()
-    .filter(|(snakecase, _camelcase)| has_feature(snakecase))
-    .collect();
+    let encoders: Vec<_> = BUILTIN_ENCODERS
+        .into_iter()
+        .filter(|(snakecase, _camelcase)| has_feature(snakecase))
+        .map(|(snakecase, camelcase)| (snakecase.to_owned(), camelcase.to_owned()))
+        .chain(extra_encoders()?)
+        .collect();
 
     if encoders.is_empty() {
         return Err(BuildError::NoEncoder);
@@ -295,7 +569,7 @@ fn concrete_structs() -> Result<Vec<ConcreteStruct>, BuildError> {
     }
 
     let mut concrete_structs = Vec::new();
-    for (encoder_snakecase, encoder_camelcase) in encoders {
+    for (encoder_snakecase, encoder_camelcase) in &encoders {
         for hash_size in &hash_sizes {
             for phf_type in ["single", "partitioned"] {
                 for minimality in &minimalities {
@@ -303,7 +577,7 @@ fn concrete_structs() -> Result<Vec<ConcreteStruct>, BuildError> {
                         struct_name: format!(
                             "{phf_type}phf_{hash_size}_{encoder_snakecase}_{minimality}"
                         ),
-                        encoder_name: encoder_camelcase.to_string(),
+                        encoder_name: encoder_camelcase.clone(),
                         hash_type: format!("hash{hash_size}"),
                         builder_name: format!(
                             "internal_memory_builder_{phf_type}_phf_{hash_size}"
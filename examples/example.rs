@@ -34,7 +34,9 @@ use std::time::Instant;
 use anyhow::{Context, Result};
 use rand::prelude::*;
 
-use pthash::{BuildConfiguration, DictionaryDictionary, Minimal, MurmurHash2_64, Phf, SinglePhf};
+use pthash::{
+    BuildConfiguration, DictionaryDictionary, Minimal, MurmurHash2_64, Phf, PhfQuery, SinglePhf,
+};
 
 fn main() {
     if let Err(e) = main_() {
@@ -86,11 +88,7 @@ fn main_() -> Result<()> {
         .context("Could not build PHF")?;
     // let timings = f.build_in_external_memory(keys, config);
     log::info!("function built in {} seconds", start.elapsed().as_secs());
-    let total_seconds = timings.partitioning_seconds
-        + timings.mapping_ordering_seconds
-        + timings.searching_seconds
-        + timings.encoding_seconds;
-    log::info!("computed: {} seconds", total_seconds.as_secs_f64());
+    log::info!("computed: {timings}");
 
     /* Compute and print the number of bits spent per key. */
     let bits_per_key = (f.num_bits() as f64) / (f.num_keys() as f64);
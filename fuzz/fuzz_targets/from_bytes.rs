@@ -0,0 +1,46 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Deserializes arbitrary bytes as functions of each type, which should either fail
+//! or return a function whose sizes are consistent
+//!
+//! Queries are not fuzzed: the internal structure of encoders is not validated
+//! when loading, so a corrupted function may still return wrong positions.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pthash::*;
+
+fn from_bytes<F: Phf>(data: &[u8], from_bytes: fn(&[u8]) -> Result<F, cxx::Exception>) {
+    if let Ok(f) = from_bytes(data) {
+        assert!(f.num_keys() <= f.table_size());
+    }
+    if let Ok(f) = F::load_from(data) {
+        assert!(f.num_keys() <= f.table_size());
+    }
+}
+
+macro_rules! from_bytes {
+    ($data:expr, $($phf:ident<$minimality:ty, $hasher:ty, $encoder:ty>),* $(,)?) => {$(
+        from_bytes($data, $phf::<$minimality, $hasher, $encoder>::from_bytes);
+    )*};
+}
+
+fuzz_target!(|data: &[u8]| {
+    from_bytes!(
+        data,
+        SinglePhf<Minimal, MurmurHash2_64, DictionaryDictionary>,
+        SinglePhf<Nonminimal, MurmurHash2_64, DictionaryDictionary>,
+        SinglePhf<Minimal, MurmurHash2_128, DictionaryDictionary>,
+        SinglePhf<Minimal, MurmurHash2_64, PartitionedCompact>,
+        SinglePhf<Minimal, MurmurHash2_64, EliasFano>,
+        PartitionedPhf<Minimal, MurmurHash2_64, DictionaryDictionary>,
+        PartitionedPhf<Nonminimal, MurmurHash2_64, DictionaryDictionary>,
+        PartitionedPhf<Minimal, MurmurHash2_128, DictionaryDictionary>,
+        PartitionedPhf<Minimal, MurmurHash2_64, PartitionedCompact>,
+        PartitionedPhf<Minimal, MurmurHash2_64, EliasFano>,
+    );
+});
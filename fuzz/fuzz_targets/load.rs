@@ -0,0 +1,23 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Loads arbitrary files with [`load_any`], which reads their footer to pick the
+//! type of the function, then loads it from the file
+
+#![no_main]
+
+use std::io::Write;
+
+use libfuzzer_sys::fuzz_target;
+use pthash::load_any;
+
+fuzz_target!(|data: &[u8]| {
+    let mut file = tempfile::NamedTempFile::new().expect("Could not create temp file");
+    file.write_all(data).expect("Could not write temp file");
+    file.flush().expect("Could not write temp file");
+    if let Ok(f) = load_any(file.path()) {
+        assert!(f.num_keys() <= f.table_size());
+    }
+});
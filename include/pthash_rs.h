@@ -0,0 +1,65 @@
+/*
+ * Copyright (C) 2026 The Software Heritage developers
+ * See the AUTHORS file at the top-level directory of this distribution
+ * License: GNU General Public License version 3, or any later version
+ * See top-level LICENSE file for more information
+ */
+
+/*
+ * C interface of the pthash crate, enabled by its `capi` feature.
+ * See the documentation of the `pthash::capi` module.
+ *
+ * Functions returning a pointer return NULL on error, and functions returning an int
+ * return -1; pthash_last_error() then returns a description of the error.
+ */
+
+#ifndef PTHASH_RS_H
+#define PTHASH_RS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct PthashPhf pthash_phf;
+
+/* Message of the last error in this thread, or NULL; valid until the next call */
+const char *pthash_last_error(void);
+
+/*
+ * Builds a non-partitioned function from num_keys keys, the i-th one being the
+ * key_lens[i] bytes at keys[i] (which may be NULL if key_lens[i] is 0). encoder
+ * is an encoder name (eg. "dictionary_dictionary"), or NULL for the default one.
+ * Functions use the MurmurHash2_64 hasher, so pthash_load() can load them back.
+ */
+pthash_phf *pthash_build(const uint8_t *const *keys, const size_t *key_lens,
+                         size_t num_keys, bool minimal, const char *encoder,
+                         uint64_t num_threads);
+
+/*
+ * Loads a function saved by pthash_save() or by Rust programs. Only functions
+ * using the MurmurHash2_64 or MurmurHash2_128 hashers are supported.
+ */
+pthash_phf *pthash_load(const char *path);
+
+/* Writes the function to path, and returns 0 */
+int pthash_save(pthash_phf *phf, const char *path);
+
+uint64_t pthash_hash(const pthash_phf *phf, const uint8_t *key, size_t key_len);
+
+uint64_t pthash_num_keys(const pthash_phf *phf);
+
+/* Largest value returned by pthash_hash() plus 1 */
+uint64_t pthash_table_size(const pthash_phf *phf);
+
+/* Frees the function; does nothing if phf is NULL */
+void pthash_free(pthash_phf *phf);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PTHASH_RS_H */
@@ -7,7 +7,8 @@ use std::pin::Pin;
 
 use cxx::{Exception, UniquePtr};
 
-use crate::build::Builder;
+#[cfg(feature = "build")]
+use crate::builders::Builder;
 use crate::encoders::*;
 use crate::hashing::Hash;
 
@@ -20,15 +21,30 @@ include!(concat!(env!("OUT_DIR"), "/backends_codegen.rs.inc"));
 pub(crate) trait BackendPhf: Sized + cxx::memory::UniquePtrTarget {
     type Hash: Hash;
     type Encoder: Encoder;
+    #[cfg(feature = "build")]
     type Builder: Builder<Hash = Self::Hash>;
 
     fn new() -> UniquePtr<Self>;
+    /// Returns a deep copy of the function, with C++'s copy constructor
+    fn copy(this: &UniquePtr<Self>) -> UniquePtr<Self>;
     fn position(&self, hash: Self::Hash) -> u64;
+    /// Same as [`Self::position`] for each hash, with a single FFI call
+    ///
+    /// Fails if `hashes` and `positions` have different lengths.
+    fn positions(&self, hashes: &[Self::Hash], positions: &mut [u64]) -> Result<()>;
     fn num_bits(&self) -> usize;
     fn num_keys(&self) -> u64;
     fn table_size(&self) -> u64;
     fn seed(&self) -> u64;
-
+    fn memory_usage(&self) -> usize;
+    /// Returns the number of bits of the overhead, bucketers, pilots and free slots
+    /// of the function, in this order
+    fn num_bits_breakdown(&self) -> Vec<u64>;
+    /// Returns the first position of each partition, or nothing for non-partitioned
+    /// functions
+    fn partition_offsets(&self) -> Vec<u64>;
+
+    #[cfg(feature = "build")]
     fn build(
         self: Pin<&mut Self>,
         builder: &Self::Builder,
@@ -37,4 +53,8 @@ pub(crate) trait BackendPhf: Sized + cxx::memory::UniquePtrTarget {
 
     unsafe fn save(self: Pin<&mut Self>, filename: *const i8) -> Result<usize>;
     unsafe fn load(self: Pin<&mut Self>, filename: *const i8) -> Result<usize>;
+    /// Same as [`Self::save`], but returns the bytes instead of writing them to a file
+    fn save_bytes(&self) -> Result<Vec<u8>>;
+    /// Same as [`Self::load`], but reads from `bytes` instead of a file
+    fn load_bytes(self: Pin<&mut Self>, bytes: &[u8]) -> Result<usize>;
 }
@@ -0,0 +1,265 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Command-line tool to build perfect-hash functions from key files, similar to
+//! PTHash's `build` tool, and to query, inspect and benchmark them
+
+use std::fs::File;
+use std::hint::black_box;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::time::Instant;
+
+use anyhow::{anyhow, Context, Result};
+use clap::{Parser, Subcommand};
+
+use pthash::dynamic::{load_any, AnyPhf, DynPartitionedPhf, DynSinglePhf};
+use pthash::*;
+
+#[derive(Parser, Debug)]
+#[command(version, about)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Builds a function from keys, and writes it to disk
+    Build(BuildArgs),
+    /// Prints the hash of each key, one per line
+    Query {
+        /// File written by `pthash build`
+        function: PathBuf,
+        /// File to read keys from, one per line; reads from stdin if absent or `-`
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+    },
+    /// Prints the properties of a function
+    Stats {
+        /// File written by `pthash build`
+        function: PathBuf,
+    },
+    /// Measures the throughput of lookups
+    Bench {
+        /// File written by `pthash build`
+        function: PathBuf,
+        /// File to read keys to look up from, one per line; uses random 64-bit
+        /// integers if absent
+        #[arg(short, long)]
+        input: Option<PathBuf>,
+        /// Number of lookups
+        #[arg(short, long, default_value_t = 10_000_000)]
+        num_queries: u64,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct BuildArgs {
+    /// File to read keys from, one per line; reads from stdin if absent or `-`
+    #[arg(short, long)]
+    input: Option<PathBuf>,
+    /// File to write the function to
+    #[arg(short, long)]
+    output: PathBuf,
+    /// Parameter `c`, to trade off build time and space
+    #[arg(short, default_value_t = 7.0)]
+    c: f64,
+    /// Load factor of the table (ignored for minimal functions with dense encoders)
+    #[arg(short, default_value_t = 0.94)]
+    alpha: f64,
+    /// Number of partitions; builds a partitioned function if set
+    #[arg(short = 'p', long)]
+    num_partitions: Option<u64>,
+    /// Encoder (dictionary_dictionary, partitioned_compact, elias_fano, or compact and
+    /// dictionary if enabled at compile time)
+    #[arg(short, long, default_value = "dictionary_dictionary", value_parser = parse_encoder)]
+    encoder: EncoderKind,
+    /// Builds a minimal function, whose values are in the range `[0; num_keys)`
+    #[arg(long)]
+    minimal: bool,
+    /// Uses 128-bit hashes instead of 64-bit hashes, for sets of billions of keys
+    #[arg(long)]
+    hash128: bool,
+    /// Seed of the function; picked at random if absent
+    #[arg(short, long)]
+    seed: Option<u64>,
+    /// Number of threads used by the build
+    #[arg(short, long, default_value_t = 1)]
+    threads: u64,
+    /// Directory for temporary files
+    #[arg(long, default_value_os_t = std::env::temp_dir())]
+    tmp_dir: PathBuf,
+    /// Prints PTHash's progress
+    #[arg(short, long)]
+    verbose: bool,
+    /// Checks the function is perfect (and minimal) on the keys after building it
+    #[arg(long)]
+    check: bool,
+}
+
+fn parse_encoder(name: &str) -> Result<EncoderKind, String> {
+    EncoderKind::from_name(name).ok_or_else(|| format!("unknown or disabled encoder: {name}"))
+}
+
+fn read_keys(input: Option<&PathBuf>) -> Result<Vec<Vec<u8>>> {
+    let reader: Box<dyn BufRead> = match input {
+        Some(path) if path.as_os_str() != "-" => Box::new(BufReader::new(
+            File::open(path).with_context(|| format!("Could not open {}", path.display()))?,
+        )),
+        _ => Box::new(std::io::stdin().lock()),
+    };
+    reader
+        .split(b'\n')
+        .map(|line| {
+            let mut line = line.context("Could not read keys")?;
+            if line.last() == Some(&b'\r') {
+                line.pop();
+            }
+            Ok(line)
+        })
+        .collect()
+}
+
+fn load(path: &PathBuf) -> Result<AnyPhf> {
+    load_any(path).with_context(|| format!("Could not load {}", path.display()))
+}
+
+fn build<M: Minimality, H: Hasher>(
+    args: &BuildArgs,
+    keys: &[Vec<u8>],
+    config: &BuildConfiguration,
+) -> Result<()> {
+    let start = Instant::now();
+    let (num_bits, num_bytes) = if args.num_partitions.is_some() {
+        let mut f = DynPartitionedPhf::<M, H>::new(args.encoder);
+        f.build_in_internal_memory_from_bytes(|| keys, config)
+            .context("Could not build function")?;
+        let num_bytes = f.save(&args.output).context("Could not write function")?;
+        (f.num_bits(), num_bytes)
+    } else {
+        let mut f = DynSinglePhf::<M, H>::new(args.encoder);
+        f.build_in_internal_memory_from_bytes(|| keys, config)
+            .context("Could not build function")?;
+        let num_bytes = f.save(&args.output).context("Could not write function")?;
+        (f.num_bits(), num_bytes)
+    };
+    eprintln!(
+        "Built function for {} keys in {:.2}s: {:.2} bits/key, wrote {} bytes to {}",
+        keys.len(),
+        start.elapsed().as_secs_f64(),
+        num_bits as f64 / keys.len() as f64,
+        num_bytes,
+        args.output.display()
+    );
+    Ok(())
+}
+
+fn build_main(args: BuildArgs) -> Result<()> {
+    let keys = read_keys(args.input.as_ref())?;
+    if keys.is_empty() {
+        return Err(anyhow!("No keys to build a function from"));
+    }
+
+    let mut config = BuildConfiguration::new(args.tmp_dir.clone());
+    config.c = args.c;
+    config.alpha = args.alpha;
+    config.num_threads = args.threads;
+    config.verbose_output = args.verbose;
+    config.verify = args.check;
+    if let Some(num_partitions) = args.num_partitions {
+        config.num_partitions = num_partitions;
+    }
+    if let Some(seed) = args.seed {
+        config.seed = seed;
+    }
+
+    match (args.minimal, args.hash128) {
+        (true, false) => build::<Minimal, MurmurHash2_64>(&args, &keys, &config),
+        (true, true) => build::<Minimal, MurmurHash2_128>(&args, &keys, &config),
+        (false, false) => build::<Nonminimal, MurmurHash2_64>(&args, &keys, &config),
+        (false, true) => build::<Nonminimal, MurmurHash2_128>(&args, &keys, &config),
+    }
+}
+
+fn query_main(function: PathBuf, input: Option<PathBuf>) -> Result<()> {
+    let f = load(&function)?;
+    let keys = read_keys(input.as_ref())?;
+    let mut stdout = BufWriter::new(std::io::stdout().lock());
+    for key in &keys {
+        writeln!(stdout, "{}", f.hash(key)).context("Could not write to stdout")?;
+    }
+    stdout.flush().context("Could not write to stdout")
+}
+
+fn stats_main(function: PathBuf) -> Result<()> {
+    let f = load(&function)?;
+    let metadata = f.metadata();
+    println!("num_keys: {}", f.num_keys());
+    println!("table_size: {}", f.table_size());
+    println!("num_bits: {}", f.num_bits());
+    println!(
+        "bits_per_key: {:.4}",
+        f.num_bits() as f64 / f.num_keys() as f64
+    );
+    println!("minimal: {}", metadata.minimal);
+    println!("partitioned: {}", metadata.partitioned);
+    println!("hash_bits: {}", metadata.hash_bits);
+    println!("hasher: {}", metadata.hasher);
+    println!("encoder: {}", metadata.encoder);
+    println!("seed: {}", f.seed());
+    Ok(())
+}
+
+fn bench_main(function: PathBuf, input: Option<PathBuf>, num_queries: u64) -> Result<()> {
+    let f = load(&function)?;
+    // Read before starting the timer, so only lookups are measured
+    let keys = input.map(|input| read_keys(Some(&input))).transpose()?;
+    if keys.as_ref().is_some_and(|keys| keys.is_empty()) {
+        return Err(anyhow!("No keys to look up"));
+    }
+    let start = Instant::now();
+    match keys {
+        Some(keys) => {
+            for key in keys.iter().cycle().take(num_queries as usize) {
+                black_box(f.hash(key));
+            }
+        }
+        None => {
+            // SplitMix64, so the generator is cheap compared to lookups
+            let mut state = 0u64;
+            for _ in 0..num_queries {
+                state = state.wrapping_add(0x9e3779b97f4a7c15);
+                let mut key = state;
+                key = (key ^ (key >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+                key = (key ^ (key >> 27)).wrapping_mul(0x94d049bb133111eb);
+                key ^= key >> 31;
+                black_box(f.hash(key));
+            }
+        }
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{} lookups in {:.3}s: {:.2} ns/lookup, {:.0} lookups/s",
+        num_queries,
+        elapsed.as_secs_f64(),
+        elapsed.as_nanos() as f64 / num_queries as f64,
+        num_queries as f64 / elapsed.as_secs_f64()
+    );
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    match Args::parse().command {
+        Command::Build(args) => build_main(args),
+        Command::Query { function, input } => query_main(function, input),
+        Command::Stats { function } => stats_main(function),
+        Command::Bench {
+            function,
+            input,
+            num_queries,
+        } => bench_main(function, input, num_queries),
+    }
+}
@@ -6,18 +6,17 @@
 //! Structures used when [building a PHF](crate::Phf::build_in_internal_memory_from_bytes`)
 //! ([`BuildTimings`] and [`BuildConfiguration`])
 
+use std::fmt;
 use std::path::PathBuf;
-use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
-use cxx::{let_cxx_string, Exception, UniquePtr};
+use cxx::Exception;
+use thiserror::Error;
 
-use crate::hashing::Hash;
-use crate::structs::build_timings;
-
-type Result<T> = std::result::Result<T, Exception>;
-
-#[cfg_attr(not(all(feature = "hash64", feature = "hash128")), allow(dead_code))]
+// Setters are only used by builders
+#[cfg_attr(not(feature = "build"), allow(dead_code))]
 #[cxx::bridge]
 pub(crate) mod ffi {
     #[namespace = "pthash"]
@@ -25,68 +24,6 @@ pub(crate) mod ffi {
         include!("pthash.hpp");
 
         type build_configuration;
-        type build_timings = crate::structs::build_timings;
-        type hash64 = crate::structs::hash64;
-        type hash128 = crate::structs::hash128;
-    }
-
-    #[namespace = "pthash_rs::concrete"]
-    unsafe extern "C++" {
-        include!("concrete.hpp");
-        type internal_memory_builder_single_phf_64;
-        type internal_memory_builder_single_phf_128;
-        type internal_memory_builder_partitioned_phf_64;
-        type internal_memory_builder_partitioned_phf_128;
-    }
-
-    #[namespace = "pthash_rs::utils"]
-    unsafe extern "C++" {
-        include!("pthash.hpp");
-        include!("cpp-utils.hpp");
-
-        #[cxx_name = "construct"]
-        fn internal_memory_builder_single_phf_64_new(
-        ) -> UniquePtr<internal_memory_builder_single_phf_64>;
-
-        unsafe fn build_from_hashes(
-            self: Pin<&mut internal_memory_builder_single_phf_64>,
-            hashes: *const hash64,
-            num_keys: u64,
-            config: &build_configuration,
-        ) -> Result<build_timings>;
-
-        #[cxx_name = "construct"]
-        fn internal_memory_builder_single_phf_128_new(
-        ) -> UniquePtr<internal_memory_builder_single_phf_128>;
-
-        unsafe fn build_from_hashes(
-            self: Pin<&mut internal_memory_builder_single_phf_128>,
-            hashes: *const hash128,
-            num_keys: u64,
-            config: &build_configuration,
-        ) -> Result<build_timings>;
-
-        #[cxx_name = "construct"]
-        fn internal_memory_builder_partitioned_phf_64_new(
-        ) -> UniquePtr<internal_memory_builder_partitioned_phf_64>;
-
-        unsafe fn build_from_hashes(
-            self: Pin<&mut internal_memory_builder_partitioned_phf_64>,
-            hashes: *const hash64,
-            num_keys: u64,
-            config: &build_configuration,
-        ) -> Result<build_timings>;
-
-        #[cxx_name = "construct"]
-        fn internal_memory_builder_partitioned_phf_128_new(
-        ) -> UniquePtr<internal_memory_builder_partitioned_phf_128>;
-
-        unsafe fn build_from_hashes(
-            self: Pin<&mut internal_memory_builder_partitioned_phf_128>,
-            hashes: *const hash128,
-            num_keys: u64,
-            config: &build_configuration,
-        ) -> Result<build_timings>;
     }
 
     #[namespace = "pthash_rs::utils"]
@@ -147,77 +84,6 @@ pub(crate) mod ffi {
         fn set_verbose_output(conf: &mut UniquePtr<build_configuration>, value: bool);
     }
 }
-#[cfg(feature = "hash64")]
-pub(crate) use ffi::{
-    hash64, internal_memory_builder_partitioned_phf_64, internal_memory_builder_single_phf_64,
-};
-
-#[cfg(feature = "hash128")]
-pub(crate) use ffi::{
-    hash128, internal_memory_builder_partitioned_phf_128, internal_memory_builder_single_phf_128,
-};
-
-pub(crate) trait Builder: Sized + cxx::memory::UniquePtrTarget {
-    type Hash: Hash;
-
-    fn new() -> UniquePtr<Self>;
-
-    unsafe fn build_from_hashes(
-        self: Pin<&mut Self>,
-        hashes: *const Self::Hash,
-        num_keys: u64,
-        config: &ffi::build_configuration,
-    ) -> Result<build_timings>;
-}
-
-macro_rules! impl_builder {
-    ($type:ty, $hash:ty, $new:path,) => {
-        impl Builder for $type {
-            type Hash = $hash;
-
-            fn new() -> UniquePtr<Self> {
-                $new()
-            }
-            unsafe fn build_from_hashes(
-                self: Pin<&mut Self>,
-                hashes: *const Self::Hash,
-                num_keys: u64,
-                config: &ffi::build_configuration,
-            ) -> Result<build_timings> {
-                <$type>::build_from_hashes(self, hashes, num_keys, config)
-            }
-        }
-    };
-}
-
-#[cfg(feature = "hash64")]
-impl_builder!(
-    internal_memory_builder_single_phf_64,
-    hash64,
-    ffi::internal_memory_builder_single_phf_64_new,
-);
-
-#[cfg(feature = "hash128")]
-impl_builder!(
-    internal_memory_builder_single_phf_128,
-    hash128,
-    ffi::internal_memory_builder_single_phf_128_new,
-);
-
-#[cfg(feature = "hash64")]
-impl_builder!(
-    internal_memory_builder_partitioned_phf_64,
-    hash64,
-    ffi::internal_memory_builder_partitioned_phf_64_new,
-);
-
-#[cfg(feature = "hash128")]
-impl_builder!(
-    internal_memory_builder_partitioned_phf_128,
-    hash128,
-    ffi::internal_memory_builder_partitioned_phf_128_new,
-);
-
 /// Parameter of
 /// [`build_in_internal_memory_from_bytes`](crate::Phf::build_in_internal_memory_from_bytes)
 #[derive(Clone, Debug, PartialEq)]
@@ -228,9 +94,64 @@ pub struct BuildConfiguration {
     pub num_buckets: u64,
     pub num_threads: u64,
     pub seed: u64,
+    /// Memory budget of builds, in bytes (by default, 75% of the available memory)
+    ///
+    /// Builds fail with [`BuildError::RamBudgetExceeded`] as soon as they are
+    /// [estimated](crate::tuning::estimate_build_ram) to need more, instead of running
+    /// out of memory late in the build. They do not spill hashes to [`Self::tmp_dir`]
+    /// to stay within the budget: building in external memory is not supported.
     pub ram: u64,
+    /// Directory for PTHash's intermediate files
+    ///
+    /// PTHash only writes there when building in external memory, so the builds of
+    /// this crate, which are all in internal memory, leave it untouched.
     pub tmp_dir: PathBuf,
     pub verbose_output: bool,
+    /// If set, overrides `alpha` so that the function's
+    /// [`table_size`](crate::PhfQuery::table_size) is this value (or this value plus 1 if
+    /// it is a power of two), for non-minimal functions indexing a pre-sized table
+    ///
+    /// For partitioned functions, the table size is only approximately this value,
+    /// as each partition's table size is rounded.
+    pub table_size: Option<u64>,
+    /// If `true`, duplicate keys are dropped instead of making the build fail, and
+    /// counted in [`BuildTimings::num_duplicate_keys`]
+    ///
+    /// Duplicates are detected from the keys' hashes, so distinct keys whose hashes
+    /// collide are merged too, and will share the same position.
+    pub deduplicate: bool,
+    /// If `true`, checks whether distinct keys have the same hash before running
+    /// PTHash, and tries another seed if they do (unless [`Self::seed`] is set)
+    ///
+    /// Such collisions are likely with 64-bit hashes past a few billion keys, and
+    /// otherwise only make PTHash fail late in the build. This needs 16 bytes of
+    /// memory per key. If [`Self::deduplicate`] is set, colliding keys are merged
+    /// instead.
+    pub check_collisions: bool,
+    /// If `true`, looks for duplicate keys before building, and fails with
+    /// [`BuildError::DuplicateKey`] naming one of them
+    ///
+    /// Otherwise, duplicate keys make PTHash fail late in the build, after trying
+    /// every seed. This hashes keys one more time, and reads again the keys whose
+    /// hashes collide. Enabled by default in debug builds, and ignored if
+    /// [`Self::deduplicate`] is set. As keys are read again,
+    /// [`build_in_internal_memory_from_iter`](crate::Phf::build_in_internal_memory_from_iter)
+    /// fails with [`BuildError::KeysReadOnce`] if this is set.
+    pub check_duplicates: bool,
+    /// If `true`, runs [`check`](crate::check) on the keys after building, like the
+    /// `--check` flag of PTHash's CLI, and fails with [`BuildError::Verification`] if
+    /// the function is not perfect (or not minimal)
+    ///
+    /// This hashes keys one more time, and needs one bit of memory per position of
+    /// the table. It needs the `check` feature, and makes
+    /// [`build_in_internal_memory_from_iter`](crate::Phf::build_in_internal_memory_from_iter)
+    /// (which cannot read keys again) fail with [`BuildError::KeysReadOnce`]. Builds
+    /// fail with [`BuildError::VerifyWithDeduplicate`] if [`Self::deduplicate`] is
+    /// also set, as distinct keys whose hashes collide are then merged.
+    pub verify: bool,
+    /// If set, builds stop with [`BuildError::Cancelled`] soon after the token is
+    /// [cancelled](CancellationToken::cancel)
+    pub cancellation: Option<CancellationToken>,
 }
 
 impl BuildConfiguration {
@@ -246,44 +167,207 @@ impl BuildConfiguration {
             ram: ffi::build_configuration_get_ram(&defaults),
             tmp_dir,
             verbose_output: ffi::build_configuration_get_verbose_output(&defaults),
+            table_size: None,
+            deduplicate: false,
+            check_collisions: false,
+            check_duplicates: cfg!(debug_assertions),
+            verify: false,
+            cancellation: None,
         }
     }
+}
 
-    /// Returns pthash's native [`build_configuration`]
-    pub(crate) fn to_ffi(&self, minimal_output: bool) -> UniquePtr<ffi::build_configuration> {
-        let mut conf = ffi::build_configuration_new();
-        ffi::build_configuration_set_c(&mut conf, self.c);
-        ffi::build_configuration_set_alpha(&mut conf, self.alpha);
-        ffi::build_configuration_set_num_partitions(&mut conf, self.num_partitions);
-        ffi::build_configuration_set_num_buckets(&mut conf, self.num_buckets);
-        ffi::build_configuration_set_num_threads(&mut conf, self.num_threads);
-        ffi::build_configuration_set_seed(&mut conf, self.seed);
-        ffi::build_configuration_set_ram(&mut conf, self.ram);
-        let_cxx_string!(tmp_dir = self.tmp_dir.as_os_str().as_encoded_bytes());
-        ffi::build_configuration_set_tmp_dir(&mut conf, tmp_dir);
-        ffi::build_configuration_set_minimal_output(&mut conf, minimal_output);
-        ffi::build_configuration_set_verbose_output(&mut conf, self.verbose_output);
-        conf
+/// PTHash's defaults, with the system's [temporary directory](std::env::temp_dir)
+///
+/// This does not touch the filesystem, and builds, which are all in internal memory,
+/// never write to `tmp_dir`.
+impl Default for BuildConfiguration {
+    fn default() -> Self {
+        BuildConfiguration::new(std::env::temp_dir())
     }
 }
 
+/// Summary of the parameters, eg. for logs; `seed` and `num_buckets` are `auto` when
+/// picked by the build
+impl fmt::Display for BuildConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "c={}, ", self.c)?;
+        match self.table_size {
+            Some(table_size) => write!(f, "table_size={table_size}, ")?,
+            None => write!(f, "alpha={}, ", self.alpha)?,
+        }
+        write!(f, "num_partitions={}, ", self.num_partitions)?;
+        if crate::utils::valid_num_buckets(self.num_buckets) {
+            write!(f, "num_buckets={}, ", self.num_buckets)?;
+        } else {
+            write!(f, "num_buckets=auto, ")?;
+        }
+        write!(f, "num_threads={}, ", self.num_threads)?;
+        if crate::utils::valid_seed(self.seed) {
+            write!(f, "seed={}, ", self.seed)?;
+        } else {
+            write!(f, "seed=auto, ")?;
+        }
+        write!(f, "ram={}, tmp_dir={}", self.ram, self.tmp_dir.display())
+    }
+}
+
+/// Handle to cancel builds from another thread, eg. when a service shuts down
+///
+/// Builds check it while hashing keys and between PTHash's phases; PTHash's own
+/// phases (mapping, searching, and encoding) run to completion once started, as it
+/// has no way to interrupt them.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests builds using this token (or one of its clones) to stop
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed)
+    }
+
+    /// Returns whether [`Self::cancel`] was called on this token or one of its clones
+    #[inline(always)]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Tokens are equal if they are clones of each other
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Error returned by
+/// [`build_in_internal_memory_from_bytes`](crate::Phf::build_in_internal_memory_from_bytes)
+#[derive(Error, Debug)]
+pub enum BuildError {
+    #[error("PTHash failed to build the function: {0}")]
+    Backend(#[from] Exception),
+    #[error("table_size ({table_size}) is lower than the number of keys ({num_keys})")]
+    TableSizeTooSmall { table_size: u64, num_keys: u64 },
+    #[error(
+        "alpha must be in (0, 1) for all encoders, got {alpha}; use a minimal function to get a table without free slots"
+    )]
+    InvalidAlpha { alpha: f64 },
+    #[error("c must be positive, got {c}")]
+    InvalidC { c: f64 },
+    #[error("No seed was configured, and the 'rand' feature is disabled")]
+    MissingSeed,
+    #[error(
+        "All {} seeds failed, last error: {}",
+        .attempts.len(),
+        .attempts.last().and_then(|attempt| attempt.error.as_deref()).unwrap_or("none")
+    )]
+    SeedsExhausted { attempts: Vec<SeedAttempt> },
+    #[error("Key {} appears more than once", .key.escape_ascii())]
+    DuplicateKey { key: Vec<u8> },
+    #[error("The build was cancelled")]
+    Cancelled,
+    #[cfg(feature = "check")]
+    #[error("The built function is invalid: {0}")]
+    Verification(#[from] crate::ViolatedInvariant),
+    #[error("verify is set, but the 'check' feature is disabled")]
+    VerificationUnavailable,
+    #[error("verify cannot be used with deduplicate, as deduplicated keys share positions")]
+    VerifyWithDeduplicate,
+    #[error("build_in_internal_memory_from_iter reads keys only once, so it needs {0}")]
+    KeysReadOnce(&'static str),
+    #[error(
+        "Building a function on {num_keys} keys or more needs about {estimated} bytes, more than the RAM budget ({ram} bytes)"
+    )]
+    RamBudgetExceeded {
+        num_keys: u64,
+        estimated: u64,
+        ram: u64,
+    },
+}
+
+/// A seed tried by
+/// [`build_in_internal_memory_from_bytes`](crate::Phf::build_in_internal_memory_from_bytes)
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SeedAttempt {
+    pub seed: u64,
+    /// Time spent on this attempt
+    pub duration: Duration,
+    /// Why PTHash failed to build the function with this seed, if it did
+    pub error: Option<String>,
+}
+
 /// Result of
 /// [`build_in_internal_memory_from_bytes`](crate::Phf::build_in_internal_memory_from_bytes)
+///
+/// Its [`Display`](fmt::Display) implementation prints the duration of each phase,
+/// eg. for logs.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BuildTimings {
+    /// Time spent hashing keys in Rust (and deduplicating them or checking their
+    /// hashes for collisions, if configured), with the seed of the function
+    pub hashing_seconds: Duration,
     pub partitioning_seconds: Duration,
     pub mapping_ordering_seconds: Duration,
     pub searching_seconds: Duration,
     pub encoding_seconds: Duration,
+    /// Number of keys dropped because of [`BuildConfiguration::deduplicate`]
+    pub num_duplicate_keys: u64,
+    /// Seeds tried to build the function, the last one being the one it was built with
+    ///
+    /// Only non-partitioned functions retry with other seeds, when no seed is configured.
+    pub attempts: Vec<SeedAttempt>,
+    /// Peak resident memory of the process after hashing keys, in bytes
+    ///
+    /// This is the high-water mark of the whole process since it started, so it is
+    /// only the peak of the build itself if nothing else used more memory before.
+    /// `None` on platforms other than Linux.
+    pub hashing_peak_memory: Option<u64>,
+    /// Peak resident memory of the process after searching pilots and encoding them,
+    /// in bytes, like [`Self::hashing_peak_memory`]
+    ///
+    /// PTHash does not report the memory used by each of its phases, so this covers
+    /// the whole build.
+    pub peak_memory: Option<u64>,
 }
 
 impl BuildTimings {
-    pub(crate) fn from_ffi(timings: &build_timings) -> Self {
-        BuildTimings {
-            partitioning_seconds: Duration::from_secs_f64(timings.partitioning_seconds),
-            mapping_ordering_seconds: Duration::from_secs_f64(timings.mapping_ordering_seconds),
-            searching_seconds: Duration::from_secs_f64(timings.searching_seconds),
-            encoding_seconds: Duration::from_secs_f64(timings.encoding_seconds),
+    /// Returns the time spent in each phase of the build, with the seed of the
+    /// function
+    ///
+    /// This does not include failed [attempts](Self::attempts), nor checking
+    /// for [duplicates](BuildConfiguration::check_duplicates) or
+    /// [verifying](BuildConfiguration::verify) the function.
+    pub fn total(&self) -> Duration {
+        self.hashing_seconds
+            + self.partitioning_seconds
+            + self.mapping_ordering_seconds
+            + self.searching_seconds
+            + self.encoding_seconds
+    }
+}
+
+impl fmt::Display for BuildTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:.3}s (hashing: {:.3}s, partitioning: {:.3}s, mapping+ordering: {:.3}s, \
+             searching: {:.3}s, encoding: {:.3}s)",
+            self.total().as_secs_f64(),
+            self.hashing_seconds.as_secs_f64(),
+            self.partitioning_seconds.as_secs_f64(),
+            self.mapping_ordering_seconds.as_secs_f64(),
+            self.searching_seconds.as_secs_f64(),
+            self.encoding_seconds.as_secs_f64(),
+        )?;
+        if self.attempts.len() > 1 {
+            write!(f, ", {} attempts", self.attempts.len())?;
         }
+        Ok(())
     }
 }
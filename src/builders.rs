@@ -0,0 +1,522 @@
+// Copyright (C) 2024 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Bindings for PTHash's builders, and helpers shared by the implementations of
+//! [`build_in_internal_memory_from_bytes`](crate::Phf::build_in_internal_memory_from_bytes)
+//!
+//! Only compiled with the `build` feature.
+
+use std::collections::HashSet;
+use std::pin::Pin;
+use std::time::Duration;
+
+use cxx::{let_cxx_string, Exception, UniquePtr};
+
+use crate::build::ffi as build_ffi;
+use crate::build::{BuildConfiguration, BuildError, BuildTimings};
+use crate::hashing::{Hash, Hashable, Hasher};
+use crate::structs::build_timings;
+
+type Result<T> = std::result::Result<T, Exception>;
+
+#[cfg_attr(not(all(feature = "hash64", feature = "hash128")), allow(dead_code))]
+#[cxx::bridge]
+pub(crate) mod ffi {
+    #[namespace = "pthash"]
+    unsafe extern "C++" {
+        include!("pthash.hpp");
+
+        type build_configuration = crate::build::ffi::build_configuration;
+        type build_timings = crate::structs::build_timings;
+        type hash64 = crate::structs::hash64;
+        type hash128 = crate::structs::hash128;
+    }
+
+    #[namespace = "pthash_rs::concrete"]
+    unsafe extern "C++" {
+        include!("concrete.hpp");
+        type internal_memory_builder_single_phf_64;
+        type internal_memory_builder_single_phf_128;
+        type internal_memory_builder_partitioned_phf_64;
+        type internal_memory_builder_partitioned_phf_128;
+    }
+
+    #[namespace = "pthash_rs::utils"]
+    unsafe extern "C++" {
+        include!("pthash.hpp");
+        include!("cpp-utils.hpp");
+
+        #[cxx_name = "construct"]
+        fn internal_memory_builder_single_phf_64_new(
+        ) -> UniquePtr<internal_memory_builder_single_phf_64>;
+
+        unsafe fn build_from_hashes(
+            self: Pin<&mut internal_memory_builder_single_phf_64>,
+            hashes: *const hash64,
+            num_keys: u64,
+            config: &build_configuration,
+        ) -> Result<build_timings>;
+
+        #[cxx_name = "construct"]
+        fn internal_memory_builder_single_phf_128_new(
+        ) -> UniquePtr<internal_memory_builder_single_phf_128>;
+
+        unsafe fn build_from_hashes(
+            self: Pin<&mut internal_memory_builder_single_phf_128>,
+            hashes: *const hash128,
+            num_keys: u64,
+            config: &build_configuration,
+        ) -> Result<build_timings>;
+
+        #[cxx_name = "construct"]
+        fn internal_memory_builder_partitioned_phf_64_new(
+        ) -> UniquePtr<internal_memory_builder_partitioned_phf_64>;
+
+        unsafe fn build_from_hashes(
+            self: Pin<&mut internal_memory_builder_partitioned_phf_64>,
+            hashes: *const hash64,
+            num_keys: u64,
+            config: &build_configuration,
+        ) -> Result<build_timings>;
+
+        #[cxx_name = "construct"]
+        fn internal_memory_builder_partitioned_phf_128_new(
+        ) -> UniquePtr<internal_memory_builder_partitioned_phf_128>;
+
+        unsafe fn build_from_hashes(
+            self: Pin<&mut internal_memory_builder_partitioned_phf_128>,
+            hashes: *const hash128,
+            num_keys: u64,
+            config: &build_configuration,
+        ) -> Result<build_timings>;
+    }
+}
+
+#[cfg(feature = "hash64")]
+pub(crate) use ffi::{
+    hash64, internal_memory_builder_partitioned_phf_64, internal_memory_builder_single_phf_64,
+};
+
+#[cfg(feature = "hash128")]
+pub(crate) use ffi::{
+    hash128, internal_memory_builder_partitioned_phf_128, internal_memory_builder_single_phf_128,
+};
+
+pub(crate) trait Builder: Sized + cxx::memory::UniquePtrTarget + 'static {
+    type Hash: Hash;
+
+    fn new() -> UniquePtr<Self>;
+
+    unsafe fn build_from_hashes(
+        self: Pin<&mut Self>,
+        hashes: *const Self::Hash,
+        num_keys: u64,
+        config: &ffi::build_configuration,
+    ) -> Result<build_timings>;
+}
+
+macro_rules! impl_builder {
+    ($type:ty, $hash:ty, $new:path,) => {
+        impl Builder for $type {
+            type Hash = $hash;
+
+            fn new() -> UniquePtr<Self> {
+                $new()
+            }
+            unsafe fn build_from_hashes(
+                self: Pin<&mut Self>,
+                hashes: *const Self::Hash,
+                num_keys: u64,
+                config: &ffi::build_configuration,
+            ) -> Result<build_timings> {
+                <$type>::build_from_hashes(self, hashes, num_keys, config)
+            }
+        }
+    };
+}
+
+#[cfg(feature = "hash64")]
+impl_builder!(
+    internal_memory_builder_single_phf_64,
+    hash64,
+    ffi::internal_memory_builder_single_phf_64_new,
+);
+
+#[cfg(feature = "hash128")]
+impl_builder!(
+    internal_memory_builder_single_phf_128,
+    hash128,
+    ffi::internal_memory_builder_single_phf_128_new,
+);
+
+#[cfg(feature = "hash64")]
+impl_builder!(
+    internal_memory_builder_partitioned_phf_64,
+    hash64,
+    ffi::internal_memory_builder_partitioned_phf_64_new,
+);
+
+#[cfg(feature = "hash128")]
+impl_builder!(
+    internal_memory_builder_partitioned_phf_128,
+    hash128,
+    ffi::internal_memory_builder_partitioned_phf_128_new,
+);
+
+impl BuildConfiguration {
+    /// Sets `alpha` from `table_size`, if it is set
+    pub(crate) fn resolve_alpha(&mut self, num_keys: u64) -> std::result::Result<(), BuildError> {
+        if let Some(table_size) = self.table_size {
+            if table_size < num_keys {
+                return Err(BuildError::TableSizeTooSmall {
+                    table_size,
+                    num_keys,
+                });
+            }
+            if num_keys > 0 {
+                // PTHash computes the table size as floor(num_keys / alpha)
+                self.alpha = num_keys as f64 / (table_size as f64 + 0.5);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns [`BuildError::KeysReadOnce`] if the build needs to read keys more
+    /// than once, for [`build_in_internal_memory_from_iter`](crate::Phf::build_in_internal_memory_from_iter)
+    pub(crate) fn check_keys_read_once(&self) -> std::result::Result<(), BuildError> {
+        if !crate::utils::valid_seed(self.seed) {
+            return Err(BuildError::KeysReadOnce(
+                "a seed, as it cannot retry with other seeds",
+            ));
+        }
+        if self.check_duplicates && !self.deduplicate {
+            return Err(BuildError::KeysReadOnce("check_duplicates to be unset"));
+        }
+        if self.verify {
+            return Err(BuildError::KeysReadOnce("verify to be unset"));
+        }
+        Ok(())
+    }
+
+    /// Returns [`BuildError::Cancelled`] if [`Self::cancellation`] was cancelled
+    #[inline(always)]
+    pub(crate) fn check_cancelled(&self) -> std::result::Result<(), BuildError> {
+        match &self.cancellation {
+            Some(token) if token.is_cancelled() => Err(BuildError::Cancelled),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns [`BuildError::RamBudgetExceeded`] if building a function on `num_keys`
+    /// keys is estimated to need more than [`Self::ram`]
+    pub(crate) fn check_ram<H: Hasher>(
+        &self,
+        num_keys: u64,
+    ) -> std::result::Result<(), BuildError> {
+        let estimated = crate::tuning::estimate_build_ram::<H>(num_keys);
+        if estimated > self.ram {
+            return Err(BuildError::RamBudgetExceeded {
+                num_keys,
+                estimated,
+                ram: self.ram,
+            });
+        }
+        Ok(())
+    }
+
+    /// Returns an error if parameters are outside the ranges PTHash supports
+    ///
+    /// This does not depend on keys, so builds call it before hashing them. `alpha`
+    /// is not checked if [`Self::table_size`] is set, as [`Self::resolve_alpha`]
+    /// then always sets it in range.
+    pub(crate) fn validate(&self) -> std::result::Result<(), BuildError> {
+        // alpha = 1 makes the search for pilots of the last buckets unbounded, and is
+        // equivalent to a minimal function anyway
+        if self.table_size.is_none() && !(self.alpha > 0. && self.alpha < 1.) {
+            return Err(BuildError::InvalidAlpha { alpha: self.alpha });
+        }
+        if !(self.c > 0.) {
+            return Err(BuildError::InvalidC { c: self.c });
+        }
+        if self.verify && self.deduplicate {
+            return Err(BuildError::VerifyWithDeduplicate);
+        }
+        Ok(())
+    }
+
+    /// Makes the configuration more robust if `num_keys` is lower than
+    /// [`SMALL_SET_NUM_KEYS`](crate::tuning::SMALL_SET_NUM_KEYS)
+    pub(crate) fn adjust_for_small_set(&mut self, num_keys: u64) {
+        if num_keys >= crate::tuning::SMALL_SET_NUM_KEYS {
+            return;
+        }
+        self.num_partitions = 1;
+        if !crate::utils::valid_num_buckets(self.num_buckets) {
+            // PTHash's formula divides by log2(num_keys), which is 0 for a single key
+            self.num_buckets = crate::tuning::num_buckets(num_keys.max(2), self.c).max(1);
+        }
+    }
+
+    /// Returns pthash's native [`build_configuration`]
+    pub(crate) fn to_ffi(&self, minimal_output: bool) -> UniquePtr<build_ffi::build_configuration> {
+        let mut conf = build_ffi::build_configuration_new();
+        build_ffi::build_configuration_set_c(&mut conf, self.c);
+        build_ffi::build_configuration_set_alpha(&mut conf, self.alpha);
+        build_ffi::build_configuration_set_num_partitions(&mut conf, self.num_partitions);
+        build_ffi::build_configuration_set_num_buckets(&mut conf, self.num_buckets);
+        build_ffi::build_configuration_set_num_threads(&mut conf, self.num_threads);
+        build_ffi::build_configuration_set_seed(&mut conf, self.seed);
+        build_ffi::build_configuration_set_ram(&mut conf, self.ram);
+        let_cxx_string!(tmp_dir = self.tmp_dir.as_os_str().as_encoded_bytes());
+        build_ffi::build_configuration_set_tmp_dir(&mut conf, tmp_dir);
+        build_ffi::build_configuration_set_minimal_output(&mut conf, minimal_output);
+        build_ffi::build_configuration_set_verbose_output(&mut conf, self.verbose_output);
+        conf
+    }
+}
+
+impl BuildTimings {
+    pub(crate) fn from_ffi(timings: &build_timings) -> Self {
+        BuildTimings {
+            hashing_seconds: Duration::ZERO,
+            partitioning_seconds: Duration::from_secs_f64(timings.partitioning_seconds),
+            mapping_ordering_seconds: Duration::from_secs_f64(timings.mapping_ordering_seconds),
+            searching_seconds: Duration::from_secs_f64(timings.searching_seconds),
+            encoding_seconds: Duration::from_secs_f64(timings.encoding_seconds),
+            num_duplicate_keys: 0,
+            attempts: Vec::new(),
+            hashing_peak_memory: None,
+            peak_memory: None,
+        }
+    }
+}
+
+/// Returns the peak resident memory of this process (`VmHWM`), in bytes
+#[cfg(target_os = "linux")]
+pub(crate) fn peak_memory() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kib = line
+        .trim_start_matches("VmHWM:")
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(kib * 1024)
+}
+
+/// Returns the peak resident memory of this process, which is unknown on this platform
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn peak_memory() -> Option<u64> {
+    None
+}
+
+/// [`SeedAttempt::error`] of attempts aborted by [`BuildConfiguration::check_collisions`]
+pub(crate) const HASH_COLLISION_ERROR: &str = "distinct keys have the same hash";
+
+/// Returns whether two items of `hashes` are equal
+pub(crate) fn has_collisions<H: Hash>(hashes: &[H]) -> bool {
+    let mut bits: Vec<u128> = hashes.iter().map(Hash::to_bits).collect();
+    bits.sort_unstable();
+    bits.windows(2).any(|pair| pair[0] == pair[1])
+}
+
+/// Removes duplicates from `hashes`, and returns how many were removed
+pub(crate) fn deduplicate_hashes<H: Hash>(hashes: &mut Vec<H>) -> u64 {
+    let len = hashes.len();
+    hashes.sort_unstable_by_key(Hash::to_bits);
+    hashes.dedup_by_key(|hash| hash.to_bits());
+    (len - hashes.len()) as u64
+}
+
+/// Number of keys hashed between checks of the [RAM budget](BuildConfiguration::ram),
+/// by sequential builds and by each job of parallel builds
+pub(crate) const RAM_CHECK_INTERVAL: u64 = 1 << 10;
+
+/// Returns the hashes of keys with [`Hasher::hash_batch`], checking for
+/// [cancellation](BuildConfiguration::cancellation) and the [RAM
+/// budget](BuildConfiguration::ram) between chunks
+pub(crate) fn hash_keys_sequential<H: Hasher, K: Hashable>(
+    mut keys: impl Iterator<Item = K>,
+    seed: u64,
+    config: &BuildConfiguration,
+) -> std::result::Result<Vec<H::Hash>, BuildError> {
+    // Fail before hashing anything if the number of keys is known
+    if let (num_keys, Some(upper_bound)) = keys.size_hint() {
+        if num_keys == upper_bound {
+            config.check_ram::<H>(num_keys as u64)?;
+        }
+    }
+    let mut hashes = Vec::new();
+    loop {
+        let len = hashes.len();
+        H::hash_batch(
+            keys.by_ref().take(RAM_CHECK_INTERVAL as usize),
+            seed,
+            &mut hashes,
+        );
+        config.check_cancelled()?;
+        config.check_ram::<H>(hashes.len() as u64)?;
+        if ((hashes.len() - len) as u64) < RAM_CHECK_INTERVAL {
+            return Ok(hashes);
+        }
+    }
+}
+
+/// Returns the hashes of keys with the given seed, for builds, or returns
+/// [`BuildError::Cancelled`] from the calling function
+///
+/// Sequential builds go through [`Hasher::hash_batch`], so hashers can process
+/// several keys at once; parallel builds hash each key on the thread pool. Both
+/// check the [RAM budget](BuildConfiguration::ram) before hashing if the number of
+/// keys is known, and while hashing otherwise. With
+/// `once`, `$keys` is an `Option` which is `None` after the first seed, and the
+/// calling loop over seeds is stopped then.
+macro_rules! hash_keys {
+    ($hasher:ty, $keys:expr, $seed:expr, $config:expr, into_iter) => {
+        crate::builders::hash_keys_sequential::<$hasher, _>($keys.into_iter(), $seed, $config)?
+    };
+    ($hasher:ty, $keys:expr, $seed:expr, $config:expr, once) => {{
+        // Keys can only be read for the first seed, so stop retrying after it
+        let Some(keys) = $keys else { break };
+        crate::builders::hash_keys_sequential::<$hasher, _>(keys.into_iter(), $seed, $config)?
+    }};
+    ($hasher:ty, $keys:expr, $seed:expr, $config:expr, into_par_iter) => {{
+        use std::sync::atomic::{AtomicU64, Ordering};
+
+        let config: &crate::build::BuildConfiguration = $config;
+        let keys = $keys.into_par_iter();
+        // Fail before hashing anything if the number of keys is known
+        if let Some(num_keys) = keys.opt_len() {
+            config.check_ram::<$hasher>(num_keys as u64)?;
+        }
+        // Each job adds the number of keys it hashed to the total every
+        // RAM_CHECK_INTERVAL keys, and all stop once it is over the budget
+        let num_hashed = AtomicU64::new(0);
+        let num_hashed_over_budget = AtomicU64::new(0);
+        let hashes = keys
+            .map_init(
+                || 0,
+                |num_pending, key| {
+                    *num_pending += 1;
+                    if *num_pending == crate::builders::RAM_CHECK_INTERVAL {
+                        let total =
+                            num_hashed.fetch_add(*num_pending, Ordering::Relaxed) + *num_pending;
+                        *num_pending = 0;
+                        if config.check_ram::<$hasher>(total).is_err() {
+                            num_hashed_over_budget.fetch_max(total, Ordering::Relaxed);
+                            return None;
+                        }
+                    }
+                    let hash = || <$hasher as crate::hashing::Hasher>::hash(key, $seed);
+                    config.check_cancelled().ok().map(|()| hash())
+                },
+            )
+            .while_some()
+            .collect::<Vec<_>>();
+        config.check_cancelled()?;
+        config
+            .check_ram::<$hasher>(num_hashed_over_budget.into_inner().max(hashes.len() as u64))?;
+        hashes
+    }};
+}
+
+pub(crate) use hash_keys;
+
+/// Seed of the hashes computed by [`check_duplicate_keys`], which do not depend on the
+/// seeds tried by the build
+pub(crate) const DUPLICATE_CHECK_SEED: u64 = 0x2545f4914f6cdd1d;
+
+/// Hash of a key used by [`check_duplicate_keys`]
+pub(crate) fn duplicate_check_hash<H: Hasher, K: Hashable>(key: K) -> u128 {
+    H::hash(key, DUPLICATE_CHECK_SEED).to_bits()
+}
+
+/// Returns the values which appear more than once in `hashes`
+pub(crate) fn colliding_hashes(mut hashes: Vec<u128>) -> HashSet<u128> {
+    hashes.sort_unstable();
+    hashes
+        .windows(2)
+        .filter(|pair| pair[0] == pair[1])
+        .map(|pair| pair[0])
+        .collect()
+}
+
+/// Returns a key which appears more than once in `keys`, if any
+pub(crate) fn find_duplicate(mut keys: Vec<Vec<u8>>) -> Option<Vec<u8>> {
+    keys.sort_unstable();
+    keys.windows(2)
+        .find(|pair| pair[0] == pair[1])
+        .map(|pair| pair[0].clone())
+}
+
+/// Returns [`BuildError::DuplicateKey`] from the calling function if two keys are
+/// equal, for [`BuildConfiguration::check_duplicates`]
+///
+/// Keys are hashed once, then only those whose hashes collide are read again and
+/// compared byte-by-byte.
+macro_rules! check_duplicate_keys {
+    // Keys can't be read twice; rejected by check_keys_read_once()
+    ($hasher:ty, $keys:expr, once) => {};
+    ($hasher:ty, $keys:expr, $into_iter:ident) => {{
+        use crate::builders::duplicate_check_hash;
+
+        let colliding = crate::builders::colliding_hashes(
+            $keys()
+                .$into_iter()
+                .map(|key| duplicate_check_hash::<$hasher, _>(key))
+                .collect(),
+        );
+        if !colliding.is_empty() {
+            let colliding_keys: Vec<Vec<u8>> = $keys()
+                .$into_iter()
+                .filter(|key| colliding.contains(&duplicate_check_hash::<$hasher, _>(key)))
+                .map(|key| crate::hashing::Hashable::as_bytes(&key).as_ref().to_vec())
+                .collect();
+            if let Some(key) = crate::builders::find_duplicate(colliding_keys) {
+                return Err(BuildError::DuplicateKey { key });
+            }
+        }
+    }};
+}
+
+pub(crate) use check_duplicate_keys;
+
+/// Runs [`check`](crate::check) on the keys, for [`BuildConfiguration::verify`]
+#[cfg(feature = "check")]
+pub(crate) fn verify_keys<Keys: IntoIterator, F: crate::PhfQuery>(
+    keys: Keys,
+    f: &F,
+) -> std::result::Result<(), BuildError>
+where
+    <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
+{
+    Ok(crate::check(keys, f)?)
+}
+
+/// Fails, as functions can't be checked without the `check` feature
+#[cfg(not(feature = "check"))]
+pub(crate) fn verify_keys<Keys: IntoIterator, F: crate::PhfQuery>(
+    _keys: Keys,
+    _f: &F,
+) -> std::result::Result<(), BuildError> {
+    Err(BuildError::VerificationUnavailable)
+}
+
+/// Returns [`BuildError::Verification`] from the calling function if `$phf` is not
+/// perfect on the keys, for [`BuildConfiguration::verify`]
+macro_rules! verify {
+    // Keys can't be read twice; rejected by check_keys_read_once()
+    ($phf:expr, $keys:expr, once) => {};
+    ($phf:expr, $keys:expr, into_iter) => {
+        crate::builders::verify_keys($keys(), $phf)?
+    };
+    ($phf:expr, $keys:expr, $into_iter:ident) => {
+        crate::builders::verify_keys($keys().$into_iter().collect::<Vec<_>>(), $phf)?
+    };
+}
+
+pub(crate) use verify;
@@ -0,0 +1,155 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Process-wide registry of loaded functions, so independent components sharing
+//! a function only load it once
+
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::metadata::LoadError;
+use crate::Phf;
+
+type Key = (PathBuf, TypeId);
+
+struct Loaded {
+    phf: Arc<dyn Any + Send + Sync>,
+    num_bytes: u64,
+}
+
+#[derive(Default)]
+struct Slot {
+    loaded: Mutex<Option<Loaded>>,
+    last_used: AtomicU64,
+}
+
+/// Cache of functions [loaded](Phf::load) from disk, keyed by path
+///
+/// Functions are handed out as [`Arc`]s. When the cache holds more than
+/// `max_bytes`, the least recently used functions which are not referenced outside
+/// the cache are evicted.
+pub struct PhfCache {
+    max_bytes: Option<u64>,
+    slots: Mutex<HashMap<Key, Arc<Slot>>>,
+    clock: AtomicU64,
+}
+
+impl PhfCache {
+    /// Returns an empty cache, which holds at most `max_bytes` of functions if set
+    pub fn new(max_bytes: Option<u64>) -> Self {
+        PhfCache {
+            max_bytes,
+            slots: Mutex::new(HashMap::new()),
+            clock: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the process-wide cache, which has no size limit
+    pub fn global() -> &'static PhfCache {
+        static GLOBAL: OnceLock<PhfCache> = OnceLock::new();
+        GLOBAL.get_or_init(|| PhfCache::new(None))
+    }
+
+    /// Returns the function at `path`, loading it if it is not in the cache
+    ///
+    /// Paths are compared as given, so the same file referred to by two different
+    /// paths is loaded twice. Concurrent calls for the same path load it only once.
+    pub fn get_or_load<F: Phf + 'static>(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Arc<F>, LoadError> {
+        let key = (path.as_ref().to_owned(), TypeId::of::<F>());
+        let slot = Arc::clone(self.slots.lock().unwrap().entry(key).or_default());
+        slot.last_used.store(
+            self.clock.fetch_add(1, Ordering::Relaxed),
+            Ordering::Relaxed,
+        );
+
+        let mut loaded = slot.loaded.lock().unwrap();
+        let phf = match &*loaded {
+            Some(loaded) => Arc::clone(&loaded.phf),
+            None => {
+                let phf = F::load(path)?;
+                let num_bytes = phf.memory_usage() as u64;
+                let phf: Arc<dyn Any + Send + Sync> = Arc::new(phf);
+                *loaded = Some(Loaded {
+                    phf: Arc::clone(&phf),
+                    num_bytes,
+                });
+                drop(loaded);
+                self.evict_to_max();
+                phf
+            }
+        };
+        Ok(phf
+            .downcast()
+            .unwrap_or_else(|_| unreachable!("cache slots are keyed by type")))
+    }
+
+    /// Removes the function at `path` from the cache
+    ///
+    /// Handles previously returned by [`Self::get_or_load`] remain valid.
+    pub fn evict(&self, path: impl AsRef<Path>) {
+        self.slots
+            .lock()
+            .unwrap()
+            .retain(|(slot_path, _), _| slot_path != path.as_ref());
+    }
+
+    /// Removes all functions from the cache
+    pub fn clear(&self) {
+        self.slots.lock().unwrap().clear();
+    }
+
+    /// Returns the total size of functions in the cache
+    pub fn num_bytes(&self) -> u64 {
+        self.slots
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(|slot| slot.loaded.try_lock().ok()?.as_ref().map(|l| l.num_bytes))
+            .sum()
+    }
+
+    /// Evicts least recently used functions not referenced outside the cache, until
+    /// the cache holds at most `max_bytes`
+    pub fn shrink_to(&self, max_bytes: u64) {
+        let mut slots = self.slots.lock().unwrap();
+        let mut candidates = Vec::new();
+        let mut total_bytes = 0;
+        for (key, slot) in slots.iter() {
+            // Slots being loaded are not evictable, and not counted yet
+            let Ok(loaded) = slot.loaded.try_lock() else {
+                continue;
+            };
+            if let Some(loaded) = &*loaded {
+                total_bytes += loaded.num_bytes;
+                if Arc::strong_count(&loaded.phf) == 1 {
+                    candidates.push((slot.last_used.load(Ordering::Relaxed), key.clone()));
+                }
+            }
+        }
+        candidates.sort_unstable_by_key(|(last_used, _)| *last_used);
+        for (_, key) in candidates {
+            if total_bytes <= max_bytes {
+                break;
+            }
+            if let Some(slot) = slots.remove(&key) {
+                if let Some(loaded) = &*slot.loaded.lock().unwrap() {
+                    total_bytes -= loaded.num_bytes;
+                }
+            }
+        }
+    }
+
+    fn evict_to_max(&self) {
+        if let Some(max_bytes) = self.max_bytes {
+            self.shrink_to(max_bytes);
+        }
+    }
+}
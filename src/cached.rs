@@ -0,0 +1,157 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Memoization of the results of queries on a few popular keys ([`CachedPhf`])
+
+use std::hash::{BuildHasher, RandomState};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+use crate::{Hashable, PhfQuery};
+
+/// Number of entries of each shard of [`CachedPhf`]'s cache, which are scanned
+/// linearly
+const SHARD_CAPACITY: usize = 8;
+
+struct Entry {
+    key: Box<[u8]>,
+    position: u64,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct Shard {
+    entries: Vec<Entry>,
+    clock: u64,
+}
+
+impl Shard {
+    fn get(&mut self, key: &[u8]) -> Option<u64> {
+        self.clock += 1;
+        let entry = self.entries.iter_mut().find(|entry| &*entry.key == key)?;
+        entry.last_used = self.clock;
+        Some(entry.position)
+    }
+
+    fn insert(&mut self, key: &[u8], position: u64, capacity: usize) {
+        if self.get(key).is_some() {
+            // Inserted by another thread since our lookup
+            return;
+        }
+        self.clock += 1;
+        let entry = Entry {
+            key: key.into(),
+            position,
+            last_used: self.clock,
+        };
+        if self.entries.len() < capacity {
+            self.entries.push(entry);
+        } else if let Some(lru) = self.entries.iter_mut().min_by_key(|entry| entry.last_used) {
+            *lru = entry;
+        }
+    }
+}
+
+/// Wrapper around a [`PhfQuery`] that remembers the results of the most recently
+/// queried keys
+///
+/// This is meant for workloads where a few keys are looked up much more often than
+/// others, so their results are read from a small cache instead of the function.
+/// However, each query locks a shard, hashes the key to select it, and updates
+/// shared counters, which can cost as much as querying the function itself: check
+/// it helps with `cargo bench --bench phf -- cached` before using it.
+///
+/// The cache is split in shards selected by a hash of the key, each evicting its
+/// least recently used entry when full. Keys are compared by their
+/// [bytes](Hashable::as_bytes), which are copied in the cache.
+pub struct CachedPhf<F: PhfQuery> {
+    inner: F,
+    shards: Box<[Mutex<Shard>]>,
+    shard_capacity: usize,
+    hash_builder: RandomState,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<F: PhfQuery> CachedPhf<F> {
+    /// Wraps `inner`, remembering the results of about `capacity` keys
+    pub fn new(inner: F, capacity: usize) -> Self {
+        let num_shards = capacity.div_ceil(SHARD_CAPACITY).next_power_of_two();
+        CachedPhf {
+            inner,
+            shards: (0..num_shards).map(|_| Mutex::default()).collect(),
+            shard_capacity: capacity.min(SHARD_CAPACITY),
+            hash_builder: RandomState::new(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the wrapped function
+    pub fn inner(&self) -> &F {
+        &self.inner
+    }
+
+    /// Returns the wrapped function, discarding the cache
+    pub fn into_inner(self) -> F {
+        self.inner
+    }
+
+    /// Returns the number of queries answered from the cache
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of queries answered by the wrapped function
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Forgets all cached results
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            shard.lock().unwrap().entries.clear();
+        }
+    }
+}
+
+impl<F: PhfQuery> PhfQuery for CachedPhf<F> {
+    const MINIMAL: bool = F::MINIMAL;
+
+    fn hash(&self, key: impl Hashable) -> u64 {
+        if self.shard_capacity == 0 {
+            return self.inner.hash(key);
+        }
+        let bytes = key.as_bytes();
+        let bytes = bytes.as_ref();
+        let shard_id = self.hash_builder.hash_one(bytes) as usize & (self.shards.len() - 1);
+        let shard = &self.shards[shard_id];
+
+        if let Some(position) = shard.lock().unwrap().get(bytes) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return position;
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        // Don't hold the lock while querying, so other keys of the shard are not blocked
+        let position = self.inner.hash(&key);
+        shard
+            .lock()
+            .unwrap()
+            .insert(bytes, position, self.shard_capacity);
+        position
+    }
+
+    fn num_bits(&self) -> usize {
+        self.inner.num_bits()
+    }
+
+    fn num_keys(&self) -> u64 {
+        self.inner.num_keys()
+    }
+
+    fn table_size(&self) -> u64 {
+        self.inner.table_size()
+    }
+}
@@ -0,0 +1,250 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! C interface, to build and query functions from other languages
+//!
+//! Only available with the `capi` feature. The declarations are in
+//! `include/pthash_rs.h`, and the shared library is built with:
+//!
+//! ```text
+//! cargo rustc --release --features capi --crate-type cdylib
+//! ```
+//!
+//! Functions are non-partitioned, use [`MurmurHash2_64`], and are saved with their
+//! [footer](crate::metadata::PhfMetadata), so files can be shared with Rust programs
+//! loading them with [`load_any`] or [`Phf::load`].
+//!
+//! Functions returning a pointer return `NULL` on error, and functions returning an
+//! `int` return `-1`; [`pthash_last_error`] then returns a description of the error.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::path::Path;
+
+use crate::build::BuildConfiguration;
+use crate::dynamic::{load_any, AnyPhf, DynSinglePhf};
+use crate::encoders::EncoderKind;
+use crate::hashing::MurmurHash2_64;
+use crate::minimality::{Minimal, Nonminimal};
+use crate::Phf;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(error: impl std::fmt::Display) {
+    let message = CString::new(error.to_string().replace('\0', ""))
+        .expect("null bytes were removed from the message");
+    LAST_ERROR.with(|last_error| *last_error.borrow_mut() = Some(message));
+}
+
+/// Function handle, opaque to C (`pthash_phf`)
+pub struct PthashPhf(AnyPhf);
+
+/// Converts a C string to a path, or sets the last error if it is `NULL`
+unsafe fn path_arg<'a>(path: *const c_char) -> Option<&'a Path> {
+    if path.is_null() {
+        set_last_error("path is NULL");
+        return None;
+    }
+    let path = unsafe { CStr::from_ptr(path) };
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Some(Path::new(std::ffi::OsStr::from_bytes(path.to_bytes())))
+    }
+    #[cfg(not(unix))]
+    match path.to_str() {
+        Ok(path) => Some(Path::new(path)),
+        Err(e) => {
+            set_last_error(format!("path is not valid UTF-8: {e}"));
+            None
+        }
+    }
+}
+
+/// Returns the message of the last error in this thread, or `NULL` if there was none
+///
+/// The message is valid until the next call to a `pthash_*` function in this thread.
+#[no_mangle]
+pub extern "C" fn pthash_last_error() -> *const c_char {
+    LAST_ERROR.with(|last_error| {
+        last_error
+            .borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Builds a function from `num_keys` keys, the `i`-th one being the `key_lens[i]`
+/// bytes at `keys[i]`
+///
+/// `encoder` is the name of an [`EncoderKind`] (eg. `"dictionary_dictionary"`), or
+/// `NULL` for the first enabled one. Temporary files are written to the system's
+/// temporary directory.
+///
+/// Functions use [`MurmurHash2_64`], so [`pthash_load`] can load them back.
+///
+/// # Safety
+///
+/// `keys` and `key_lens` must point to `num_keys` elements, each `keys[i]` to
+/// `key_lens[i]` bytes (`keys[i]` may be `NULL` if `key_lens[i]` is 0), and
+/// `encoder` must be `NULL` or a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pthash_build(
+    keys: *const *const u8,
+    key_lens: *const usize,
+    num_keys: usize,
+    minimal: bool,
+    encoder: *const c_char,
+    num_threads: u64,
+) -> *mut PthashPhf {
+    if num_keys > 0 && (keys.is_null() || key_lens.is_null()) {
+        set_last_error("keys or key_lens is NULL");
+        return std::ptr::null_mut();
+    }
+    let encoder = if encoder.is_null() {
+        EncoderKind::ALL[0]
+    } else {
+        let name = unsafe { CStr::from_ptr(encoder) }.to_string_lossy();
+        match EncoderKind::from_name(&name) {
+            Some(encoder) => encoder,
+            None => {
+                set_last_error(format!("unknown or disabled encoder: {name}"));
+                return std::ptr::null_mut();
+            }
+        }
+    };
+    let keys: Result<Vec<&[u8]>, _> = (0..num_keys)
+        .map(|i| {
+            let (key, key_len) = unsafe { (*keys.add(i), *key_lens.add(i)) };
+            if key_len == 0 {
+                Ok(&[][..])
+            } else if key.is_null() {
+                Err(format!("keys[{i}] is NULL but key_lens[{i}] is {key_len}"))
+            } else {
+                Ok(unsafe { std::slice::from_raw_parts(key, key_len) })
+            }
+        })
+        .collect();
+    let keys = match keys {
+        Ok(keys) => keys,
+        Err(e) => {
+            set_last_error(e);
+            return std::ptr::null_mut();
+        }
+    };
+
+    let mut config = BuildConfiguration::default();
+    config.verbose_output = false;
+    config.num_threads = num_threads.max(1);
+    let res = if minimal {
+        let mut f = DynSinglePhf::<Minimal, MurmurHash2_64>::new(encoder);
+        f.build_in_internal_memory_from_bytes(|| &keys, &config)
+            .map(|_| AnyPhf::MinimalSingle64(f))
+    } else {
+        let mut f = DynSinglePhf::<Nonminimal, MurmurHash2_64>::new(encoder);
+        f.build_in_internal_memory_from_bytes(|| &keys, &config)
+            .map(|_| AnyPhf::NonminimalSingle64(f))
+    };
+    match res {
+        Ok(f) => Box::into_raw(Box::new(PthashPhf(f))),
+        Err(e) => {
+            set_last_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Loads a function saved by [`pthash_save`] (or [`Phf::save`])
+///
+/// Like [`load_any`], this only supports functions using [`MurmurHash2_64`] or
+/// `MurmurHash2_128`.
+///
+/// # Safety
+///
+/// `path` must be a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pthash_load(path: *const c_char) -> *mut PthashPhf {
+    let Some(path) = (unsafe { path_arg(path) }) else {
+        return std::ptr::null_mut();
+    };
+    match load_any(path) {
+        Ok(f) => Box::into_raw(Box::new(PthashPhf(f))),
+        Err(e) => {
+            set_last_error(e);
+            std::ptr::null_mut()
+        }
+    }
+}
+
+/// Writes `phf` to `path`, and returns 0
+///
+/// # Safety
+///
+/// `phf` must be a function returned by [`pthash_build`] or [`pthash_load`], and
+/// `path` a null-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn pthash_save(phf: *mut PthashPhf, path: *const c_char) -> c_int {
+    let Some(path) = (unsafe { path_arg(path) }) else {
+        return -1;
+    };
+    match unsafe { &mut *phf }.0.save(path) {
+        Ok(_) => 0,
+        Err(e) => {
+            set_last_error(e);
+            -1
+        }
+    }
+}
+
+/// Returns the hash of the `key_len` bytes at `key`
+///
+/// # Safety
+///
+/// `phf` must be a function returned by [`pthash_build`] or [`pthash_load`], and
+/// `key` must point to `key_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn pthash_hash(phf: *const PthashPhf, key: *const u8, key_len: usize) -> u64 {
+    let key: &[u8] = if key_len == 0 {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(key, key_len) }
+    };
+    unsafe { &*phf }.0.hash(key)
+}
+
+/// Returns the number of keys `phf` was built from
+///
+/// # Safety
+///
+/// `phf` must be a function returned by [`pthash_build`] or [`pthash_load`].
+#[no_mangle]
+pub unsafe extern "C" fn pthash_num_keys(phf: *const PthashPhf) -> u64 {
+    unsafe { &*phf }.0.num_keys()
+}
+
+/// Returns the largest value returned by [`pthash_hash`] plus 1
+///
+/// # Safety
+///
+/// `phf` must be a function returned by [`pthash_build`] or [`pthash_load`].
+#[no_mangle]
+pub unsafe extern "C" fn pthash_table_size(phf: *const PthashPhf) -> u64 {
+    unsafe { &*phf }.0.table_size()
+}
+
+/// Frees a function returned by [`pthash_build`] or [`pthash_load`]; does nothing if
+/// `phf` is `NULL`
+///
+/// # Safety
+///
+/// `phf` must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn pthash_free(phf: *mut PthashPhf) {
+    if !phf.is_null() {
+        drop(unsafe { Box::from_raw(phf) });
+    }
+}
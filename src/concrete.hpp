@@ -46,6 +46,13 @@ namespace pthash_rs {
         concrete(128, partitioned_compact);
         concrete(64, elias_fano);
         concrete(128, elias_fano);
+        concrete(64, compact);
+        concrete(128, compact);
+        concrete(64, dictionary);
+        concrete(128, dictionary);
     }
 
 }
+
+// Encoders listed in the PTHASH_EXTRA_ENCODERS environment variable; generated by build.rs
+#include "extra_concrete.hpp"
@@ -0,0 +1,291 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Loading [`BuildConfiguration`] from files and environment variables, so builds can
+//! be tuned without recompiling
+
+#[cfg(feature = "toml")]
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::build::{BuildConfiguration, CancellationToken};
+
+/// Prefix of environment variables read by [`BuildConfigurationOverrides::from_env`]
+pub const ENV_PREFIX: &str = "PTHASH_";
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Could not read {path}: {source}")]
+    Read {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[cfg(feature = "toml")]
+    #[error("Could not parse {path}: {source}")]
+    Toml {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("Invalid value for {name}: {value:?}")]
+    InvalidVar { name: String, value: String },
+    #[error("{name} must be {expected}, got {value}")]
+    OutOfRange {
+        name: &'static str,
+        value: String,
+        expected: &'static str,
+    },
+}
+
+/// A partial [`BuildConfiguration`], whose fields that are set override the ones of
+/// an existing configuration
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, deny_unknown_fields))]
+pub struct BuildConfigurationOverrides {
+    pub c: Option<f64>,
+    pub alpha: Option<f64>,
+    pub num_partitions: Option<u64>,
+    pub num_buckets: Option<u64>,
+    pub num_threads: Option<u64>,
+    pub seed: Option<u64>,
+    pub ram: Option<u64>,
+    pub tmp_dir: Option<PathBuf>,
+    pub verbose_output: Option<bool>,
+    pub table_size: Option<u64>,
+    pub deduplicate: Option<bool>,
+    pub check_collisions: Option<bool>,
+    pub check_duplicates: Option<bool>,
+    pub verify: Option<bool>,
+}
+
+impl BuildConfigurationOverrides {
+    /// Reads overrides from a TOML file, whose keys are the names of
+    /// [`BuildConfiguration`]'s fields
+    #[cfg(feature = "toml")]
+    pub fn from_toml(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|source| ConfigError::Read {
+            path: path.to_owned(),
+            source,
+        })?;
+        toml::from_str(&contents).map_err(|source| ConfigError::Toml {
+            path: path.to_owned(),
+            source,
+        })
+    }
+
+    /// Reads overrides from environment variables named after
+    /// [`BuildConfiguration`]'s fields in uppercase, prefixed with [`ENV_PREFIX`]
+    /// (eg. `PTHASH_NUM_THREADS`)
+    pub fn from_env() -> Result<Self, ConfigError> {
+        Self::from_vars(std::env::vars())
+    }
+
+    /// Same as [`Self::from_env`], but reads variables from the given `(name, value)`
+    /// pairs; those not starting with [`ENV_PREFIX`] are ignored
+    pub fn from_vars(
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
+        fn parse<T: FromStr>(name: &str, value: &str) -> Result<Option<T>, ConfigError> {
+            value
+                .parse()
+                .map(Some)
+                .map_err(|_| ConfigError::InvalidVar {
+                    name: name.to_owned(),
+                    value: value.to_owned(),
+                })
+        }
+
+        let mut overrides = Self::default();
+        for (name, value) in vars {
+            let Some(field) = name.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            match field {
+                "C" => overrides.c = parse(&name, &value)?,
+                "ALPHA" => overrides.alpha = parse(&name, &value)?,
+                "NUM_PARTITIONS" => overrides.num_partitions = parse(&name, &value)?,
+                "NUM_BUCKETS" => overrides.num_buckets = parse(&name, &value)?,
+                "NUM_THREADS" => overrides.num_threads = parse(&name, &value)?,
+                "SEED" => overrides.seed = parse(&name, &value)?,
+                "RAM" => overrides.ram = parse(&name, &value)?,
+                "TMP_DIR" => overrides.tmp_dir = Some(value.into()),
+                "VERBOSE_OUTPUT" => overrides.verbose_output = parse(&name, &value)?,
+                "TABLE_SIZE" => overrides.table_size = parse(&name, &value)?,
+                "DEDUPLICATE" => overrides.deduplicate = parse(&name, &value)?,
+                "CHECK_COLLISIONS" => overrides.check_collisions = parse(&name, &value)?,
+                "CHECK_DUPLICATES" => overrides.check_duplicates = parse(&name, &value)?,
+                "VERIFY" => overrides.verify = parse(&name, &value)?,
+                // Not a configuration variable (eg. PTHASH_EXTRA_ENCODERS)
+                _ => {}
+            }
+        }
+        Ok(overrides)
+    }
+
+    /// Sets the fields of `config` that are set in `self`
+    pub fn apply(&self, config: &mut BuildConfiguration) {
+        let BuildConfigurationOverrides {
+            c,
+            alpha,
+            num_partitions,
+            num_buckets,
+            num_threads,
+            seed,
+            ram,
+            tmp_dir,
+            verbose_output,
+            table_size,
+            deduplicate,
+            check_collisions,
+            check_duplicates,
+            verify,
+        } = self.clone();
+        config.c = c.unwrap_or(config.c);
+        config.alpha = alpha.unwrap_or(config.alpha);
+        config.num_partitions = num_partitions.unwrap_or(config.num_partitions);
+        config.num_buckets = num_buckets.unwrap_or(config.num_buckets);
+        config.num_threads = num_threads.unwrap_or(config.num_threads);
+        config.seed = seed.unwrap_or(config.seed);
+        config.ram = ram.unwrap_or(config.ram);
+        if let Some(tmp_dir) = tmp_dir {
+            config.tmp_dir = tmp_dir;
+        }
+        config.verbose_output = verbose_output.unwrap_or(config.verbose_output);
+        if table_size.is_some() {
+            config.table_size = table_size;
+        }
+        config.deduplicate = deduplicate.unwrap_or(config.deduplicate);
+        config.check_collisions = check_collisions.unwrap_or(config.check_collisions);
+        config.check_duplicates = check_duplicates.unwrap_or(config.check_duplicates);
+        config.verify = verify.unwrap_or(config.verify);
+    }
+
+    /// Returns the default configuration with these overrides applied, using the
+    /// system's temporary directory if `tmp_dir` is not set
+    pub fn to_configuration(&self) -> BuildConfiguration {
+        let mut config = BuildConfiguration::default();
+        self.apply(&mut config);
+        config
+    }
+}
+
+impl BuildConfiguration {
+    /// Returns the default configuration, overridden by the given TOML file
+    ///
+    /// See [`BuildConfigurationOverrides::from_toml`]
+    #[cfg(feature = "toml")]
+    pub fn from_toml(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        BuildConfigurationOverrides::from_toml(path).map(|overrides| overrides.to_configuration())
+    }
+
+    /// Returns the default configuration, overridden by environment variables
+    ///
+    /// See [`BuildConfigurationOverrides::from_env`]
+    pub fn from_env() -> Result<Self, ConfigError> {
+        BuildConfigurationOverrides::from_env().map(|overrides| overrides.to_configuration())
+    }
+}
+
+/// Builder of [`BuildConfiguration`], which checks parameters are in the ranges
+/// PTHash supports when [building](Self::build) the configuration
+///
+/// Otherwise, invalid parameters only make builds fail, or PTHash throw exceptions.
+#[derive(Clone, Debug)]
+pub struct BuildConfigurationBuilder {
+    config: BuildConfiguration,
+}
+
+macro_rules! setters {
+    ($($(#[$attr:meta])* $name:ident: $ty:ty,)*) => {$(
+        $(#[$attr])*
+        #[doc = concat!("Sets [`BuildConfiguration::", stringify!($name), "`]")]
+        pub fn $name(mut self, $name: $ty) -> Self {
+            self.config.$name = $name;
+            self
+        }
+    )*};
+}
+
+impl BuildConfigurationBuilder {
+    /// Starts from the default configuration, see [`BuildConfiguration::new`]
+    pub fn new(tmp_dir: PathBuf) -> Self {
+        BuildConfigurationBuilder {
+            config: BuildConfiguration::new(tmp_dir),
+        }
+    }
+
+    setters! {
+        c: f64,
+        alpha: f64,
+        num_partitions: u64,
+        num_buckets: u64,
+        num_threads: u64,
+        seed: u64,
+        ram: u64,
+        verbose_output: bool,
+        table_size: Option<u64>,
+        deduplicate: bool,
+        check_collisions: bool,
+        check_duplicates: bool,
+        verify: bool,
+        cancellation: Option<CancellationToken>,
+    }
+
+    /// Returns the configuration, or an error if a parameter is out of the range
+    /// PTHash supports:
+    ///
+    /// * `0 < alpha < 1` (`alpha = 1` would make the search for pilots unbounded;
+    ///   minimal functions should be used instead)
+    /// * `c > 0`
+    /// * `num_threads ≥ 1`
+    /// * `num_partitions ≥ 1`
+    pub fn build(self) -> Result<BuildConfiguration, ConfigError> {
+        let config = self.config;
+        let check = |name, valid: bool, value: String, expected| {
+            if valid {
+                Ok(())
+            } else {
+                Err(ConfigError::OutOfRange {
+                    name,
+                    value,
+                    expected,
+                })
+            }
+        };
+        let alpha_is_valid = config.alpha > 0. && config.alpha < 1.;
+        check(
+            "alpha",
+            alpha_is_valid,
+            config.alpha.to_string(),
+            "in (0, 1)",
+        )?;
+        check("c", config.c > 0., config.c.to_string(), "positive")?;
+        check(
+            "num_threads",
+            config.num_threads >= 1,
+            config.num_threads.to_string(),
+            "at least 1",
+        )?;
+        check(
+            "num_partitions",
+            config.num_partitions >= 1,
+            config.num_partitions.to_string(),
+            "at least 1",
+        )?;
+        Ok(config)
+    }
+}
+
+impl BuildConfiguration {
+    /// Returns a builder starting from the default configuration, which validates
+    /// parameters
+    pub fn builder(tmp_dir: PathBuf) -> BuildConfigurationBuilder {
+        BuildConfigurationBuilder::new(tmp_dir)
+    }
+}
@@ -5,10 +5,24 @@
 
 #pragma once
 
+#include <algorithm>
+#include <cstring>
+#include <fstream>
+#include <iterator>
 #include <memory>
+#include <stdexcept>
+#include <string>
+#include <type_traits>
+#include <vector>
+
+#if defined(__GLIBC__)
+#include <malloc.h>
+#endif
 
 #include <pthash.hpp>
 
+#include "rust/cxx.h"
+
 #define getter(name) \
     template<typename T, typename Ret> \
     Ret \
@@ -84,9 +98,479 @@ namespace pthash_rs {
         bool valid_seed(T seed) {
             return seed != ::pthash::constants::invalid_seed;
         }
+
+        template<typename T> // Force C++ compiler to inline it
+        bool valid_num_buckets(T num_buckets) {
+            return num_buckets != ::pthash::constants::invalid_num_buckets;
+        }
+
+        // Appends data at the end of a file, eg. after a data structure written by
+        // essentials::save, which essentials::load ignores
+        inline size_t append_bytes(char const* filename, rust::Slice<const uint8_t> data) {
+            std::ofstream out(filename, std::ios::binary | std::ios::app);
+            out.write(reinterpret_cast<char const*>(data.data()), data.size());
+            out.close();
+            if (out.fail()) {
+                throw std::runtime_error("could not append to " + std::string(filename));
+            }
+            return data.size();
+        }
+
+        // Type of the argument of T::position, ie. pthash::hash64 or pthash::hash128
+        template<typename T, typename Hash>
+        Hash position_argument(uint64_t (T::*)(Hash) const);
+
+        // Writes the position of each hash to `positions`. Hashes are passed as
+        // their 64-bit words, because cxx does not support slices of opaque types.
+        template<typename T>
+        void positions(T const &obj,
+                       rust::Slice<const uint64_t> hashes,
+                       rust::Slice<uint64_t> positions) {
+            typedef decltype(position_argument(&T::position)) hash_type;
+            constexpr size_t words_per_hash = sizeof(hash_type) / sizeof(uint64_t);
+            if (hashes.size() != positions.size() * words_per_hash) {
+                throw std::invalid_argument("number of hashes and positions differ");
+            }
+            for (size_t i = 0; i < positions.size(); i++) {
+                hash_type hash;
+                std::memcpy(&hash, hashes.data() + i * words_per_hash, sizeof(hash_type));
+                positions[i] = obj.position(hash);
+            }
+        }
+
+        // Plain values, which own no allocation
+        template<typename T>
+        constexpr bool is_plain_v =
+            std::is_trivially_copyable_v<T> && std::is_standard_layout_v<T>;
+
+        // Number of bytes the allocator reserved for the allocation of `len` bytes at
+        // `ptr` (or nullptr if nothing was allocated) by std::allocator: with glibc,
+        // this includes rounding up to its size classes, and elsewhere it is just `len`
+        inline size_t allocated_bytes(void const* ptr, size_t len) {
+#if defined(__GLIBC__)
+            if (ptr != nullptr) {
+                return malloc_usable_size(const_cast<void*>(ptr));
+            }
+#endif
+            (void)ptr;
+            return len;
+        }
+
+        // Visitor summing the memory allocated by vectors of a data structure,
+        // including their unused capacity, and the allocator's rounding of each
+        // allocation where allocated_bytes() can query it
+        struct memory_usage_visitor {
+            size_t bytes = 0;
+
+            template<typename T>
+            void visit(T &val) {
+                // Plain values are counted in their parent's size
+                if constexpr (!is_plain_v<T>) {
+                    val.visit(*this);
+                }
+            }
+
+            template<typename T, typename Allocator>
+            void visit(std::vector<T, Allocator> &vec) {
+                size_t len = vec.capacity() * sizeof(T);
+                if constexpr (std::is_same_v<Allocator, std::allocator<T>>) {
+                    bytes += allocated_bytes(vec.data(), len);
+                } else {
+                    bytes += len;
+                }
+                if constexpr (!is_plain_v<T>) {
+                    for (auto &val : vec) {
+                        val.visit(*this);
+                    }
+                }
+            }
+        };
+
+        // Visitor serializing a data structure to a buffer of its serialized size
+        // (see serialized_size_visitor), in the same format as essentials::save.
+        // Like essentials, it writes std::is_pod values as raw bytes, as other
+        // conditions would change the format.
+        struct bytes_saver {
+            rust::Slice<uint8_t> bytes;
+            size_t pos = 0;
+
+            void write(void const* data, size_t len) {
+                if (len > bytes.size() - pos) {
+                    throw std::length_error("serialized data larger than expected");
+                }
+                auto ptr = reinterpret_cast<uint8_t const*>(data);
+                std::copy_n(ptr, len, bytes.data() + pos);
+                pos += len;
+            }
+
+            template<typename T>
+            void visit(T &val) {
+                if constexpr (std::is_pod<T>::value) {
+                    write(&val, sizeof(T));
+                } else {
+                    val.visit(*this);
+                }
+            }
+
+            template<typename T, typename Allocator>
+            void visit(std::vector<T, Allocator> &vec) {
+                size_t n = vec.size();
+                visit(n);
+                if constexpr (std::is_pod<T>::value) {
+                    write(vec.data(), sizeof(T) * n);
+                } else {
+                    for (auto &val : vec) {
+                        visit(val);
+                    }
+                }
+            }
+        };
+
+        // Visitor deserializing a data structure written by bytes_saver or
+        // essentials::save
+        struct bytes_loader {
+            rust::Slice<const uint8_t> bytes;
+            size_t offset = 0;
+
+            void read(void* data, size_t len) {
+                if (len > bytes.size() - offset) {
+                    throw std::runtime_error("unexpected end of data");
+                }
+                std::memcpy(data, bytes.data() + offset, len);
+                offset += len;
+            }
+
+            template<typename T>
+            void visit(T &val) {
+                if constexpr (std::is_pod<T>::value) {
+                    read(&val, sizeof(T));
+                } else {
+                    val.visit(*this);
+                }
+            }
+
+            template<typename T, typename Allocator>
+            void visit(std::vector<T, Allocator> &vec) {
+                size_t n;
+                visit(n);
+                if constexpr (std::is_pod<T>::value) {
+                    if (n > (bytes.size() - offset) / sizeof(T)) {
+                        throw std::runtime_error("unexpected end of data");
+                    }
+                    vec.resize(n);
+                    read(vec.data(), sizeof(T) * n);
+                } else {
+                    // Each element takes at least one byte, so this does not allocate
+                    // an arbitrarily large vector from a corrupted size
+                    if (n > bytes.size() - offset) {
+                        throw std::runtime_error("unexpected end of data");
+                    }
+                    vec.resize(n);
+                    for (auto &val : vec) {
+                        visit(val);
+                    }
+                }
+            }
+        };
+
+        template<typename T, typename = void>
+        struct has_num_buckets : std::false_type {};
+        template<typename T>
+        struct has_num_buckets<T, std::void_t<decltype(std::declval<T const &>().num_buckets())>>
+            : std::true_type {};
+
+        // Visitor checking the invariants queries of a pthash::single_phf rely on,
+        // so corrupted data yields an error instead of out-of-bounds reads. Fields
+        // are visited in the order of single_phf::visit: seed, num_keys, table_size,
+        // M, bucketer, pilots, free_slots. The internal structure of encoders is not
+        // checked.
+        struct single_phf_validator {
+            size_t field = 0;
+            uint64_t num_keys = 0;
+            uint64_t table_size = 0;
+            uint64_t num_buckets = 0;
+            uint64_t num_pilots = 0;
+            uint64_t num_free_slots = 0;
+
+            template<typename T>
+            void visit(T &val) {
+                if constexpr (std::is_same<T, uint64_t>::value) {
+                    if (field == 1) {
+                        num_keys = val;
+                    } else if (field == 2) {
+                        table_size = val;
+                    }
+                } else if constexpr (has_num_buckets<T>::value) {
+                    num_buckets = val.num_buckets();
+                } else if constexpr (!std::is_pod<T>::value) {
+                    if (field == 5) {
+                        num_pilots = val.size();
+                    } else if (field == 6) {
+                        num_free_slots = val.size();
+                    }
+                }
+                field++;
+            }
+
+            void check(bool minimal) const {
+                if (table_size == 0 || table_size < num_keys) {
+                    throw std::runtime_error("invalid table size");
+                }
+                if (num_buckets > num_pilots) {
+                    throw std::runtime_error("fewer pilots than buckets");
+                }
+                if (minimal && num_free_slots < table_size - num_keys) {
+                    throw std::runtime_error("fewer free slots than positions past the keys");
+                }
+            }
+        };
+
+        // Visitor checking the invariants of a pthash::single_phf, or of a
+        // pthash::partitioned_phf and each of its partitions
+        struct validation_visitor {
+            uint64_t num_partitions = 0;
+
+            template<typename Hasher, typename Encoder, bool Minimal>
+            void visit(::pthash::single_phf<Hasher, Encoder, Minimal> &f) {
+                single_phf_validator fields;
+                f.visit(fields);
+                fields.check(Minimal);
+            }
+
+            // Fields of partitioned_phf and its partitions other than single_phf
+            template<typename T>
+            void visit(T &val) {
+                if constexpr (has_num_buckets<T>::value) {
+                    // Bucketer assigning keys to partitions
+                    num_partitions = val.num_buckets();
+                } else if constexpr (!std::is_pod<T>::value) {
+                    val.visit(*this);
+                }
+            }
+
+            template<typename T, typename Allocator>
+            void visit(std::vector<T, Allocator> &vec) {
+                if constexpr (!std::is_pod<T>::value) {
+                    if (num_partitions > vec.size()) {
+                        throw std::runtime_error("fewer partitions than buckets");
+                    }
+                    for (auto &val : vec) {
+                        visit(val);
+                    }
+                }
+            }
+        };
+
+        template<typename Hasher, typename Encoder, bool Minimal>
+        void validate(::pthash::single_phf<Hasher, Encoder, Minimal> &f) {
+            validation_visitor visitor;
+            visitor.visit(f);
+        }
+
+        template<typename Hasher, typename Encoder, bool Minimal>
+        void validate(::pthash::partitioned_phf<Hasher, Encoder, Minimal> &f) {
+            validation_visitor visitor;
+            f.visit(visitor);
+        }
+
+        // Writes obj to `bytes`, which must be serialized_size(obj) bytes long
+        template<typename T>
+        void save_bytes(T const &obj, rust::Slice<uint8_t> bytes) {
+            bytes_saver saver{bytes};
+            // bytes_saver does not mutate, but PTHash's visit() is not const
+            const_cast<T &>(obj).visit(saver);
+            if (saver.pos != bytes.size()) {
+                throw std::length_error("serialized data smaller than expected");
+            }
+        }
+
+        // Returns the number of bytes read, trailing bytes are ignored like
+        // essentials::load does
+        template<typename T>
+        size_t load_bytes(T &obj, rust::Slice<const uint8_t> bytes) {
+            bytes_loader loader{bytes};
+            obj.visit(loader);
+            validate(obj);
+            return loader.offset;
+        }
+
+        // Same as essentials::load, but reads the whole file first, so it is
+        // checked like by load_bytes instead of trusting sizes read from the file
+        template<typename T>
+        size_t load_file(T &obj, char const* filename) {
+            std::ifstream in(filename, std::ios::binary);
+            if (!in.good()) {
+                throw std::runtime_error("could not open " + std::string(filename));
+            }
+            std::vector<uint8_t> bytes{std::istreambuf_iterator<char>(in),
+                                       std::istreambuf_iterator<char>()};
+            if (in.bad()) {
+                throw std::runtime_error("could not read " + std::string(filename));
+            }
+            return load_bytes(obj, rust::Slice<const uint8_t>(bytes.data(), bytes.size()));
+        }
+
+        // Visitor summing the number of bytes written by bytes_saver
+        struct serialized_size_visitor {
+            size_t bytes = 0;
+
+            template<typename T>
+            void visit(T &val) {
+                if constexpr (std::is_pod<T>::value) {
+                    bytes += sizeof(T);
+                } else {
+                    val.visit(*this);
+                }
+            }
+
+            template<typename T, typename Allocator>
+            void visit(std::vector<T, Allocator> &vec) {
+                bytes += sizeof(size_t);
+                if constexpr (std::is_pod<T>::value) {
+                    bytes += sizeof(T) * vec.size();
+                } else {
+                    for (auto &val : vec) {
+                        visit(val);
+                    }
+                }
+            }
+        };
+
+        // Visitor summing the serialized size (in bits) of the components of
+        // pthash::single_phf objects, including the partitions of a
+        // pthash::partitioned_phf: [overhead, bucketer, pilots, free slots]
+        struct num_bits_breakdown_visitor {
+            uint64_t bits[4] = {0, 0, 0, 0};
+
+            // Visits the fields of a single_phf, in the order of single_phf::visit:
+            // seed, num_keys, table_size, M, bucketer, pilots, free_slots
+            struct fields_visitor {
+                uint64_t *bits;
+                size_t field = 0;
+
+                template<typename T>
+                void visit(T &val) {
+                    serialized_size_visitor size;
+                    size.visit(val);
+                    switch (field) {
+                        case 4: bits[1] += 8 * size.bytes; break;
+                        case 5: bits[2] += 8 * size.bytes; break;
+                        case 6: bits[3] += 8 * size.bytes; break;
+                        default: bits[0] += 8 * size.bytes;
+                    }
+                    field++;
+                }
+            };
+
+            template<typename Hasher, typename Encoder, bool Minimal>
+            void visit(::pthash::single_phf<Hasher, Encoder, Minimal> &f) {
+                fields_visitor fields{bits};
+                f.visit(fields);
+            }
+
+            // Fields of partitioned_phf and its partitions other than single_phf
+            template<typename T>
+            void visit(T &val) {
+                if constexpr (std::is_pod<T>::value) {
+                    bits[0] += 8 * sizeof(T);
+                } else {
+                    val.visit(*this);
+                }
+            }
+
+            template<typename T, typename Allocator>
+            void visit(std::vector<T, Allocator> &vec) {
+                bits[0] += 8 * sizeof(size_t);
+                if constexpr (std::is_pod<T>::value) {
+                    bits[0] += 8 * sizeof(T) * vec.size();
+                } else {
+                    for (auto &val : vec) {
+                        visit(val);
+                    }
+                }
+            }
+        };
+
+        template<typename T>
+        rust::Vec<uint64_t> num_bits_breakdown(T const &obj) {
+            num_bits_breakdown_visitor visitor;
+            // The visitor does not mutate, but PTHash's visit() is not const
+            visitor.visit(const_cast<T &>(obj));
+            rust::Vec<uint64_t> bits;
+            for (auto component_bits : visitor.bits) {
+                bits.push_back(component_bits);
+            }
+            return bits;
+        }
+
+        // Visitor collecting the offsets of the partitions of a
+        // pthash::partitioned_phf, ie. the first position of each partition; these
+        // are the 64-bit integers inside its vector of partitions, outside their
+        // single_phf.
+        struct partition_offsets_visitor {
+            rust::Vec<uint64_t> offsets;
+            bool in_partitions = false;
+
+            template<typename Hasher, typename Encoder, bool Minimal>
+            void visit(::pthash::single_phf<Hasher, Encoder, Minimal> &) {}
+
+            template<typename T>
+            void visit(T &val) {
+                if constexpr (std::is_same<T, uint64_t>::value) {
+                    if (in_partitions) {
+                        offsets.push_back(val);
+                    }
+                } else if constexpr (!std::is_pod<T>::value) {
+                    val.visit(*this);
+                }
+            }
+
+            template<typename T, typename Allocator>
+            void visit(std::vector<T, Allocator> &vec) {
+                if constexpr (!std::is_pod<T>::value) {
+                    in_partitions = true;
+                    for (auto &val : vec) {
+                        visit(val);
+                    }
+                    in_partitions = false;
+                }
+            }
+        };
+
+        // Returns the offsets of the partitions of a pthash::partitioned_phf, or an
+        // empty vector for a pthash::single_phf
+        template<typename T>
+        rust::Vec<uint64_t> partition_offsets(T const &obj) {
+            partition_offsets_visitor visitor;
+            // The visitor does not mutate, but PTHash's visit() is not const
+            const_cast<T &>(obj).visit(visitor);
+            return std::move(visitor.offsets);
+        }
+
+        template<typename T>
+        size_t serialized_size(T const &obj) {
+            serialized_size_visitor visitor;
+            // serialized_size_visitor does not mutate, but PTHash's visit() is not const
+            const_cast<T &>(obj).visit(visitor);
+            return visitor.bytes;
+        }
+
+        template<typename T>
+        size_t memory_usage(T const &obj) {
+            memory_usage_visitor visitor;
+            // memory_usage_visitor does not mutate, but PTHash's visit() is not const
+            const_cast<T &>(obj).visit(visitor);
+            return sizeof(T) + visitor.bytes;
+        }
     }
 
 
+    // Rust definitions of these structures are in structs.rs
+    static_assert(sizeof(::pthash::hash64) == 8, "pthash::hash64 changed");
+    static_assert(sizeof(::pthash::hash128) == 16, "pthash::hash128 changed");
+    static_assert(sizeof(::pthash::build_timings) == 4 * sizeof(double),
+                  "pthash::build_timings changed");
+
     namespace accessors {
         gettersetter(c)
         gettersetter(alpha)
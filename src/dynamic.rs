@@ -0,0 +1,600 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Functions whose encoder is picked at runtime ([`DynSinglePhf`] and
+//! [`DynPartitionedPhf`]), eg. from a command-line flag or a configuration file,
+//! and functions whose type is read from the file they are loaded from ([`AnyPhf`])
+
+use std::any::Any;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use cxx::Exception;
+#[cfg(all(feature = "build", feature = "rayon"))]
+use rayon::prelude::*;
+
+use crate::build::BuildConfiguration;
+#[cfg(feature = "build")]
+use crate::build::{BuildError, BuildTimings};
+use crate::encoders::*;
+use crate::hashing::*;
+pub use crate::metadata::LoadError;
+use crate::metadata::PhfMetadata;
+use crate::minimality::*;
+use crate::{NumBitsBreakdown, PartitionedPhf, Phf, PhfQuery, SinglePhf};
+
+/// Calls `$body` with `$f` bound to the function wrapped by `$value`, a variant of
+/// the `$name` enum
+macro_rules! dispatch {
+    ($name:ident, $value:expr, $f:ident => $body:expr) => {
+        match $value {
+            #[cfg(feature = "dictionary_dictionary")]
+            $name::DictionaryDictionary($f) => $body,
+            #[cfg(feature = "partitioned_compact")]
+            $name::PartitionedCompact($f) => $body,
+            #[cfg(feature = "elias_fano")]
+            $name::EliasFano($f) => $body,
+            #[cfg(feature = "compact")]
+            $name::Compact($f) => $body,
+            #[cfg(feature = "dictionary")]
+            $name::Dictionary($f) => $body,
+        }
+    };
+}
+
+macro_rules! dyn_phf {
+    ($name:ident, $phf:ident) => {
+        #[doc = concat!("A [`", stringify!($phf), "`] whose encoder is one of [`EncoderKind`]")]
+        ///
+        /// Its methods mirror the ones of [`Phf`], with the encoder passed to
+        /// [`Self::new`] and [`Self::load`].
+        pub enum $name<M: Minimality, H: Hasher> {
+            #[cfg(feature = "dictionary_dictionary")]
+            DictionaryDictionary($phf<M, H, DictionaryDictionary>),
+            #[cfg(feature = "partitioned_compact")]
+            PartitionedCompact($phf<M, H, PartitionedCompact>),
+            #[cfg(feature = "elias_fano")]
+            EliasFano($phf<M, H, EliasFano>),
+            #[cfg(feature = "compact")]
+            Compact($phf<M, H, Compact>),
+            #[cfg(feature = "dictionary")]
+            Dictionary($phf<M, H, Dictionary>),
+        }
+
+        impl<M: Minimality, H: Hasher> Clone for $name<M, H> {
+            fn clone(&self) -> Self {
+                match self {
+                    #[cfg(feature = "dictionary_dictionary")]
+                    $name::DictionaryDictionary(f) => $name::DictionaryDictionary(f.clone()),
+                    #[cfg(feature = "partitioned_compact")]
+                    $name::PartitionedCompact(f) => $name::PartitionedCompact(f.clone()),
+                    #[cfg(feature = "elias_fano")]
+                    $name::EliasFano(f) => $name::EliasFano(f.clone()),
+                    #[cfg(feature = "compact")]
+                    $name::Compact(f) => $name::Compact(f.clone()),
+                    #[cfg(feature = "dictionary")]
+                    $name::Dictionary(f) => $name::Dictionary(f.clone()),
+                }
+            }
+        }
+
+        impl<M: Minimality, H: Hasher> $name<M, H> {
+            /// Returns an empty function using the given encoder
+            pub fn new(encoder: EncoderKind) -> Self {
+                match encoder {
+                    #[cfg(feature = "dictionary_dictionary")]
+                    EncoderKind::DictionaryDictionary => {
+                        $name::DictionaryDictionary($phf::new())
+                    }
+                    #[cfg(feature = "partitioned_compact")]
+                    EncoderKind::PartitionedCompact => $name::PartitionedCompact($phf::new()),
+                    #[cfg(feature = "elias_fano")]
+                    EncoderKind::EliasFano => $name::EliasFano($phf::new()),
+                    #[cfg(feature = "compact")]
+                    EncoderKind::Compact => $name::Compact($phf::new()),
+                    #[cfg(feature = "dictionary")]
+                    EncoderKind::Dictionary => $name::Dictionary($phf::new()),
+                }
+            }
+
+            /// Returns the encoder used by this function
+            pub fn encoder(&self) -> EncoderKind {
+                match self {
+                    #[cfg(feature = "dictionary_dictionary")]
+                    $name::DictionaryDictionary(_) => EncoderKind::DictionaryDictionary,
+                    #[cfg(feature = "partitioned_compact")]
+                    $name::PartitionedCompact(_) => EncoderKind::PartitionedCompact,
+                    #[cfg(feature = "elias_fano")]
+                    $name::EliasFano(_) => EncoderKind::EliasFano,
+                    #[cfg(feature = "compact")]
+                    $name::Compact(_) => EncoderKind::Compact,
+                    #[cfg(feature = "dictionary")]
+                    $name::Dictionary(_) => EncoderKind::Dictionary,
+                }
+            }
+
+            #[doc = concat!("See [`", stringify!($phf), "::seed`]")]
+            pub fn seed(&self) -> u64 {
+                dispatch!($name, self, f => f.seed())
+            }
+
+            #[doc = concat!("See [`", stringify!($phf), "::alpha`]")]
+            pub fn alpha(&self) -> f64 {
+                dispatch!($name, self, f => f.alpha())
+            }
+
+            #[doc = concat!("See [`", stringify!($phf), "::c`]")]
+            pub fn c(&self) -> Option<f64> {
+                dispatch!($name, self, f => f.c())
+            }
+
+            /// See [`Phf::build_in_internal_memory_from_bytes`]
+            #[cfg(feature = "build")]
+            pub fn build_in_internal_memory_from_bytes<Keys: IntoIterator>(
+                &mut self,
+                keys: impl FnMut() -> Keys,
+                config: &BuildConfiguration,
+            ) -> Result<BuildTimings, BuildError>
+            where
+                <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
+            {
+                dispatch!($name, self, f => f.build_in_internal_memory_from_bytes(keys, config))
+            }
+
+            /// See [`Phf::build_in_internal_memory_from_iter`]
+            #[cfg(feature = "build")]
+            pub fn build_in_internal_memory_from_iter<Keys: IntoIterator>(
+                &mut self,
+                keys: Keys,
+                config: &BuildConfiguration,
+            ) -> Result<BuildTimings, BuildError>
+            where
+                <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
+            {
+                dispatch!($name, self, f => f.build_in_internal_memory_from_iter(keys, config))
+            }
+
+            /// See [`Phf::par_build_in_internal_memory_from_bytes`]
+            #[cfg(all(feature = "build", feature = "rayon"))]
+            pub fn par_build_in_internal_memory_from_bytes<Keys: IntoParallelIterator>(
+                &mut self,
+                keys: impl FnMut() -> Keys,
+                config: &BuildConfiguration,
+            ) -> Result<BuildTimings, BuildError>
+            where
+                <<Keys as IntoParallelIterator>::Iter as ParallelIterator>::Item: Hashable,
+            {
+                dispatch!($name, self, f => f.par_build_in_internal_memory_from_bytes(keys, config))
+            }
+
+            /// See [`Phf::par_build_in_internal_memory_from_bytes_in_pool`]
+            #[cfg(all(feature = "build", feature = "rayon"))]
+            pub fn par_build_in_internal_memory_from_bytes_in_pool<Keys: IntoParallelIterator>(
+                &mut self,
+                pool: &rayon::ThreadPool,
+                keys: impl FnMut() -> Keys + Send,
+                config: &BuildConfiguration,
+            ) -> Result<BuildTimings, BuildError>
+            where
+                <<Keys as IntoParallelIterator>::Iter as ParallelIterator>::Item: Hashable,
+            {
+                dispatch!($name, self, f => {
+                    f.par_build_in_internal_memory_from_bytes_in_pool(pool, keys, config)
+                })
+            }
+
+            /// See [`Phf::build_configuration`]
+            pub fn build_configuration(&self) -> Option<&BuildConfiguration> {
+                dispatch!($name, self, f => f.build_configuration())
+            }
+
+            /// See [`Phf::memory_usage`]
+            pub fn memory_usage(&self) -> usize {
+                dispatch!($name, self, f => f.memory_usage())
+            }
+
+            /// See [`Phf::num_bits_breakdown`]
+            pub fn num_bits_breakdown(&self) -> NumBitsBreakdown {
+                dispatch!($name, self, f => f.num_bits_breakdown())
+            }
+
+            /// See [`Phf::save`]
+            pub fn save(&mut self, path: impl AsRef<Path>) -> Result<usize, Exception> {
+                dispatch!($name, self, f => f.save(path))
+            }
+
+            /// See [`Phf::save_to`]
+            pub fn save_to(&mut self, writer: impl Write) -> std::io::Result<usize> {
+                dispatch!($name, self, f => f.save_to(writer))
+            }
+
+            #[doc = concat!("See [`", stringify!($phf), "::to_bytes`]")]
+            pub fn to_bytes(&self) -> Result<Vec<u8>, Exception> {
+                dispatch!($name, self, f => f.to_bytes())
+            }
+
+            /// Loads a function saved with the given encoder
+            ///
+            /// See [`Phf::load`]
+            pub fn load(path: impl AsRef<Path>, encoder: EncoderKind) -> Result<Self, LoadError> {
+                Ok(match encoder {
+                    #[cfg(feature = "dictionary_dictionary")]
+                    EncoderKind::DictionaryDictionary => {
+                        $name::DictionaryDictionary($phf::load(path)?)
+                    }
+                    #[cfg(feature = "partitioned_compact")]
+                    EncoderKind::PartitionedCompact => $name::PartitionedCompact($phf::load(path)?),
+                    #[cfg(feature = "elias_fano")]
+                    EncoderKind::EliasFano => $name::EliasFano($phf::load(path)?),
+                    #[cfg(feature = "compact")]
+                    EncoderKind::Compact => $name::Compact($phf::load(path)?),
+                    #[cfg(feature = "dictionary")]
+                    EncoderKind::Dictionary => $name::Dictionary($phf::load(path)?),
+                })
+            }
+
+            /// Loads a function written with the given encoder by [`Self::save_to`]
+            ///
+            /// See [`Phf::load_from`]
+            pub fn load_from(reader: impl Read, encoder: EncoderKind) -> std::io::Result<Self> {
+                Ok(match encoder {
+                    #[cfg(feature = "dictionary_dictionary")]
+                    EncoderKind::DictionaryDictionary => {
+                        $name::DictionaryDictionary($phf::load_from(reader)?)
+                    }
+                    #[cfg(feature = "partitioned_compact")]
+                    EncoderKind::PartitionedCompact => {
+                        $name::PartitionedCompact($phf::load_from(reader)?)
+                    }
+                    #[cfg(feature = "elias_fano")]
+                    EncoderKind::EliasFano => $name::EliasFano($phf::load_from(reader)?),
+                    #[cfg(feature = "compact")]
+                    EncoderKind::Compact => $name::Compact($phf::load_from(reader)?),
+                    #[cfg(feature = "dictionary")]
+                    EncoderKind::Dictionary => $name::Dictionary($phf::load_from(reader)?),
+                })
+            }
+
+            /// Returns the wrapped function, for [`AnyPhf::downcast`]
+            fn into_any(self) -> Box<dyn Any>
+            where
+                M: 'static,
+                H: 'static,
+            {
+                dispatch!($name, self, f => Box::new(f) as Box<dyn Any>)
+            }
+        }
+
+        impl<M: Minimality, H: Hasher> PhfQuery for $name<M, H> {
+            const MINIMAL: bool = M::AS_BOOL;
+
+            #[inline(always)]
+            fn hash(&self, key: impl Hashable) -> u64 {
+                dispatch!($name, self, f => f.hash(key))
+            }
+
+            fn hash_many<K: Hashable>(&self, keys: impl IntoIterator<Item = K>) -> Vec<u64> {
+                dispatch!($name, self, f => f.hash_many(keys))
+            }
+
+            fn hash_many_into<K: Hashable>(
+                &self,
+                keys: impl IntoIterator<Item = K>,
+                out: &mut [u64],
+            ) {
+                dispatch!($name, self, f => f.hash_many_into(keys, out))
+            }
+
+            fn num_bits(&self) -> usize {
+                dispatch!($name, self, f => f.num_bits())
+            }
+
+            fn num_keys(&self) -> u64 {
+                dispatch!($name, self, f => f.num_keys())
+            }
+
+            fn table_size(&self) -> u64 {
+                dispatch!($name, self, f => f.table_size())
+            }
+        }
+    };
+}
+
+dyn_phf!(DynSinglePhf, SinglePhf);
+dyn_phf!(DynPartitionedPhf, PartitionedPhf);
+
+/// Size of a function encoded with one of the enabled encoders, reported by
+/// [`DynSinglePhf::build_smallest_in_internal_memory_from_bytes`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EncoderSize {
+    pub encoder: EncoderKind,
+    /// See [`PhfQuery::num_bits`]
+    pub num_bits: usize,
+    /// Time spent encoding pilots with this encoder
+    pub encoding_seconds: Duration,
+}
+
+#[cfg(feature = "build")]
+impl<M: Minimality, H: Hasher> DynSinglePhf<M, H> {
+    /// Builds a function with each of the [enabled encoders](EncoderKind::ALL), and
+    /// returns the smallest one along with the size of each
+    ///
+    /// Pilots are only searched once, then encoded with every encoder, so this takes
+    /// little more time than a single build; but all encodings are kept in memory
+    /// until the smallest one is known.
+    pub fn build_smallest_in_internal_memory_from_bytes<Keys: IntoIterator>(
+        keys: impl FnMut() -> Keys,
+        config: &BuildConfiguration,
+    ) -> Result<(Self, BuildTimings, Vec<EncoderSize>), BuildError>
+    where
+        <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
+    {
+        let (&first, others) = EncoderKind::ALL
+            .split_first()
+            .expect("no encoder is enabled");
+        let mut candidates = Vec::new();
+        let mut f = Self::new(first);
+        let timings = dispatch!(DynSinglePhf, &mut f, f => {
+            f.build_in_internal_memory_from_bytes_with(keys, config, |builder, ffi_config, config| {
+                for &encoder in others {
+                    let mut other = Self::new(encoder);
+                    let encoding_seconds = dispatch!(DynSinglePhf, &mut other, other => {
+                        other.encode_from_builder(builder, ffi_config, config)?
+                    });
+                    candidates.push((other, encoding_seconds));
+                }
+                Ok(())
+            })?
+        });
+        candidates.insert(0, (f, timings.encoding_seconds));
+
+        let sizes = candidates
+            .iter()
+            .map(|(f, encoding_seconds)| EncoderSize {
+                encoder: f.encoder(),
+                num_bits: f.num_bits(),
+                encoding_seconds: *encoding_seconds,
+            })
+            .collect();
+        let (f, _) = candidates
+            .into_iter()
+            .min_by_key(|(f, _)| f.num_bits())
+            .expect("no candidate function");
+        Ok((f, timings, sizes))
+    }
+}
+
+/// Calls `$body` with `$f` bound to the [`DynSinglePhf`] or [`DynPartitionedPhf`]
+/// wrapped by `$value`, an [`AnyPhf`]
+macro_rules! dispatch_any {
+    ($value:expr, $f:ident => $body:expr) => {
+        match $value {
+            #[cfg(all(feature = "minimal", feature = "hash64"))]
+            AnyPhf::MinimalSingle64($f) => $body,
+            #[cfg(all(feature = "minimal", feature = "hash64"))]
+            AnyPhf::MinimalPartitioned64($f) => $body,
+            #[cfg(all(feature = "minimal", feature = "hash128"))]
+            AnyPhf::MinimalSingle128($f) => $body,
+            #[cfg(all(feature = "minimal", feature = "hash128"))]
+            AnyPhf::MinimalPartitioned128($f) => $body,
+            #[cfg(all(feature = "nonminimal", feature = "hash64"))]
+            AnyPhf::NonminimalSingle64($f) => $body,
+            #[cfg(all(feature = "nonminimal", feature = "hash64"))]
+            AnyPhf::NonminimalPartitioned64($f) => $body,
+            #[cfg(all(feature = "nonminimal", feature = "hash128"))]
+            AnyPhf::NonminimalSingle128($f) => $body,
+            #[cfg(all(feature = "nonminimal", feature = "hash128"))]
+            AnyPhf::NonminimalPartitioned128($f) => $body,
+        }
+    };
+}
+
+/// A function loaded by [`load_any`], whose type parameters were read from its file
+///
+/// Only functions using [`MurmurHash2_64`] or [`MurmurHash2_128`] can be loaded this
+/// way; [`load_any`] fails with [`LoadError::Unsupported`] on functions using other
+/// hashers, which must be loaded with their concrete type.
+#[derive(Clone)]
+pub enum AnyPhf {
+    #[cfg(all(feature = "minimal", feature = "hash64"))]
+    MinimalSingle64(DynSinglePhf<Minimal, MurmurHash2_64>),
+    #[cfg(all(feature = "minimal", feature = "hash64"))]
+    MinimalPartitioned64(DynPartitionedPhf<Minimal, MurmurHash2_64>),
+    #[cfg(all(feature = "minimal", feature = "hash128"))]
+    MinimalSingle128(DynSinglePhf<Minimal, MurmurHash2_128>),
+    #[cfg(all(feature = "minimal", feature = "hash128"))]
+    MinimalPartitioned128(DynPartitionedPhf<Minimal, MurmurHash2_128>),
+    #[cfg(all(feature = "nonminimal", feature = "hash64"))]
+    NonminimalSingle64(DynSinglePhf<Nonminimal, MurmurHash2_64>),
+    #[cfg(all(feature = "nonminimal", feature = "hash64"))]
+    NonminimalPartitioned64(DynPartitionedPhf<Nonminimal, MurmurHash2_64>),
+    #[cfg(all(feature = "nonminimal", feature = "hash128"))]
+    NonminimalSingle128(DynSinglePhf<Nonminimal, MurmurHash2_128>),
+    #[cfg(all(feature = "nonminimal", feature = "hash128"))]
+    NonminimalPartitioned128(DynPartitionedPhf<Nonminimal, MurmurHash2_128>),
+}
+
+impl AnyPhf {
+    /// Returns the hash of the given key
+    ///
+    /// See [`PhfQuery::hash`]
+    #[inline(always)]
+    pub fn hash(&self, key: impl Hashable) -> u64 {
+        dispatch_any!(self, f => f.hash(key))
+    }
+
+    /// See [`PhfQuery::hash_many`]
+    pub fn hash_many<K: Hashable>(&self, keys: impl IntoIterator<Item = K>) -> Vec<u64> {
+        dispatch_any!(self, f => f.hash_many(keys))
+    }
+
+    /// See [`PhfQuery::hash_many_into`]
+    pub fn hash_many_into<K: Hashable>(&self, keys: impl IntoIterator<Item = K>, out: &mut [u64]) {
+        dispatch_any!(self, f => f.hash_many_into(keys, out))
+    }
+
+    /// See [`PhfQuery::par_hash_many`]
+    #[cfg(feature = "rayon")]
+    pub fn par_hash_many<K: Hashable + Sync>(&self, keys: &[K]) -> Vec<u64> {
+        dispatch_any!(self, f => f.par_hash_many(keys))
+    }
+
+    /// See [`PhfQuery::num_bits`]
+    pub fn num_bits(&self) -> usize {
+        dispatch_any!(self, f => f.num_bits())
+    }
+
+    /// See [`Phf::num_bits_breakdown`]
+    pub fn num_bits_breakdown(&self) -> NumBitsBreakdown {
+        dispatch_any!(self, f => f.num_bits_breakdown())
+    }
+
+    /// See [`PhfQuery::num_keys`]
+    pub fn num_keys(&self) -> u64 {
+        dispatch_any!(self, f => f.num_keys())
+    }
+
+    /// See [`PhfQuery::table_size`]
+    pub fn table_size(&self) -> u64 {
+        dispatch_any!(self, f => f.table_size())
+    }
+
+    /// Returns the encoder used by the wrapped function
+    pub fn encoder(&self) -> EncoderKind {
+        dispatch_any!(self, f => f.encoder())
+    }
+
+    /// See [`Phf::save`]
+    pub fn save(&mut self, path: impl AsRef<Path>) -> Result<usize, Exception> {
+        dispatch_any!(self, f => f.save(path))
+    }
+
+    /// Returns the seed of the hashes of keys
+    pub fn seed(&self) -> u64 {
+        dispatch_any!(self, f => f.seed())
+    }
+
+    /// See [`SinglePhf::alpha`]
+    pub fn alpha(&self) -> f64 {
+        dispatch_any!(self, f => f.alpha())
+    }
+
+    /// Whether values returned by [`Self::hash`] are in the range `[0; num_keys)`
+    pub fn is_minimal(&self) -> bool {
+        self.metadata().minimal
+    }
+
+    /// Returns the type parameters of the wrapped function
+    pub fn metadata(&self) -> PhfMetadata {
+        let (partitioned, minimal, hash_bits, hasher) = match self {
+            #[cfg(all(feature = "minimal", feature = "hash64"))]
+            AnyPhf::MinimalSingle64(_) => (false, true, 64, MurmurHash2_64::NAME),
+            #[cfg(all(feature = "minimal", feature = "hash64"))]
+            AnyPhf::MinimalPartitioned64(_) => (true, true, 64, MurmurHash2_64::NAME),
+            #[cfg(all(feature = "minimal", feature = "hash128"))]
+            AnyPhf::MinimalSingle128(_) => (false, true, 128, MurmurHash2_128::NAME),
+            #[cfg(all(feature = "minimal", feature = "hash128"))]
+            AnyPhf::MinimalPartitioned128(_) => (true, true, 128, MurmurHash2_128::NAME),
+            #[cfg(all(feature = "nonminimal", feature = "hash64"))]
+            AnyPhf::NonminimalSingle64(_) => (false, false, 64, MurmurHash2_64::NAME),
+            #[cfg(all(feature = "nonminimal", feature = "hash64"))]
+            AnyPhf::NonminimalPartitioned64(_) => (true, false, 64, MurmurHash2_64::NAME),
+            #[cfg(all(feature = "nonminimal", feature = "hash128"))]
+            AnyPhf::NonminimalSingle128(_) => (false, false, 128, MurmurHash2_128::NAME),
+            #[cfg(all(feature = "nonminimal", feature = "hash128"))]
+            AnyPhf::NonminimalPartitioned128(_) => (true, false, 128, MurmurHash2_128::NAME),
+        };
+        PhfMetadata {
+            partitioned,
+            minimal,
+            hash_bits,
+            hasher: hasher.to_owned(),
+            encoder: self.encoder().name().to_owned(),
+        }
+    }
+
+    /// Returns whether `other` has the same type parameters and seed, so both
+    /// functions can be swapped for each other if they were built from the same keys
+    pub fn is_compatible(&self, other: &AnyPhf) -> bool {
+        self.metadata() == other.metadata() && self.seed() == other.seed()
+    }
+
+    /// Returns whether `other` has the same type parameters and serialized
+    /// representation, so both functions return the same hash for every key
+    pub fn is_equivalent(&self, other: &AnyPhf) -> bool {
+        self.is_compatible(other)
+            && match (
+                dispatch_any!(self, f => f.to_bytes()),
+                dispatch_any!(other, f => f.to_bytes()),
+            ) {
+                (Ok(bytes), Ok(other_bytes)) => bytes == other_bytes,
+                _ => false,
+            }
+    }
+
+    /// Returns the wrapped function as a concrete [`Phf`] type
+    ///
+    /// Fails with [`LoadError::TypeMismatch`] if `F` is not the type of the function
+    /// in the file.
+    pub fn downcast<F: Phf + 'static>(self) -> Result<F, LoadError> {
+        let found = self.metadata();
+        dispatch_any!(self, f => f.into_any())
+            .downcast::<F>()
+            .map(|f| *f)
+            .map_err(|_| LoadError::TypeMismatch {
+                expected: std::any::type_name::<F>(),
+                found,
+            })
+    }
+}
+
+/// Loads a function saved by [`Phf::save`], whose type is read from the file's
+/// [footer](PhfMetadata)
+pub fn load_any(path: impl AsRef<Path>) -> Result<AnyPhf, LoadError> {
+    let path = path.as_ref();
+    let metadata = PhfMetadata::read(path)?;
+    let encoder = EncoderKind::from_name(&metadata.encoder)
+        .ok_or_else(|| LoadError::UnknownEncoder(metadata.encoder.clone()))?;
+    let murmur_hasher = match metadata.hash_bits {
+        #[cfg(feature = "hash64")]
+        64 => Some(MurmurHash2_64::NAME),
+        #[cfg(feature = "hash128")]
+        128 => Some(MurmurHash2_128::NAME),
+        _ => None,
+    };
+    if murmur_hasher != Some(metadata.hasher.as_str()) {
+        return Err(LoadError::Unsupported(metadata));
+    }
+    Ok(
+        match (metadata.minimal, metadata.hash_bits, metadata.partitioned) {
+            #[cfg(all(feature = "minimal", feature = "hash64"))]
+            (true, 64, false) => AnyPhf::MinimalSingle64(DynSinglePhf::load(path, encoder)?),
+            #[cfg(all(feature = "minimal", feature = "hash64"))]
+            (true, 64, true) => {
+                AnyPhf::MinimalPartitioned64(DynPartitionedPhf::load(path, encoder)?)
+            }
+            #[cfg(all(feature = "minimal", feature = "hash128"))]
+            (true, 128, false) => AnyPhf::MinimalSingle128(DynSinglePhf::load(path, encoder)?),
+            #[cfg(all(feature = "minimal", feature = "hash128"))]
+            (true, 128, true) => {
+                AnyPhf::MinimalPartitioned128(DynPartitionedPhf::load(path, encoder)?)
+            }
+            #[cfg(all(feature = "nonminimal", feature = "hash64"))]
+            (false, 64, false) => AnyPhf::NonminimalSingle64(DynSinglePhf::load(path, encoder)?),
+            #[cfg(all(feature = "nonminimal", feature = "hash64"))]
+            (false, 64, true) => {
+                AnyPhf::NonminimalPartitioned64(DynPartitionedPhf::load(path, encoder)?)
+            }
+            #[cfg(all(feature = "nonminimal", feature = "hash128"))]
+            (false, 128, false) => AnyPhf::NonminimalSingle128(DynSinglePhf::load(path, encoder)?),
+            #[cfg(all(feature = "nonminimal", feature = "hash128"))]
+            (false, 128, true) => {
+                AnyPhf::NonminimalPartitioned128(DynPartitionedPhf::load(path, encoder)?)
+            }
+            _ => return Err(LoadError::Unsupported(metadata)),
+        },
+    )
+}
@@ -5,7 +5,14 @@
 
 //! Implementations of the last type parameter of [`SinglePhf`](crate::SinglePhf) and
 //! [`PartitionedPhf`](crate::PartitionedPhf) ([`DictionaryDictionary`],
-//! [`PartitionedCompact`], and [`EliasFano`])
+//! [`PartitionedCompact`], [`EliasFano`], and the opt-in [`Compact`] and
+//! [`Dictionary`])
+//!
+//! Other encoders implemented by PTHash can be exposed by setting the
+//! `PTHASH_EXTRA_ENCODERS` environment variable at build time to a comma-separated
+//! list of their C++ names (eg. `PTHASH_EXTRA_ENCODERS=compact_compact`); each is
+//! exposed as a type with the CamelCase version of that name (eg. `CompactCompact`).
+//! They are not part of [`EncoderKind`].
 
 use crate::hashing::Hash;
 #[cfg(feature = "hash128")]
@@ -33,6 +40,61 @@ pub trait Encoder: BackendForEncoderByHash<hash128> {
 }
 // build.rs rejects both hash64 and hash128 being disabled
 
+// Encoders listed in PTHASH_EXTRA_ENCODERS, generated by build.rs
+include!(concat!(env!("OUT_DIR"), "/encoders_codegen.rs.inc"));
+
+/// Runtime equivalent of an [`Encoder`] type
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EncoderKind {
+    #[cfg(feature = "dictionary_dictionary")]
+    DictionaryDictionary,
+    #[cfg(feature = "partitioned_compact")]
+    PartitionedCompact,
+    #[cfg(feature = "elias_fano")]
+    EliasFano,
+    #[cfg(feature = "compact")]
+    Compact,
+    #[cfg(feature = "dictionary")]
+    Dictionary,
+}
+
+impl EncoderKind {
+    /// All encoders enabled at compile time
+    pub const ALL: &'static [EncoderKind] = &[
+        #[cfg(feature = "dictionary_dictionary")]
+        EncoderKind::DictionaryDictionary,
+        #[cfg(feature = "partitioned_compact")]
+        EncoderKind::PartitionedCompact,
+        #[cfg(feature = "elias_fano")]
+        EncoderKind::EliasFano,
+        #[cfg(feature = "compact")]
+        EncoderKind::Compact,
+        #[cfg(feature = "dictionary")]
+        EncoderKind::Dictionary,
+    ];
+
+    /// Same value as [`Encoder::NAME`]
+    pub fn name(self) -> &'static str {
+        match self {
+            #[cfg(feature = "dictionary_dictionary")]
+            EncoderKind::DictionaryDictionary => DictionaryDictionary::NAME,
+            #[cfg(feature = "partitioned_compact")]
+            EncoderKind::PartitionedCompact => PartitionedCompact::NAME,
+            #[cfg(feature = "elias_fano")]
+            EncoderKind::EliasFano => EliasFano::NAME,
+            #[cfg(feature = "compact")]
+            EncoderKind::Compact => Compact::NAME,
+            #[cfg(feature = "dictionary")]
+            EncoderKind::Dictionary => Dictionary::NAME,
+        }
+    }
+
+    /// Returns the encoder whose [`Encoder::NAME`] is `name`, if it is enabled
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|kind| kind.name() == name)
+    }
+}
+
 /// Type trickery to make [`Hash`] implementable
 pub(crate) trait BackendForEncoderByHash<H: Hash> {
     #[cfg(feature = "minimal")]
@@ -172,3 +234,90 @@ mod elias_fano {
 
 #[cfg(feature = "elias_fano")]
 pub use elias_fano::*;
+
+#[cfg(feature = "compact")]
+mod compact {
+    use super::*;
+
+    /// Encoder known as "C" in the PTHash papers, storing each pilot with as many
+    /// bits as the largest one
+    ///
+    /// Lookups read a single value, but this is usually the largest encoder.
+    pub struct Compact;
+    impl Encoder for Compact {
+        const NAME: &'static str = "compact";
+    }
+
+    #[cfg(feature = "hash64")]
+    impl BackendForEncoderByHash<hash64> for Compact {
+        #[cfg(feature = "minimal")]
+        type MinimalSinglePhfBackend = crate::backends::singlephf_64_compact_minimal;
+        #[cfg(feature = "nonminimal")]
+        type NonminimalSinglePhfBackend = crate::backends::singlephf_64_compact_nonminimal;
+        #[cfg(feature = "minimal")]
+        type MinimalPartitionedPhfBackend = crate::backends::partitionedphf_64_compact_minimal;
+        #[cfg(feature = "nonminimal")]
+        type NonminimalPartitionedPhfBackend =
+            crate::backends::partitionedphf_64_compact_nonminimal;
+    }
+
+    #[cfg(feature = "hash128")]
+    impl BackendForEncoderByHash<hash128> for Compact {
+        #[cfg(feature = "minimal")]
+        type MinimalSinglePhfBackend = crate::backends::singlephf_128_compact_minimal;
+        #[cfg(feature = "nonminimal")]
+        type NonminimalSinglePhfBackend = crate::backends::singlephf_128_compact_nonminimal;
+        #[cfg(feature = "minimal")]
+        type MinimalPartitionedPhfBackend = crate::backends::partitionedphf_128_compact_minimal;
+        #[cfg(feature = "nonminimal")]
+        type NonminimalPartitionedPhfBackend =
+            crate::backends::partitionedphf_128_compact_nonminimal;
+    }
+}
+
+#[cfg(feature = "compact")]
+pub use compact::*;
+
+#[cfg(feature = "dictionary")]
+mod dictionary {
+    use super::*;
+
+    /// Encoder known as "D" in the PTHash papers, storing each distinct pilot once
+    /// and each bucket's index in that dictionary
+    ///
+    /// Unlike [`DictionaryDictionary`] it uses a single dictionary for all buckets,
+    /// which saves a branch on lookups.
+    pub struct Dictionary;
+    impl Encoder for Dictionary {
+        const NAME: &'static str = "dictionary";
+    }
+
+    #[cfg(feature = "hash64")]
+    impl BackendForEncoderByHash<hash64> for Dictionary {
+        #[cfg(feature = "minimal")]
+        type MinimalSinglePhfBackend = crate::backends::singlephf_64_dictionary_minimal;
+        #[cfg(feature = "nonminimal")]
+        type NonminimalSinglePhfBackend = crate::backends::singlephf_64_dictionary_nonminimal;
+        #[cfg(feature = "minimal")]
+        type MinimalPartitionedPhfBackend = crate::backends::partitionedphf_64_dictionary_minimal;
+        #[cfg(feature = "nonminimal")]
+        type NonminimalPartitionedPhfBackend =
+            crate::backends::partitionedphf_64_dictionary_nonminimal;
+    }
+
+    #[cfg(feature = "hash128")]
+    impl BackendForEncoderByHash<hash128> for Dictionary {
+        #[cfg(feature = "minimal")]
+        type MinimalSinglePhfBackend = crate::backends::singlephf_128_dictionary_minimal;
+        #[cfg(feature = "nonminimal")]
+        type NonminimalSinglePhfBackend = crate::backends::singlephf_128_dictionary_nonminimal;
+        #[cfg(feature = "minimal")]
+        type MinimalPartitionedPhfBackend = crate::backends::partitionedphf_128_dictionary_minimal;
+        #[cfg(feature = "nonminimal")]
+        type NonminimalPartitionedPhfBackend =
+            crate::backends::partitionedphf_128_dictionary_nonminimal;
+    }
+}
+
+#[cfg(feature = "dictionary")]
+pub use dictionary::*;
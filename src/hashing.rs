@@ -4,7 +4,8 @@
 // See top-level LICENSE file for more information
 
 //! Non-perfect hash algorithms underlying a PHF ([`MurmurHash2_64`] and
-//! [`MurmurHash2_128`])
+//! [`MurmurHash2_128`], `Xxh3_64` and `Xxh3_128` with the `xxh3` feature, SipHash
+//! with the `siphash` feature, and `Blake3Hasher128` with the `blake3` feature)
 
 use crate::encoders::{BackendForEncoderByHash, Encoder};
 #[cfg(feature = "hash128")]
@@ -13,6 +14,9 @@ pub use crate::structs::hash128;
 pub use crate::structs::hash64;
 
 pub(crate) trait Hash: Sized {
+    /// Returns all the bits of this hash, so hashes can be compared
+    fn to_bits(&self) -> u128;
+
     #[cfg(feature = "minimal")]
     type MinimalSinglePhfBackend<E: Encoder>: crate::backends::BackendPhf<Hash = Self>;
     #[cfg(feature = "nonminimal")]
@@ -25,6 +29,10 @@ pub(crate) trait Hash: Sized {
 
 #[cfg(feature = "hash64")]
 impl Hash for hash64 {
+    fn to_bits(&self) -> u128 {
+        self.first().into()
+    }
+
     #[cfg(feature = "minimal")]
     type MinimalSinglePhfBackend<E: Encoder> =
         <E as BackendForEncoderByHash<Self>>::MinimalSinglePhfBackend;
@@ -41,6 +49,10 @@ impl Hash for hash64 {
 
 #[cfg(feature = "hash128")]
 impl Hash for hash128 {
+    fn to_bits(&self) -> u128 {
+        (u128::from(self.first()) << 64) | u128::from(self.second())
+    }
+
     #[cfg(feature = "minimal")]
     type MinimalSinglePhfBackend<E: Encoder> =
         <E as BackendForEncoderByHash<Self>>::MinimalSinglePhfBackend;
@@ -62,6 +74,16 @@ pub trait Hashable {
         Self: 'a;
 
     fn as_bytes(&self) -> Self::Bytes<'_>;
+
+    /// Returns `Some(n)` if [`as_bytes`](Self::as_bytes) is the native-endian
+    /// representation of `n`
+    ///
+    /// This allows hashers to use a faster path for integers, producing the same
+    /// hashes as from the bytes.
+    #[inline(always)]
+    fn as_u64(&self) -> Option<u64> {
+        None
+    }
 }
 
 impl Hashable for [u8] {
@@ -72,6 +94,97 @@ impl Hashable for [u8] {
     }
 }
 
+impl Hashable for Vec<u8> {
+    type Bytes<'a> = &'a [u8];
+
+    fn as_bytes(&self) -> Self::Bytes<'_> {
+        self
+    }
+}
+
+impl<const N: usize> Hashable for [u8; N] {
+    type Bytes<'a> = &'a [u8];
+
+    fn as_bytes(&self) -> Self::Bytes<'_> {
+        self
+    }
+}
+
+/// Hashes the bytes returned by [`OsStr::as_encoded_bytes`](std::ffi::OsStr::as_encoded_bytes)
+///
+/// On Unix, these are the raw bytes of the string. On other platforms, their encoding
+/// is unspecified, so functions should not be shared across platforms.
+impl Hashable for std::ffi::OsStr {
+    type Bytes<'a> = &'a [u8];
+
+    fn as_bytes(&self) -> Self::Bytes<'_> {
+        self.as_encoded_bytes()
+    }
+}
+
+/// Same as the implementation for [`OsStr`](std::ffi::OsStr)
+impl Hashable for std::ffi::OsString {
+    type Bytes<'a> = &'a [u8];
+
+    fn as_bytes(&self) -> Self::Bytes<'_> {
+        self.as_encoded_bytes()
+    }
+}
+
+/// Same as the implementation for [`OsStr`](std::ffi::OsStr)
+impl Hashable for std::path::Path {
+    type Bytes<'a> = &'a [u8];
+
+    fn as_bytes(&self) -> Self::Bytes<'_> {
+        self.as_os_str().as_encoded_bytes()
+    }
+}
+
+/// Same as the implementation for [`OsStr`](std::ffi::OsStr)
+impl Hashable for std::path::PathBuf {
+    type Bytes<'a> = &'a [u8];
+
+    fn as_bytes(&self) -> Self::Bytes<'_> {
+        self.as_os_str().as_encoded_bytes()
+    }
+}
+
+/// Hashes the bytes of the string, without the trailing nul byte
+impl Hashable for std::ffi::CStr {
+    type Bytes<'a> = &'a [u8];
+
+    fn as_bytes(&self) -> Self::Bytes<'_> {
+        self.to_bytes()
+    }
+}
+
+/// Hashes the bytes of the string, without the trailing nul byte
+impl Hashable for std::ffi::CString {
+    type Bytes<'a> = &'a [u8];
+
+    fn as_bytes(&self) -> Self::Bytes<'_> {
+        self.as_c_str().to_bytes()
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Hashable for bytes::Bytes {
+    type Bytes<'a> = &'a [u8];
+
+    fn as_bytes(&self) -> Self::Bytes<'_> {
+        self
+    }
+}
+
+#[cfg(feature = "bytes")]
+impl Hashable for bytes::BytesMut {
+    type Bytes<'a> = &'a [u8];
+
+    fn as_bytes(&self) -> Self::Bytes<'_> {
+        self
+    }
+}
+
 impl<T: Hashable + ?Sized> Hashable for &T {
     type Bytes<'b>
         = T::Bytes<'b>
@@ -81,6 +194,11 @@ impl<T: Hashable + ?Sized> Hashable for &T {
     fn as_bytes(&self) -> Self::Bytes<'_> {
         T::as_bytes(self)
     }
+
+    #[inline(always)]
+    fn as_u64(&self) -> Option<u64> {
+        T::as_u64(self)
+    }
 }
 
 impl Hashable for u64 {
@@ -97,15 +215,66 @@ impl Hashable for u64 {
         let bytes = self.to_be_bytes();
         bytes
     }
+
+    #[inline(always)]
+    fn as_u64(&self) -> Option<u64> {
+        Some(*self)
+    }
 }
 
+macro_rules! impl_hashable_for_tuple {
+    ($($name:ident),+) => {
+        /// Hashes the concatenation of the elements' bytes
+        ///
+        /// This is ambiguous when several elements have variable lengths (eg.
+        /// `(b"ab", b"c")` and `(b"a", b"bc")` collide), so such tuples should have at
+        /// most one element of variable length.
+        impl<$($name: Hashable),+> Hashable for ($($name,)+) {
+            type Bytes<'a>
+                = Vec<u8>
+            where
+                Self: 'a;
+
+            fn as_bytes(&self) -> Self::Bytes<'_> {
+                #[allow(non_snake_case)]
+                let ($($name,)+) = self;
+                let mut bytes = Vec::new();
+                $(bytes.extend_from_slice($name.as_bytes().as_ref());)+
+                bytes
+            }
+        }
+    };
+}
+
+impl_hashable_for_tuple!(A, B);
+impl_hashable_for_tuple!(A, B, C);
+impl_hashable_for_tuple!(A, B, C, D);
+
 /// Trait of generic non-cryptographic hash function, which can be used to back
 /// a PTHash perfect hash function.
 pub trait Hasher {
     #[allow(private_bounds)] // Users shouldn't be able to impl the Hash trait
     type Hash: Hash + Send;
 
+    /// Name of this hasher, written in files by [`Phf::save`](crate::Phf::save) so
+    /// they are not loaded with another hasher
+    ///
+    /// It must be unique, and at most 255 bytes long.
+    const NAME: &'static str;
+
     fn hash(val: impl Hashable, seed: u64) -> Self::Hash;
+
+    /// Appends the hash of each key to `hashes`, in order
+    ///
+    /// Builds and [batch lookups](crate::PhfQuery::hash_many) call this instead of
+    /// [`Self::hash`], so hashers can process several keys at once.
+    fn hash_batch<K: Hashable>(
+        keys: impl Iterator<Item = K>,
+        seed: u64,
+        hashes: &mut Vec<Self::Hash>,
+    ) {
+        hashes.extend(keys.map(|key| Self::hash(key, seed)))
+    }
 }
 
 #[cxx::bridge]
@@ -125,6 +294,30 @@ mod ffi {
     }
 }
 
+#[cfg(feature = "hash64")]
+const M: u64 = 0xc6a4a7935bd1e995;
+#[cfg(feature = "hash64")]
+const R: u32 = 47;
+
+#[cfg(feature = "hash64")]
+/// Rust implementation of `pthash::MurmurHash2_64` for 8-byte keys, avoiding the FFI
+/// call and the loop on bytes
+#[inline(always)]
+fn murmurhash2_64_u64(key: u64, seed: u64) -> u64 {
+    let mut h = seed ^ 8u64.wrapping_mul(M);
+
+    let mut k = key.wrapping_mul(M);
+    k ^= k >> R;
+    k = k.wrapping_mul(M);
+    h ^= k;
+    h = h.wrapping_mul(M);
+
+    h ^= h >> R;
+    h = h.wrapping_mul(M);
+    h ^= h >> R;
+    h
+}
+
 #[cfg(feature = "hash64")]
 /// Implementation of the Murmur2 64-bits hash
 ///
@@ -135,8 +328,12 @@ pub struct MurmurHash2_64;
 #[cfg(feature = "hash64")]
 impl Hasher for MurmurHash2_64 {
     type Hash = hash64;
+    const NAME: &'static str = "murmurhash2_64";
 
     fn hash(val: impl Hashable, seed: u64) -> Self::Hash {
+        if let Some(val) = val.as_u64() {
+            return murmurhash2_64_u64(val, seed).into();
+        }
         let val = val.as_bytes();
         let val = val.as_ref();
         unsafe { ffi::MurmurHash2_64(val.as_ptr() as *const ffi::c_void, val.len(), seed) }.into()
@@ -156,8 +353,16 @@ pub struct MurmurHash2_128;
 #[cfg(feature = "hash128")]
 impl Hasher for MurmurHash2_128 {
     type Hash = hash128;
+    const NAME: &'static str = "murmurhash2_128";
 
     fn hash(val: impl Hashable, seed: u64) -> Self::Hash {
+        if let Some(val) = val.as_u64() {
+            return (
+                murmurhash2_64_u64(val, seed),
+                murmurhash2_64_u64(val, !seed),
+            )
+                .into();
+        }
         let val = val.as_bytes();
         let val = val.as_ref();
         unsafe {
@@ -169,3 +374,178 @@ impl Hasher for MurmurHash2_128 {
         .into()
     }
 }
+
+#[cfg(all(feature = "xxh3", feature = "hash64"))]
+/// Implementation of the 64-bits [XXH3](https://xxhash.com/) hash
+///
+/// This is pure Rust, and faster than [`MurmurHash2_64`] on keys longer than a few
+/// dozen bytes.
+pub struct Xxh3_64;
+
+#[cfg(all(feature = "xxh3", feature = "hash64"))]
+impl Hasher for Xxh3_64 {
+    type Hash = hash64;
+    const NAME: &'static str = "xxh3_64";
+
+    fn hash(val: impl Hashable, seed: u64) -> Self::Hash {
+        xxhash_rust::xxh3::xxh3_64_with_seed(val.as_bytes().as_ref(), seed).into()
+    }
+}
+
+#[cfg(all(feature = "xxh3", feature = "hash128"))]
+/// Implementation of the 128-bits [XXH3](https://xxhash.com/) hash
+///
+/// This is pure Rust, and faster than [`MurmurHash2_128`] on keys longer than a few
+/// dozen bytes.
+pub struct Xxh3_128;
+
+#[cfg(all(feature = "xxh3", feature = "hash128"))]
+impl Hasher for Xxh3_128 {
+    type Hash = hash128;
+    const NAME: &'static str = "xxh3_128";
+
+    fn hash(val: impl Hashable, seed: u64) -> Self::Hash {
+        let hash = xxhash_rust::xxh3::xxh3_128_with_seed(val.as_bytes().as_ref(), seed);
+        ((hash >> 64) as u64, hash as u64).into()
+    }
+}
+
+/// Returns the two 64-bits keys of SipHash hashers for the given seed
+///
+/// They are expanded with SplitMix64, so related seeds do not give related keys.
+#[cfg(all(feature = "siphash", feature = "hash64"))]
+#[inline(always)]
+fn siphash_keys(seed: u64) -> (u64, u64) {
+    let mut state = seed;
+    let mut next = || {
+        state = state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    };
+    (next(), next())
+}
+
+/// Feeds `val` to a SipHash hasher
+#[cfg(all(feature = "siphash", feature = "hash64"))]
+#[inline(always)]
+fn siphash<H: std::hash::Hasher>(mut hasher: H, val: impl Hashable) -> H {
+    hasher.write(val.as_bytes().as_ref());
+    hasher
+}
+
+#[cfg(all(feature = "siphash", feature = "hash64"))]
+/// Implementation of the 64-bits SipHash-1-3 hash, keyed by the seed
+///
+/// Its 128-bits key is derived from the seed, so keys crafted to make builds fail
+/// (by colliding) can only be found by someone who knows the seed. Builds pick a
+/// random seed unless one is [configured](crate::BuildConfiguration::seed); as the
+/// seed is stored in saved functions, they should be kept as secret as the keys.
+///
+/// This is much slower than [`MurmurHash2_64`], and should only be used for keys
+/// chosen by an adversary.
+pub struct SipHash13_64;
+
+#[cfg(all(feature = "siphash", feature = "hash64"))]
+impl Hasher for SipHash13_64 {
+    type Hash = hash64;
+    const NAME: &'static str = "siphash13_64";
+
+    fn hash(val: impl Hashable, seed: u64) -> Self::Hash {
+        use std::hash::Hasher as _;
+
+        let (k0, k1) = siphash_keys(seed);
+        siphash(siphasher::sip::SipHasher13::new_with_keys(k0, k1), val)
+            .finish()
+            .into()
+    }
+}
+
+#[cfg(all(feature = "siphash", feature = "hash64"))]
+/// Implementation of the 64-bits SipHash-2-4 hash, keyed by the seed
+///
+/// This is slower than [`SipHash13_64`], with a larger security margin.
+pub struct SipHash24_64;
+
+#[cfg(all(feature = "siphash", feature = "hash64"))]
+impl Hasher for SipHash24_64 {
+    type Hash = hash64;
+    const NAME: &'static str = "siphash24_64";
+
+    fn hash(val: impl Hashable, seed: u64) -> Self::Hash {
+        use std::hash::Hasher as _;
+
+        let (k0, k1) = siphash_keys(seed);
+        siphash(siphasher::sip::SipHasher24::new_with_keys(k0, k1), val)
+            .finish()
+            .into()
+    }
+}
+
+#[cfg(all(feature = "siphash", feature = "hash128"))]
+/// Implementation of the 128-bits SipHash-1-3 hash, keyed by the seed
+///
+/// See [`SipHash13_64`].
+pub struct SipHash13_128;
+
+#[cfg(all(feature = "siphash", feature = "hash128"))]
+impl Hasher for SipHash13_128 {
+    type Hash = hash128;
+    const NAME: &'static str = "siphash13_128";
+
+    fn hash(val: impl Hashable, seed: u64) -> Self::Hash {
+        use siphasher::sip128::Hasher128;
+
+        let (k0, k1) = siphash_keys(seed);
+        let hash = siphash(siphasher::sip128::SipHasher13::new_with_keys(k0, k1), val).finish128();
+        (hash.h1, hash.h2).into()
+    }
+}
+
+#[cfg(all(feature = "siphash", feature = "hash128"))]
+/// Implementation of the 128-bits SipHash-2-4 hash, keyed by the seed
+///
+/// See [`SipHash13_64`] and [`SipHash24_64`].
+pub struct SipHash24_128;
+
+#[cfg(all(feature = "siphash", feature = "hash128"))]
+impl Hasher for SipHash24_128 {
+    type Hash = hash128;
+    const NAME: &'static str = "siphash24_128";
+
+    fn hash(val: impl Hashable, seed: u64) -> Self::Hash {
+        use siphasher::sip128::Hasher128;
+
+        let (k0, k1) = siphash_keys(seed);
+        let hash = siphash(siphasher::sip128::SipHasher24::new_with_keys(k0, k1), val).finish128();
+        (hash.h1, hash.h2).into()
+    }
+}
+
+#[cfg(feature = "blake3")]
+/// 128-bits hash made of the first 16 bytes of the [BLAKE3](https://github.com/BLAKE3-team/BLAKE3)
+/// cryptographic hash of the key, keyed by the seed
+///
+/// As BLAKE3 is a cryptographic hash, the probability that two distinct keys collide is
+/// `2^-128` for any seed, which makes it suitable for archives whose keys are not known
+/// in advance. This is an order of magnitude slower than [`MurmurHash2_128`].
+pub struct Blake3Hasher128;
+
+#[cfg(feature = "blake3")]
+impl Hasher for Blake3Hasher128 {
+    type Hash = hash128;
+    const NAME: &'static str = "blake3_128";
+
+    fn hash(val: impl Hashable, seed: u64) -> Self::Hash {
+        let mut key = [0; blake3::KEY_LEN];
+        key[..8].copy_from_slice(&seed.to_le_bytes());
+        let hash = blake3::keyed_hash(&key, val.as_bytes().as_ref());
+        let bytes = hash.as_bytes();
+        (
+            u64::from_le_bytes(bytes[0..8].try_into().unwrap()),
+            u64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+        )
+            .into()
+    }
+}
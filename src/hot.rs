@@ -0,0 +1,126 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Atomically replacing a function used by a long-running process
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use arc_swap::ArcSwap;
+use thiserror::Error;
+
+use crate::metadata::LoadError;
+use crate::{Hashable, Phf, PhfQuery};
+
+#[derive(Error, Debug)]
+pub enum ReloadError {
+    #[error("HotPhf was not loaded from a path")]
+    NoPath,
+    #[error("Could not read metadata of {path}: {source}")]
+    Metadata {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Could not load {path}: {source}")]
+    Load { path: PathBuf, source: LoadError },
+}
+
+/// Wrapper around a [`Phf`] which can be replaced while it is being queried
+///
+/// Queries through [`PhfQuery`] use whichever function is current when they start;
+/// use [`Self::load_full`] to run several queries against the same function.
+pub struct HotPhf<F> {
+    current: ArcSwap<F>,
+    path: Option<PathBuf>,
+    modified: Mutex<Option<SystemTime>>,
+}
+
+impl<F: Phf> HotPhf<F> {
+    pub fn new(f: F) -> Self {
+        HotPhf {
+            current: ArcSwap::from_pointee(f),
+            path: None,
+            modified: Mutex::new(None),
+        }
+    }
+
+    /// Loads the function at `path`, which [`Self::reload_if_changed`] watches
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReloadError> {
+        let path = path.as_ref().to_owned();
+        let modified = modified(&path)?;
+        let f = F::load(&path).map_err(|source| ReloadError::Load {
+            path: path.clone(),
+            source,
+        })?;
+        Ok(HotPhf {
+            current: ArcSwap::from_pointee(f),
+            path: Some(path),
+            modified: Mutex::new(Some(modified)),
+        })
+    }
+
+    /// Returns the current function
+    pub fn load_full(&self) -> Arc<F> {
+        self.current.load_full()
+    }
+
+    /// Replaces the current function with `f`, and returns the previous one
+    pub fn swap(&self, f: F) -> Arc<F> {
+        self.current.swap(Arc::new(f))
+    }
+
+    /// Loads the file the function was [loaded](Self::load) from again if its
+    /// modification time changed, and returns whether it did
+    ///
+    /// New files should be moved into place atomically (eg. with
+    /// [`std::fs::rename`]) rather than written in place, so this never reads a
+    /// partially-written function.
+    pub fn reload_if_changed(&self) -> Result<bool, ReloadError> {
+        let path = self.path.as_ref().ok_or(ReloadError::NoPath)?;
+        let mut last_modified = self.modified.lock().unwrap();
+        let modified = modified(path)?;
+        if *last_modified == Some(modified) {
+            return Ok(false);
+        }
+        let f = F::load(path).map_err(|source| ReloadError::Load {
+            path: path.clone(),
+            source,
+        })?;
+        self.swap(f);
+        *last_modified = Some(modified);
+        Ok(true)
+    }
+}
+
+fn modified(path: &Path) -> Result<SystemTime, ReloadError> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|source| ReloadError::Metadata {
+            path: path.to_owned(),
+            source,
+        })
+}
+
+impl<F: Phf> PhfQuery for HotPhf<F> {
+    const MINIMAL: bool = F::MINIMAL;
+
+    #[inline(always)]
+    fn hash(&self, key: impl Hashable) -> u64 {
+        self.current.load().hash(key)
+    }
+
+    fn num_bits(&self) -> usize {
+        self.current.load().num_bits()
+    }
+
+    fn num_keys(&self) -> u64 {
+        self.current.load().num_keys()
+    }
+
+    fn table_size(&self) -> u64 {
+        self.current.load().table_size()
+    }
+}
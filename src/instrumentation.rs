@@ -0,0 +1,156 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Query instrumentation, exported through the [`metrics`](https://crates.io/crates/metrics)
+//! facade
+
+use std::cell::Cell;
+use std::ops::Deref;
+use std::time::Instant;
+
+use metrics::{Counter, Histogram, SharedString};
+
+use crate::{Encoder, Hashable, Hasher, Minimality, PartitionedPhf, PhfQuery};
+
+/// Name of the counter of queries, labelled with `phf` and `region`
+pub const QUERIES_METRIC: &str = "pthash_queries_total";
+/// Name of the histogram of query latencies, in seconds, labelled with `phf`
+pub const QUERY_DURATION_METRIC: &str = "pthash_query_duration_seconds";
+/// Default number of queries per timed query of [`InstrumentedPhf`]
+pub const DEFAULT_DURATION_SAMPLE_INTERVAL: u64 = 64;
+
+thread_local! {
+    /// Number of queries made on this thread through any [`InstrumentedPhf`]
+    static NUM_QUERIES: Cell<u64> = const { Cell::new(0) };
+}
+
+/// Wrapper around a [`PhfQuery`] that records how many queries land in each region
+/// of the output space, and how long they take
+///
+/// With [`Self::new`], regions are `num_regions` equally-sized ranges of
+/// `[0; table_size)`. With [`Self::per_partition`], they are the partitions of a
+/// [`PartitionedPhf`].
+///
+/// Reading the clock costs about as much as a query, so only one query out of
+/// [`DEFAULT_DURATION_SAMPLE_INTERVAL`] on each thread is timed (see
+/// [`Self::with_duration_sample_interval`]).
+///
+/// Metrics are registered with the recorder installed when the wrapper is created.
+pub struct InstrumentedPhf<F: PhfQuery> {
+    inner: F,
+    table_size: u64,
+    /// Region of a position, if they are not equally-sized
+    region_of: Option<fn(&F, u64) -> u64>,
+    duration_sample_interval: u64,
+    queries: Vec<Counter>,
+    duration: Histogram,
+}
+
+impl<F: PhfQuery> InstrumentedPhf<F> {
+    /// Wraps `inner`, labelling its metrics with `phf=name`
+    ///
+    /// # Panics
+    ///
+    /// If `num_regions` is 0
+    pub fn new(inner: F, name: impl Into<SharedString>, num_regions: u64) -> Self {
+        assert!(num_regions > 0, "num_regions must be positive");
+        let name = name.into();
+        let queries = (0..num_regions)
+            .map(|region| {
+                metrics::counter!(
+                    QUERIES_METRIC,
+                    "phf" => name.clone(),
+                    "region" => region.to_string()
+                )
+            })
+            .collect();
+        InstrumentedPhf {
+            table_size: inner.table_size(),
+            inner,
+            region_of: None,
+            duration_sample_interval: DEFAULT_DURATION_SAMPLE_INTERVAL,
+            queries,
+            duration: metrics::histogram!(QUERY_DURATION_METRIC, "phf" => name),
+        }
+    }
+
+    /// Times one query out of `interval` on each thread, instead of
+    /// [`DEFAULT_DURATION_SAMPLE_INTERVAL`]
+    ///
+    /// # Panics
+    ///
+    /// If `interval` is 0
+    pub fn with_duration_sample_interval(mut self, interval: u64) -> Self {
+        assert!(interval > 0, "interval must be positive");
+        self.duration_sample_interval = interval;
+        self
+    }
+
+    /// Returns the wrapped function
+    pub fn inner(&self) -> &F {
+        &self.inner
+    }
+
+    /// Returns the wrapped function, discarding instrumentation
+    pub fn into_inner(self) -> F {
+        self.inner
+    }
+
+    fn region(&self, position: u64) -> usize {
+        if let Some(region_of) = self.region_of {
+            return region_of(&self.inner, position) as usize;
+        }
+        let num_regions = self.queries.len() as u128;
+        let region = (position as u128 * num_regions) / (self.table_size.max(1) as u128);
+        (region as usize).min(self.queries.len() - 1)
+    }
+}
+
+impl<F, M: Minimality, H: Hasher, E: Encoder> InstrumentedPhf<F>
+where
+    F: PhfQuery + Deref<Target = PartitionedPhf<M, H, E>>,
+{
+    /// Wraps `inner`, labelling its metrics with `phf=name`, and counting queries
+    /// in each of its partitions
+    pub fn per_partition(inner: F, name: impl Into<SharedString>) -> Self {
+        let num_partitions = inner.num_partitions();
+        let region_of: fn(&F, u64) -> u64 = |f, position| f.partition_of_position(position);
+        InstrumentedPhf {
+            region_of: Some(region_of),
+            ..Self::new(inner, name, num_partitions)
+        }
+    }
+}
+
+impl<F: PhfQuery> PhfQuery for InstrumentedPhf<F> {
+    const MINIMAL: bool = F::MINIMAL;
+
+    fn hash(&self, key: impl Hashable) -> u64 {
+        let num_queries = NUM_QUERIES.get();
+        NUM_QUERIES.set(num_queries.wrapping_add(1));
+        let position = if num_queries % self.duration_sample_interval == 0 {
+            let start = Instant::now();
+            let position = self.inner.hash(key);
+            self.duration.record(start.elapsed().as_secs_f64());
+            position
+        } else {
+            self.inner.hash(key)
+        };
+        self.queries[self.region(position)].increment(1);
+        position
+    }
+
+    fn num_bits(&self) -> usize {
+        self.inner.num_bits()
+    }
+
+    fn num_keys(&self) -> u64 {
+        self.inner.num_keys()
+    }
+
+    fn table_size(&self) -> u64 {
+        self.table_size
+    }
+}
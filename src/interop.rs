@@ -0,0 +1,55 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Common interface between this crate's perfect-hash functions and other static
+//! functions, such as [`sux`](https://crates.io/crates/sux)'s `VFunc`
+
+use crate::{Hashable, PhfQuery};
+
+/// A static function mapping keys to integers
+///
+/// This is implemented by every [`PhfQuery`] (including references and smart
+/// pointers to [`Phf`](crate::Phf)s), and by `sux::func::VFunc` when the `sux`
+/// feature is enabled, so call sites can be written once and work with either.
+pub trait StaticFunction<K: ?Sized> {
+    /// Returns the value associated to the given key
+    ///
+    /// Like [`PhfQuery::hash`], the result is arbitrary if `key` was not part of the
+    /// set the function was built on.
+    fn get(&self, key: &K) -> u64;
+
+    /// Returns the number of keys the function was built on
+    fn len(&self) -> usize;
+
+    /// Returns whether the function was built on an empty set of keys
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<K: Hashable + ?Sized, F: PhfQuery> StaticFunction<K> for F {
+    #[inline(always)]
+    fn get(&self, key: &K) -> u64 {
+        self.hash(key)
+    }
+
+    fn len(&self) -> usize {
+        self.num_keys()
+            .try_into()
+            .expect("number of keys overflowed usize")
+    }
+}
+
+#[cfg(feature = "sux")]
+impl<T: sux::utils::ToSig + ?Sized> StaticFunction<T> for sux::func::VFunc<T> {
+    #[inline(always)]
+    fn get(&self, key: &T) -> u64 {
+        sux::func::VFunc::get(self, key) as u64
+    }
+
+    fn len(&self) -> usize {
+        sux::func::VFunc::len(self)
+    }
+}
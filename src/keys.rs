@@ -0,0 +1,277 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Sources of keys to build functions from ([`MergedKeyFiles`] and [`KeySnapshot`])
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::hashing::Hashable;
+
+/// Set of files, each containing one key per line sorted in byte order, read as the
+/// sorted union of their keys
+///
+/// This performs a k-way merge of the files on the fly, skipping duplicate keys.
+/// As [`build_in_internal_memory_from_bytes`](crate::Phf::build_in_internal_memory_from_bytes)
+/// may need to iterate on keys several times, files are re-opened every time
+/// [`iter`](Self::iter) is called:
+///
+/// ```no_run
+/// # use pthash::*;
+/// # use pthash::keys::MergedKeyFiles;
+/// # let config = BuildConfiguration::new(std::env::temp_dir());
+/// let keys = MergedKeyFiles::new(["shard1.txt", "shard2.txt"]);
+/// let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+/// f.build_in_internal_memory_from_bytes(
+///     || keys.iter().expect("Could not open keys").map(|key| key.expect("Could not read key")),
+///     &config,
+/// ).expect("Failed to build");
+/// ```
+#[derive(Clone, Debug)]
+pub struct MergedKeyFiles {
+    paths: Vec<PathBuf>,
+}
+
+impl MergedKeyFiles {
+    pub fn new<P: AsRef<Path>>(paths: impl IntoIterator<Item = P>) -> Self {
+        MergedKeyFiles {
+            paths: paths.into_iter().map(|p| p.as_ref().to_owned()).collect(),
+        }
+    }
+
+    /// Opens all files and returns an iterator on their merged keys
+    pub fn iter(&self) -> std::io::Result<MergedKeys<BufReader<File>>> {
+        let readers = self
+            .paths
+            .iter()
+            .map(|path| File::open(path).map(BufReader::new))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        MergedKeys::new(readers)
+    }
+}
+
+/// Iterator on the sorted and deduplicated union of lines of sorted readers
+///
+/// Returned by [`MergedKeyFiles::iter`].
+pub struct MergedKeys<R: BufRead> {
+    readers: Vec<R>,
+    heap: BinaryHeap<Reverse<(Vec<u8>, usize)>>,
+    read_key: fn(&mut R) -> std::io::Result<Option<Vec<u8>>>,
+}
+
+impl<R: BufRead> MergedKeys<R> {
+    pub fn new(readers: Vec<R>) -> std::io::Result<Self> {
+        Self::with_read_key(readers, read_line_key)
+    }
+
+    fn with_read_key(
+        mut readers: Vec<R>,
+        read_key: fn(&mut R) -> std::io::Result<Option<Vec<u8>>>,
+    ) -> std::io::Result<Self> {
+        let mut heap = BinaryHeap::with_capacity(readers.len());
+        for (i, reader) in readers.iter_mut().enumerate() {
+            if let Some(key) = read_key(reader)? {
+                heap.push(Reverse((key, i)));
+            }
+        }
+        Ok(MergedKeys {
+            readers,
+            heap,
+            read_key,
+        })
+    }
+
+    /// Reads the next key of the `i`-th reader into the heap, checking it does not
+    /// come before `previous_key`
+    fn refill(&mut self, i: usize, previous_key: &[u8]) -> std::io::Result<()> {
+        if let Some(key) = (self.read_key)(&mut self.readers[i])? {
+            if key.as_slice() < previous_key {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("input {i} is not sorted"),
+                ));
+            }
+            self.heap.push(Reverse((key, i)));
+        }
+        Ok(())
+    }
+}
+
+impl<R: BufRead> Iterator for MergedKeys<R> {
+    type Item = std::io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((key, i)) = self.heap.pop()?;
+        if let Err(e) = self.refill(i, &key) {
+            return Some(Err(e));
+        }
+        // Skip copies of the same key, from the same reader or others
+        while let Some(Reverse((other_key, _))) = self.heap.peek() {
+            if *other_key != key {
+                break;
+            }
+            let Reverse((_, j)) = self.heap.pop().unwrap();
+            if let Err(e) = self.refill(j, &key) {
+                return Some(Err(e));
+            }
+        }
+        Some(Ok(key))
+    }
+}
+
+/// Reads a line, without its trailing newline
+fn read_line_key(reader: &mut impl BufRead) -> std::io::Result<Option<Vec<u8>>> {
+    let mut key = Vec::new();
+    if reader.read_until(b'\n', &mut key)? == 0 {
+        return Ok(None);
+    }
+    if key.last() == Some(&b'\n') {
+        key.pop();
+    }
+    Ok(Some(key))
+}
+
+/// Reads a key prefixed with its length, as written by [`write_length_prefixed_key`]
+fn read_length_prefixed_key(reader: &mut impl BufRead) -> std::io::Result<Option<Vec<u8>>> {
+    let mut len = [0u8; 8];
+    match reader.read_exact(&mut len) {
+        Ok(()) => (),
+        Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let mut key = vec![0u8; u64::from_le_bytes(len) as usize];
+    reader.read_exact(&mut key)?;
+    Ok(Some(key))
+}
+
+fn write_length_prefixed_key(writer: &mut impl Write, key: &[u8]) -> std::io::Result<()> {
+    writer.write_all(&(key.len() as u64).to_le_bytes())?;
+    writer.write_all(key)
+}
+
+/// Deterministically ordered copy of a collection of keys whose iteration order is
+/// not deterministic (such as a [`HashSet`](std::collections::HashSet))
+///
+/// Keys are sorted by their [bytes](Hashable::as_bytes) and deduplicated. Keys
+/// are kept in memory until they exceed `memory_limit` bytes, then they are sorted
+/// and written to files in `tmp_dir` (removed when the snapshot is dropped), which
+/// are merged when iterating.
+///
+/// ```no_run
+/// # use std::collections::HashSet;
+/// # use pthash::*;
+/// # use pthash::keys::KeySnapshot;
+/// # let config = BuildConfiguration::new(std::env::temp_dir());
+/// let set: HashSet<u64> = (0..1000).collect();
+/// let keys = KeySnapshot::new(&set, 1 << 30, std::env::temp_dir())
+///     .expect("Could not snapshot keys");
+/// let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+/// f.build_in_internal_memory_from_bytes(
+///     || keys.iter().expect("Could not open keys").map(|key| key.expect("Could not read key")),
+///     &config,
+/// ).expect("Failed to build");
+/// ```
+#[derive(Debug)]
+pub struct KeySnapshot {
+    keys: Vec<Vec<u8>>,
+    runs: Vec<PathBuf>,
+}
+
+impl KeySnapshot {
+    pub fn new<K: Hashable>(
+        keys: impl IntoIterator<Item = K>,
+        memory_limit: usize,
+        tmp_dir: impl AsRef<Path>,
+    ) -> std::io::Result<Self> {
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+        let mut snapshot = KeySnapshot {
+            keys: Vec::new(),
+            runs: Vec::new(),
+        };
+        let mut buffered_bytes = 0;
+        let spill = |keys: &mut Vec<Vec<u8>>, runs: &mut Vec<PathBuf>| {
+            keys.sort_unstable();
+            keys.dedup();
+            let path = tmp_dir.as_ref().join(format!(
+                "pthash-snapshot-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            ));
+            let file = File::options().write(true).create_new(true).open(&path)?;
+            runs.push(path);
+            let mut writer = BufWriter::new(file);
+            for key in keys.drain(..) {
+                write_length_prefixed_key(&mut writer, &key)?;
+            }
+            writer.flush()
+        };
+        for key in keys {
+            let key = key.as_bytes().as_ref().to_vec();
+            buffered_bytes += key.len() + std::mem::size_of::<Vec<u8>>();
+            snapshot.keys.push(key);
+            if buffered_bytes > memory_limit {
+                spill(&mut snapshot.keys, &mut snapshot.runs)?;
+                buffered_bytes = 0;
+            }
+        }
+        if snapshot.runs.is_empty() {
+            snapshot.keys.sort_unstable();
+            snapshot.keys.dedup();
+        } else if !snapshot.keys.is_empty() {
+            spill(&mut snapshot.keys, &mut snapshot.runs)?;
+        }
+        snapshot.keys.shrink_to_fit();
+        Ok(snapshot)
+    }
+
+    /// Returns an iterator on the sorted keys
+    pub fn iter(&self) -> std::io::Result<SnapshotKeys<'_>> {
+        if self.runs.is_empty() {
+            return Ok(SnapshotKeys(SnapshotKeysInner::InMemory(self.keys.iter())));
+        }
+        let readers = self
+            .runs
+            .iter()
+            .map(|path| File::open(path).map(BufReader::new))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        Ok(SnapshotKeys(SnapshotKeysInner::Spilled(
+            MergedKeys::with_read_key(readers, read_length_prefixed_key)?,
+        )))
+    }
+}
+
+impl Drop for KeySnapshot {
+    fn drop(&mut self) {
+        for path in &self.runs {
+            if let Err(e) = std::fs::remove_file(path) {
+                log::warn!("Could not remove {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Iterator on the keys of a [`KeySnapshot`]
+pub struct SnapshotKeys<'a>(SnapshotKeysInner<'a>);
+
+enum SnapshotKeysInner<'a> {
+    InMemory(std::slice::Iter<'a, Vec<u8>>),
+    Spilled(MergedKeys<BufReader<File>>),
+}
+
+impl Iterator for SnapshotKeys<'_> {
+    type Item = std::io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            SnapshotKeysInner::InMemory(keys) => keys.next().cloned().map(Ok),
+            SnapshotKeysInner::Spilled(keys) => keys.next(),
+        }
+    }
+}
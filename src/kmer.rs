@@ -0,0 +1,105 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! 2-bit-packed DNA k-mers, usable as keys without building a byte buffer for
+//! each of them ([`Kmer`])
+
+use crate::Hashable;
+
+/// A sequence of `K` nucleotides (with `K` at most 64), packed with two bits per
+/// nucleotide
+///
+/// Nucleotides are encoded as `A=0`, `C=1`, `G=2`, `T=3`, the first nucleotide of
+/// the sequence being in the most significant bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Kmer<const K: usize>(u128);
+
+impl<const K: usize> Kmer<K> {
+    const CHECK_K: () = assert!(K > 0 && K <= 64, "K must be in [1; 64]");
+
+    /// Number of bytes of [`Self::as_bytes`]
+    pub const NUM_BYTES: usize = (2 * K).div_ceil(8);
+
+    const MASK: u128 = if K == 64 {
+        u128::MAX
+    } else {
+        (1 << (2 * K)) - 1
+    };
+
+    /// Builds a k-mer from its packed representation, ignoring bits above the
+    /// `2*K` lowest ones
+    pub fn from_packed(bits: u128) -> Self {
+        let () = Self::CHECK_K;
+        Kmer(bits & Self::MASK)
+    }
+
+    /// Returns the packed representation of this k-mer
+    pub fn packed(self) -> u128 {
+        self.0
+    }
+
+    /// Parses a k-mer from a sequence of `A`, `C`, `G` and `T` (case-insensitive)
+    ///
+    /// Returns `None` if the sequence does not have exactly `K` nucleotides, or
+    /// contains any other character.
+    pub fn from_ascii(seq: &[u8]) -> Option<Self> {
+        let () = Self::CHECK_K;
+        if seq.len() != K {
+            return None;
+        }
+        let mut bits = 0u128;
+        for nucleotide in seq {
+            let code = match nucleotide {
+                b'A' | b'a' => 0,
+                b'C' | b'c' => 1,
+                b'G' | b'g' => 2,
+                b'T' | b't' => 3,
+                _ => return None,
+            };
+            bits = (bits << 2) | code;
+        }
+        Some(Kmer(bits))
+    }
+
+    /// Returns the sequence of nucleotides of this k-mer, as uppercase ASCII
+    pub fn to_ascii(self) -> [u8; K] {
+        let mut seq = [0u8; K];
+        for (i, nucleotide) in seq.iter_mut().enumerate() {
+            *nucleotide = b"ACGT"[((self.0 >> (2 * (K - 1 - i))) & 0b11) as usize];
+        }
+        seq
+    }
+}
+
+/// Byte representation of a [`Kmer`], stored inline
+#[derive(Clone, Copy, Debug)]
+pub struct KmerBytes {
+    bytes: [u8; 16],
+    len: usize,
+}
+
+impl AsRef<[u8]> for KmerBytes {
+    #[inline(always)]
+    fn as_ref(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Hashes the `Kmer::<K>::NUM_BYTES` low bytes of the packed representation, in
+/// little-endian order
+impl<const K: usize> Hashable for Kmer<K> {
+    type Bytes<'a>
+        = KmerBytes
+    where
+        Self: 'a;
+
+    #[inline(always)]
+    fn as_bytes(&self) -> Self::Bytes<'_> {
+        KmerBytes {
+            bytes: self.0.to_le_bytes(),
+            len: Self::NUM_BYTES,
+        }
+    }
+}
@@ -3,11 +3,14 @@
 // License: GNU General Public License version 3, or any later version
 // See top-level LICENSE file for more information
 
-#![cfg_attr(all(feature = "minimal", feature = "nonminimal", feature = "dictionary_dictionary", feature = "hash64"), doc = include_str!("../README.md"))]
+#![cfg_attr(all(feature = "build", feature = "minimal", feature = "nonminimal", feature = "dictionary_dictionary", feature = "hash64"), doc = include_str!("../README.md"))]
 
+use std::io::{Read, Write};
 use std::path::Path;
+use std::sync::Arc;
 
 use cxx::Exception;
+use metadata::LoadError;
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
 
@@ -16,71 +19,377 @@ pub use build::*;
 
 mod backends;
 
+#[cfg(feature = "build")]
+mod builders;
+
+pub mod cache;
+
+#[cfg(feature = "capi")]
+pub mod capi;
+
+pub mod cached;
+
+pub mod config;
+
+#[cfg(any(
+    feature = "dictionary_dictionary",
+    feature = "partitioned_compact",
+    feature = "elias_fano",
+    feature = "compact",
+    feature = "dictionary"
+))]
+pub mod dynamic;
+#[cfg(any(
+    feature = "dictionary_dictionary",
+    feature = "partitioned_compact",
+    feature = "elias_fano",
+    feature = "compact",
+    feature = "dictionary"
+))]
+pub use dynamic::load_any;
+
 pub mod encoders;
 pub use encoders::*;
 
 pub mod hashing;
 pub use hashing::*;
 
+#[cfg(feature = "hot-reload")]
+pub mod hot;
+
+#[cfg(feature = "metrics")]
+pub mod instrumentation;
+
+mod interop;
+pub use interop::*;
+
+pub mod keys;
+
+pub mod kmer;
+
+pub mod map;
+
+pub mod metadata;
+
 pub mod minimality;
 pub use minimality::*;
 
 mod partitioned_phf;
 pub use partitioned_phf::*;
 
+mod permute;
+pub use permute::*;
+
+#[cfg(feature = "python")]
+pub mod python;
+
+#[cfg(feature = "rayon")]
+pub mod scheduler;
+
+#[cfg(feature = "hash64")]
+pub mod set;
+
 mod structs;
 
 mod single_phf;
 pub use single_phf::*;
 
+#[cfg(feature = "swhid")]
+pub mod swhid;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub mod tuning;
+
 mod utils;
 #[allow(unused_imports)] // check() is feature-gated
 pub use utils::*;
 
-/// A [perfect-hash function](https://en.wikipedia.org/wiki/Perfect_hash_function)
-/// implemented with the [PTHash algorithm](https://dl.acm.org/doi/10.1145/3404835.3462849)
-pub trait Phf: Sized + Send + Sync {
+mod version;
+pub use version::*;
+
+/// Query methods of a [`Phf`]
+///
+/// Unlike [`Phf`], this is also implemented for references and smart pointers to
+/// functions, so shared functions can be passed to generic code.
+pub trait PhfQuery {
     /// Whether instances of this function have their values in the range `[0; num_keys)`.
     const MINIMAL: bool;
 
+    /// Returns the hash of the given key
+    ///
+    /// If the `key` was not one of the keys passed to
+    /// [`build_in_internal_memory_from_bytes`](Phf::build_in_internal_memory_from_bytes)
+    /// when building the function, the hash will collide with another key's
+    fn hash(&self, key: impl Hashable) -> u64;
+
+    /// Returns the hash of each key, in order
+    ///
+    /// This is equivalent to calling [`Self::hash`] on each key, but [`SinglePhf`]
+    /// and [`PartitionedPhf`] look up keys by batches, which amortizes the cost of
+    /// calls to C++.
+    fn hash_many<K: Hashable>(&self, keys: impl IntoIterator<Item = K>) -> Vec<u64> {
+        keys.into_iter().map(|key| self.hash(key)).collect()
+    }
+    /// Same as [`Self::hash_many`], but writes the hash of each key to `out` instead
+    /// of allocating a vector
+    ///
+    /// # Panics
+    ///
+    /// If the number of keys is not `out.len()`
+    fn hash_many_into<K: Hashable>(&self, keys: impl IntoIterator<Item = K>, out: &mut [u64]) {
+        let mut keys = keys.into_iter();
+        for position in out.iter_mut() {
+            *position = self.hash(keys.next().expect("Fewer keys than positions"));
+        }
+        assert!(keys.next().is_none(), "More keys than positions");
+    }
+    /// Same as [`Self::hash_many`], but splits keys in chunks hashed in parallel on
+    /// rayon's thread pool
+    ///
+    /// Hashes are still in the same order as `keys`. Chunks are sized with
+    /// [`tuning::hashing_max_len`].
+    #[cfg(feature = "rayon")]
+    fn par_hash_many<K: Hashable + Sync>(&self, keys: &[K]) -> Vec<u64>
+    where
+        Self: Sync,
+    {
+        let chunk_len = tuning::hashing_max_len(keys.len(), rayon::current_num_threads());
+        let mut positions = vec![0; keys.len()];
+        keys.par_chunks(chunk_len)
+            .zip(positions.par_chunks_mut(chunk_len))
+            .for_each(|(keys, positions)| self.hash_many_into(keys, positions));
+        positions
+    }
+
+    /// Returns the number of bits needed to represent this perfect-hash function
+    fn num_bits(&self) -> usize;
+    /// Returns the number of keys used to build this perfect-hash function
+    fn num_keys(&self) -> u64;
+    /// Largest value returned by [`Self::hash`] plus 1
+    fn table_size(&self) -> u64;
+}
+
+macro_rules! impl_phf_query_for_pointer {
+    ($($ptr:ty),*) => {$(
+        impl<F: PhfQuery + ?Sized> PhfQuery for $ptr {
+            const MINIMAL: bool = F::MINIMAL;
+
+            #[inline(always)]
+            fn hash(&self, key: impl Hashable) -> u64 {
+                (**self).hash(key)
+            }
+            fn hash_many<K: Hashable>(&self, keys: impl IntoIterator<Item = K>) -> Vec<u64> {
+                (**self).hash_many(keys)
+            }
+            fn hash_many_into<K: Hashable>(
+                &self,
+                keys: impl IntoIterator<Item = K>,
+                out: &mut [u64],
+            ) {
+                (**self).hash_many_into(keys, out)
+            }
+            fn num_bits(&self) -> usize {
+                (**self).num_bits()
+            }
+            fn num_keys(&self) -> u64 {
+                (**self).num_keys()
+            }
+            fn table_size(&self) -> u64 {
+                (**self).table_size()
+            }
+        }
+
+        impl<F: MinimalPhf + ?Sized> MinimalPhf for $ptr {}
+    )*};
+}
+
+impl_phf_query_for_pointer!(&F, Box<F>, Arc<F>);
+
+/// Functions whose values are always in the range `[0; num_keys)`, ie. whose
+/// [`PhfQuery::MINIMAL`] is `true`
+///
+/// This allows APIs which index arrays by hashes (like [`map::PhfMap`]) to reject
+/// non-minimal functions at compile time.
+pub trait MinimalPhf: PhfQuery {}
+
+/// Number of bits used by each component of a function, returned by
+/// [`Phf::num_bits_breakdown`]
+///
+/// Sizes are those of the serialized components, so their sum may differ by a few
+/// bytes from [`PhfQuery::num_bits`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NumBitsBreakdown {
+    /// Seeds, sizes and constants of the function; for partitioned functions, this
+    /// also includes those of each partition, their offsets, and the bucketer
+    /// assigning keys to partitions
+    pub overhead_bits: u64,
+    /// Bucketers assigning keys to buckets
+    pub bucketer_bits: u64,
+    /// Pilots, compressed by the [encoder](encoders::Encoder)
+    pub pilots_bits: u64,
+    /// Remapping of positions greater than the number of keys to free slots, in
+    /// minimal functions (empty in non-minimal ones)
+    pub free_slots_bits: u64,
+}
+
+impl NumBitsBreakdown {
+    pub(crate) fn from_ffi(bits: &[u64]) -> Self {
+        let [overhead_bits, bucketer_bits, pilots_bits, free_slots_bits] =
+            bits.try_into().expect("unexpected number of components");
+        NumBitsBreakdown {
+            overhead_bits,
+            bucketer_bits,
+            pilots_bits,
+            free_slots_bits,
+        }
+    }
+
+    /// Returns the sum of all components
+    pub fn total_bits(&self) -> u64 {
+        self.overhead_bits + self.bucketer_bits + self.pilots_bits + self.free_slots_bits
+    }
+}
+
+/// A [perfect-hash function](https://en.wikipedia.org/wiki/Perfect_hash_function)
+/// implemented with the [PTHash algorithm](https://dl.acm.org/doi/10.1145/3404835.3462849)
+pub trait Phf: PhfQuery + Sized + Send + Sync {
     /// Builds the function from a set of keys
     ///
     /// In plain English, this function's trait bound on keys is that they should be
     /// a collection that can provide cloneable iterators of hashable values.
+    ///
+    /// Given the same keys and the same configuration with a fixed
+    /// [`seed`](BuildConfiguration::seed), the built function is deterministic:
+    /// its [serialized form](Self::save) is byte-identical across runs on the same
+    /// platform, regardless of the number of threads used to hash keys. This is not
+    /// guaranteed across platforms (eg. x86_64 and aarch64), as it depends on the
+    /// C++ compiler and standard library.
+    ///
+    /// Only available with the `build` feature (enabled by default).
+    #[cfg(feature = "build")]
     fn build_in_internal_memory_from_bytes<Keys: IntoIterator>(
         &mut self,
         keys: impl FnMut() -> Keys,
         config: &BuildConfiguration,
-    ) -> Result<BuildTimings, Exception>
+    ) -> Result<BuildTimings, BuildError>
     where
         <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable;
 
-    #[cfg(feature = "rayon")]
+    /// Same as [`Self::build_in_internal_memory_from_bytes`], but reads keys only
+    /// once, so they can come from a one-shot iterator (eg. read from a socket)
+    ///
+    /// Only the hashes of keys are kept in memory. As keys cannot be hashed again
+    /// with another seed, this makes a single attempt with
+    /// [`BuildConfiguration::seed`], which must be set: it fails with
+    /// [`BuildError::SeedsExhausted`] where other builds would retry with another
+    /// seed (on [hash collisions](BuildConfiguration::check_collisions), or when
+    /// PTHash fails on small sets). It fails with [`BuildError::KeysReadOnce`] if no
+    /// seed is set, or if [`BuildConfiguration::check_duplicates`] or
+    /// [`BuildConfiguration::verify`] is set, as they read keys again.
+    ///
+    /// Only available with the `build` feature (enabled by default).
+    #[cfg(feature = "build")]
+    fn build_in_internal_memory_from_iter<Keys: IntoIterator>(
+        &mut self,
+        keys: Keys,
+        config: &BuildConfiguration,
+    ) -> Result<BuildTimings, BuildError>
+    where
+        <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable;
+
+    #[cfg(all(feature = "build", feature = "rayon"))]
     /// Same as [`Self::build_in_internal_memory_from_bytes`], but hashes in parallel
+    ///
+    /// Keys are hashed in chunks as split by their parallel iterator, which for
+    /// indexed iterators (slices, vectors, ranges) is rayon's adaptive splitting: a
+    /// chunk per thread, split again when other threads run out of work. This is not
+    /// changed here, as non-indexed iterators cannot be split further. When key
+    /// lengths are skewed, cap indexed iterators with `with_max_len` to balance the
+    /// load between threads (see [`tuning::hashing_max_len`]).
     fn par_build_in_internal_memory_from_bytes<Keys: IntoParallelIterator>(
         &mut self,
         keys: impl FnMut() -> Keys,
         config: &BuildConfiguration,
-    ) -> Result<BuildTimings, Exception>
+    ) -> Result<BuildTimings, BuildError>
     where
         <<Keys as IntoParallelIterator>::Iter as ParallelIterator>::Item: Hashable;
 
-    /// Returns the hash of the given key
+    /// Same as [`Self::par_build_in_internal_memory_from_bytes`], but hashes keys on
+    /// `pool` instead of the current (usually global) rayon thread pool
     ///
-    /// If the `key` was not one of the keys passed to
-    /// [`build_in_internal_memory_from_bytes`](Self::build_in_internal_memory_from_bytes)
-    /// when building the function, the hash will collide with another key's
-    fn hash(&self, key: impl Hashable) -> u64;
+    /// PTHash's own threads run after keys are hashed, while the pool is idle; so
+    /// [`BuildConfiguration::num_threads`] is capped to the number of threads of
+    /// `pool`, for the whole build to stay within the cores given to the pool.
+    #[cfg(all(feature = "build", feature = "rayon"))]
+    fn par_build_in_internal_memory_from_bytes_in_pool<Keys: IntoParallelIterator>(
+        &mut self,
+        pool: &rayon::ThreadPool,
+        keys: impl FnMut() -> Keys + Send,
+        config: &BuildConfiguration,
+    ) -> Result<BuildTimings, BuildError>
+    where
+        Self: Send,
+        <<Keys as IntoParallelIterator>::Iter as ParallelIterator>::Item: Hashable,
+    {
+        let mut config = config.clone();
+        config.num_threads = config
+            .num_threads
+            .clamp(1, pool.current_num_threads() as u64);
+        pool.install(|| self.par_build_in_internal_memory_from_bytes(keys, &config))
+    }
 
-    /// Returns the number of bits needed to represent this perfect-hash function
-    fn num_bits(&self) -> usize;
-    /// Returns the number of keys used to build this perfect-hash function
-    fn num_keys(&self) -> u64;
-    /// Largest value returned by [`Self::hash`] plus 1
-    fn table_size(&self) -> u64;
+    /// Returns the configuration this function was built with, where values picked
+    /// during the build (such as the seed, or the number of buckets of
+    /// non-partitioned functions) are replaced with the ones actually used
+    ///
+    /// Returns `None` if the function was not built by this process (eg. it was
+    /// [loaded](Self::load) from disk).
+    fn build_configuration(&self) -> Option<&BuildConfiguration>;
+
+    /// Returns the number of bytes of memory allocated by this function
+    ///
+    /// Unlike [`num_bits`](PhfQuery::num_bits), this includes the unused capacity of
+    /// the underlying C++ vectors and the size of their headers. With glibc, it also
+    /// includes the rounding of each allocation up to the allocator's size classes
+    /// (as reported by `malloc_usable_size`), but not the allocator's own metadata;
+    /// with other C libraries, it includes neither.
+    fn memory_usage(&self) -> usize;
+
+    /// Returns the number of bits used by each component of this function, whose sum
+    /// is close to [`num_bits`](PhfQuery::num_bits)
+    fn num_bits_breakdown(&self) -> NumBitsBreakdown;
 
     /// Dump this function to disk
+    ///
+    /// The file ends with a [footer](metadata::PhfMetadata) describing the type of the
+    /// function, which PTHash ignores.
     fn save(&mut self, path: impl AsRef<Path>) -> Result<usize, Exception>;
     /// Load this function from disk
-    fn load(path: impl AsRef<Path>) -> Result<Self, Exception>;
+    ///
+    /// Fails with [`LoadError::TypeMismatch`](metadata::LoadError::TypeMismatch) if
+    /// the file's [footer](metadata::PhfMetadata) describes a function of another
+    /// type, and with [`MetadataError::Missing`](metadata::MetadataError::Missing) if
+    /// it has no footer; use [`Self::load_raw`] to load such files.
+    fn load(path: impl AsRef<Path>) -> Result<Self, LoadError>;
+    /// Load this function from disk, without checking the file's
+    /// [footer](metadata::PhfMetadata)
+    ///
+    /// This can load files written by PTHash itself, but returns a function with
+    /// meaningless hashes if the file was written by a function of a different type.
+    fn load_raw(path: impl AsRef<Path>) -> Result<Self, Exception>;
+
+    /// Same as [`Self::save`], but writes to `writer` instead of a file
+    ///
+    /// Returns the number of bytes written.
+    fn save_to(&mut self, writer: impl Write) -> std::io::Result<usize>;
+    /// Same as [`Self::load`], but reads from `reader` instead of a file
+    ///
+    /// This reads `reader` until its end; functions followed by other data in a
+    /// stream can be read with [`Read::take`], using the length returned by
+    /// [`Self::save_to`]. Errors of the footer's check are returned as
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData) errors wrapping a
+    /// [`LoadError`](metadata::LoadError).
+    fn load_from(reader: impl Read) -> std::io::Result<Self>;
 }
@@ -0,0 +1,215 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Static maps ([`PhfMap`]), storing values in an array indexed by the hashes of a
+//! minimal perfect-hash function
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use cxx::Exception;
+use thiserror::Error;
+
+#[cfg(feature = "build")]
+use crate::build::{BuildConfiguration, BuildError};
+use crate::metadata::{load_prefix, LoadError};
+use crate::{Hashable, MinimalPhf, Phf};
+
+/// Last bytes of files written by [`PhfMap::save`]
+pub const MAP_MAGIC: &[u8; 8] = b"PTHASHmp";
+
+#[derive(Error, Debug)]
+pub enum PhfMapError {
+    #[error("Could not save function: {0}")]
+    Backend(#[from] Exception),
+    #[error("Could not load function: {0}")]
+    Load(#[from] LoadError),
+    #[error("Could not save or load values: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid map file: {0}")]
+    Invalid(&'static str),
+}
+
+/// Values which can be stored in a [`PhfMap`]
+///
+/// They are serialized as `SIZE` bytes each.
+pub trait MapValue: Sized {
+    /// Number of bytes written by [`Self::write_bytes`]
+    const SIZE: usize;
+
+    /// Writes exactly [`Self::SIZE`] bytes to `buf`
+    fn write_bytes(&self, buf: &mut [u8]);
+    /// Reads a value from exactly [`Self::SIZE`] bytes
+    fn read_bytes(buf: &[u8]) -> Self;
+}
+
+macro_rules! impl_map_value_for_number {
+    ($($ty:ty),*) => {$(
+        impl MapValue for $ty {
+            const SIZE: usize = std::mem::size_of::<$ty>();
+
+            fn write_bytes(&self, buf: &mut [u8]) {
+                buf.copy_from_slice(&self.to_le_bytes())
+            }
+            fn read_bytes(buf: &[u8]) -> Self {
+                <$ty>::from_le_bytes(buf.try_into().expect("unexpected value size"))
+            }
+        }
+    )*};
+}
+
+impl_map_value_for_number!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl<const N: usize> MapValue for [u8; N] {
+    const SIZE: usize = N;
+
+    fn write_bytes(&self, buf: &mut [u8]) {
+        buf.copy_from_slice(self)
+    }
+    fn read_bytes(buf: &[u8]) -> Self {
+        buf.try_into().expect("unexpected value size")
+    }
+}
+
+/// A static map from keys of type `K` to values of type `V`, which stores values
+/// in the order given by the minimal perfect-hash function `F`
+///
+/// Like the underlying function, it does not store keys: looking up a key which was
+/// not in the map returns the value of another key.
+pub struct PhfMap<K, V, F: Phf + MinimalPhf> {
+    phf: F,
+    values: Vec<V>,
+    marker: PhantomData<fn(&K)>,
+}
+
+impl<K: Hashable, V, F: Phf + MinimalPhf> PhfMap<K, V, F> {
+    /// Builds `phf` (usually a new function) from the keys, and stores values in the
+    /// order of their hashes
+    ///
+    /// Only available with the `build` feature (enabled by default).
+    ///
+    /// As each key needs its own value, duplicate keys make the build fail with
+    /// [`BuildError::DuplicateKey`], even if
+    /// [`deduplicate`](BuildConfiguration::deduplicate) is set.
+    #[cfg(feature = "build")]
+    pub fn build(
+        mut phf: F,
+        pairs: impl IntoIterator<Item = (K, V)>,
+        config: &BuildConfiguration,
+    ) -> Result<Self, BuildError> {
+        let mut config = config.clone();
+        config.deduplicate = false;
+        config.check_duplicates = true;
+        let (keys, mut values): (Vec<K>, Vec<V>) = pairs.into_iter().unzip();
+        phf.build_in_internal_memory_from_bytes(|| &keys, &config)?;
+        crate::permute_by_phf(&phf, &keys, &mut values);
+        Ok(PhfMap {
+            phf,
+            values,
+            marker: PhantomData,
+        })
+    }
+
+    /// Returns the value associated to `key`
+    ///
+    /// If `key` was not in the pairs the map was built from, this returns the value
+    /// of another key.
+    #[inline(always)]
+    pub fn get(&self, key: &K) -> &V {
+        &self.values[self.phf.hash(key) as usize]
+    }
+
+    /// Returns the number of keys in this map
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns whether this map has no keys
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the function mapping keys to indices in [`Self::values`]
+    pub fn phf(&self) -> &F {
+        &self.phf
+    }
+
+    /// Returns the values, in the order of the hashes of their keys
+    pub fn values(&self) -> &[V] {
+        &self.values
+    }
+}
+
+impl<K: Hashable, V: MapValue, F: Phf + MinimalPhf> PhfMap<K, V, F> {
+    /// Dumps both the function and the values to a single file
+    ///
+    /// The function is written first with [`Phf::save`], followed by the values and a
+    /// trailer giving the number of values and where they start.
+    pub fn save(&mut self, path: impl AsRef<Path>) -> Result<usize, PhfMapError> {
+        let path = path.as_ref();
+        self.phf.save(path)?;
+
+        let file = OpenOptions::new().append(true).open(path)?;
+        let phf_len = file.metadata()?.len();
+        let mut file = BufWriter::new(file);
+        let mut buf = vec![0; V::SIZE];
+        for value in &self.values {
+            value.write_bytes(&mut buf);
+            file.write_all(&buf)?;
+        }
+        file.write_all(&(self.values.len() as u64).to_le_bytes())?;
+        file.write_all(&phf_len.to_le_bytes())?;
+        file.write_all(MAP_MAGIC)?;
+        file.flush()?;
+
+        Ok(phf_len as usize + self.values.len() * V::SIZE + 16 + MAP_MAGIC.len())
+    }
+
+    /// Loads a map written by [`Self::save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PhfMapError> {
+        let path = path.as_ref();
+        let mut file = File::open(path)?;
+        let trailer_len = 16 + MAP_MAGIC.len() as u64;
+        let file_len = file.metadata()?.len();
+        if file_len < trailer_len {
+            return Err(PhfMapError::Invalid("file is too short"));
+        }
+        file.seek(SeekFrom::End(-(trailer_len as i64)))?;
+        let mut trailer = [0; 16 + MAP_MAGIC.len()];
+        file.read_exact(&mut trailer)?;
+        if &trailer[16..] != MAP_MAGIC {
+            return Err(PhfMapError::Invalid("missing map trailer"));
+        }
+        let num_values = u64::from_le_bytes(trailer[0..8].try_into().unwrap());
+        let phf_len = u64::from_le_bytes(trailer[8..16].try_into().unwrap());
+        let phf = load_prefix::<F>(path, phf_len)?;
+        if num_values != phf.num_keys() {
+            return Err(PhfMapError::Invalid(
+                "number of values does not match the function",
+            ));
+        }
+        if phf_len + num_values * (V::SIZE as u64) + trailer_len != file_len {
+            return Err(PhfMapError::Invalid("unexpected file length"));
+        }
+
+        file.seek(SeekFrom::Start(phf_len))?;
+        let mut file = BufReader::new(file);
+        let mut buf = vec![0; V::SIZE];
+        let values = (0..num_values)
+            .map(|_| {
+                file.read_exact(&mut buf)?;
+                Ok(V::read_bytes(&buf))
+            })
+            .collect::<std::io::Result<_>>()?;
+
+        Ok(PhfMap {
+            phf,
+            values,
+            marker: PhantomData,
+        })
+    }
+}
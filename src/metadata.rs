@@ -0,0 +1,196 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Description of the type of a saved function ([`PhfMetadata`]), stored in a footer
+//! after the data written by PTHash
+//!
+//! PTHash's loader stops reading after the function's data, so files with this
+//! footer can still be loaded by PTHash itself. Files saved by PTHash or by older
+//! versions of this crate have no footer, and can only be loaded with
+//! [`Phf::load_raw`](crate::Phf::load_raw).
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+use cxx::Exception;
+use thiserror::Error;
+
+use crate::Phf;
+
+/// Last bytes of files with a [`PhfMetadata`] footer
+pub const FOOTER_MAGIC: &[u8; 8] = b"PTHASHrs";
+
+/// Version of the footer's format written by this version of the crate
+pub const FOOTER_VERSION: u8 = 2;
+
+const FLAG_PARTITIONED: u8 = 1;
+const FLAG_MINIMAL: u8 = 2;
+
+/// Length of the payload length and magic, after the payload
+const TRAILER_LEN: usize = 4 + FOOTER_MAGIC.len();
+
+#[derive(Error, Debug)]
+pub enum MetadataError {
+    #[error("Could not read metadata: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(
+        "File has no metadata footer (it was not saved by this crate, or by an older version)"
+    )]
+    Missing,
+    #[error("Unsupported metadata version {0} (expected {FOOTER_VERSION})")]
+    UnsupportedVersion(u8),
+    #[error("Invalid metadata: {0}")]
+    Invalid(&'static str),
+}
+
+#[derive(Error, Debug)]
+pub enum LoadError {
+    #[error(transparent)]
+    Metadata(#[from] MetadataError),
+    #[error("Unknown or disabled encoder: {0}")]
+    UnknownEncoder(String),
+    #[error("Functions of type {0:?} are not supported by this build of pthash-rs")]
+    Unsupported(PhfMetadata),
+    #[error("Could not load function: {0}")]
+    Backend(#[from] Exception),
+    #[error("Expected a {expected}, but file contains a function of type {found:?}")]
+    TypeMismatch {
+        expected: &'static str,
+        found: PhfMetadata,
+    },
+}
+
+/// Loads a function written by [`Phf::save`] in the first `len` bytes of the file at
+/// `path`, which has other data after the function's footer
+pub(crate) fn load_prefix<F: Phf>(path: &Path, len: u64) -> Result<F, LoadError> {
+    let file = File::open(path).map_err(MetadataError::Io)?;
+    F::load_from(file.take(len)).map_err(|e| {
+        if e.get_ref().is_some_and(|inner| inner.is::<LoadError>()) {
+            *e.into_inner().unwrap().downcast::<LoadError>().unwrap()
+        } else {
+            MetadataError::Io(e).into()
+        }
+    })
+}
+
+/// Type parameters of a saved function
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PhfMetadata {
+    /// Whether it is a [`PartitionedPhf`](crate::PartitionedPhf) rather than a
+    /// [`SinglePhf`](crate::SinglePhf)
+    pub partitioned: bool,
+    /// Whether it is [`Minimal`](crate::Minimal)
+    pub minimal: bool,
+    /// Size of the hashes computed by its [`Hasher`](crate::Hasher), in bits
+    pub hash_bits: u16,
+    /// [`Hasher::NAME`](crate::Hasher::NAME) of its hasher
+    pub hasher: String,
+    /// [`Encoder::NAME`](crate::Encoder::NAME) of its encoder
+    pub encoder: String,
+}
+
+impl PhfMetadata {
+    /// Checks that the footer read from a file matches the type `F` loading it
+    pub(crate) fn check<F>(self, expected: &PhfMetadata) -> Result<(), LoadError> {
+        if self == *expected {
+            Ok(())
+        } else {
+            Err(LoadError::TypeMismatch {
+                expected: std::any::type_name::<F>(),
+                found: self,
+            })
+        }
+    }
+
+    /// Returns the footer to append to the file
+    pub(crate) fn to_footer(&self) -> Vec<u8> {
+        let mut flags = 0;
+        if self.partitioned {
+            flags |= FLAG_PARTITIONED;
+        }
+        if self.minimal {
+            flags |= FLAG_MINIMAL;
+        }
+        let hasher_len = u8::try_from(self.hasher.len()).expect("Hasher name is too long");
+        let mut footer = vec![FOOTER_VERSION, flags];
+        footer.extend(self.hash_bits.to_le_bytes());
+        footer.push(hasher_len);
+        footer.extend(self.hasher.as_bytes());
+        footer.extend(self.encoder.as_bytes());
+        let payload_len = footer.len() as u32;
+        footer.extend(payload_len.to_le_bytes());
+        footer.extend(FOOTER_MAGIC);
+        footer
+    }
+
+    /// Reads the footer of a file written by [`Phf::save`](crate::Phf::save)
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, MetadataError> {
+        let mut file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        let trailer_len = TRAILER_LEN as u64;
+        if file_len < trailer_len {
+            return Err(MetadataError::Missing);
+        }
+        file.seek(SeekFrom::End(-(trailer_len as i64)))?;
+        let mut trailer = [0; TRAILER_LEN];
+        file.read_exact(&mut trailer)?;
+        let payload_len = u64::from(Self::payload_len(&trailer)?);
+        if payload_len + trailer_len > file_len {
+            return Err(MetadataError::Invalid("footer is longer than the file"));
+        }
+        file.seek(SeekFrom::End(-((payload_len + trailer_len) as i64)))?;
+        let mut payload = vec![0; payload_len as usize];
+        file.read_exact(&mut payload)?;
+
+        Self::from_payload(&payload)
+    }
+
+    /// Parses the footer at the end of `bytes`, as written by
+    /// [`Phf::save_to`](crate::Phf::save_to)
+    pub(crate) fn from_suffix(bytes: &[u8]) -> Result<Self, MetadataError> {
+        let Some(trailer_start) = bytes.len().checked_sub(TRAILER_LEN) else {
+            return Err(MetadataError::Missing);
+        };
+        let payload_len = Self::payload_len(&bytes[trailer_start..])? as usize;
+        let Some(payload_start) = trailer_start.checked_sub(payload_len) else {
+            return Err(MetadataError::Invalid("footer is longer than the data"));
+        };
+        Self::from_payload(&bytes[payload_start..trailer_start])
+    }
+
+    /// Checks the magic of a footer's trailer, and returns the length of its payload
+    fn payload_len(trailer: &[u8]) -> Result<u32, MetadataError> {
+        let (payload_len, magic) = trailer.split_at(4);
+        if magic != FOOTER_MAGIC {
+            return Err(MetadataError::Missing);
+        }
+        Ok(u32::from_le_bytes(payload_len.try_into().unwrap()))
+    }
+
+    fn from_payload(payload: &[u8]) -> Result<Self, MetadataError> {
+        let [version, rest @ ..] = payload else {
+            return Err(MetadataError::Invalid("footer is too short"));
+        };
+        if *version != FOOTER_VERSION {
+            return Err(MetadataError::UnsupportedVersion(*version));
+        }
+        let [flags, hash_bits_0, hash_bits_1, hasher_len, names @ ..] = rest else {
+            return Err(MetadataError::Invalid("footer is too short"));
+        };
+        let Some((hasher, encoder)) = names.split_at_checked(usize::from(*hasher_len)) else {
+            return Err(MetadataError::Invalid("hasher name is too long"));
+        };
+        Ok(PhfMetadata {
+            partitioned: flags & FLAG_PARTITIONED != 0,
+            minimal: flags & FLAG_MINIMAL != 0,
+            hash_bits: u16::from_le_bytes([*hash_bits_0, *hash_bits_1]),
+            hasher: String::from_utf8(hasher.to_vec())
+                .map_err(|_| MetadataError::Invalid("hasher name is not UTF-8"))?,
+            encoder: String::from_utf8(encoder.to_vec())
+                .map_err(|_| MetadataError::Invalid("encoder name is not UTF-8"))?,
+        })
+    }
+}
@@ -5,19 +5,27 @@
 
 #![allow(non_camel_case_types)]
 
+use std::ffi::{c_char, c_void};
+use std::fmt;
+use std::io::{ErrorKind, Read, Write};
 use std::marker::PhantomData;
 use std::path::Path;
+use std::sync::OnceLock;
 
 //use autocxx::prelude::*;
 use cxx::{Exception, UniquePtr};
-use rand::Rng;
-#[cfg(feature = "rayon")]
+#[cfg(all(feature = "build", feature = "rayon"))]
 use rayon::prelude::*;
 
 use crate::backends::BackendPhf;
-use crate::build::{BuildConfiguration, BuildTimings, Builder};
+use crate::build::BuildConfiguration;
+#[cfg(feature = "build")]
+use crate::build::{BuildError, BuildTimings, SeedAttempt};
+#[cfg(feature = "build")]
+use crate::builders::Builder;
 use crate::hashing::{Hashable, Hasher};
-use crate::{Encoder, Minimality, Phf, SealedMinimality};
+use crate::metadata::{LoadError, PhfMetadata};
+use crate::{Encoder, Minimality, NumBitsBreakdown, Phf, PhfQuery, SealedMinimality};
 
 /// Partitioned minimal perfect hash function
 ///
@@ -25,23 +33,224 @@ use crate::{Encoder, Minimality, Phf, SealedMinimality};
 pub struct PartitionedPhf<M: Minimality, H: Hasher, E: Encoder> {
     inner: UniquePtr<<M as SealedMinimality>::PartitionedPhfBackend<H::Hash, E>>,
     seed: u64,
+    build_configuration: Option<BuildConfiguration>,
+    /// First position of each partition, read from `inner` when first needed
+    partition_offsets: OnceLock<Vec<u64>>,
     marker: PhantomData<M>,
 }
 
 unsafe impl<M: Minimality, H: Hasher, E: Encoder> Send for PartitionedPhf<M, H, E> {}
 unsafe impl<M: Minimality, H: Hasher, E: Encoder> Sync for PartitionedPhf<M, H, E> {}
 
+/// Copies the underlying C++ object, which is as fast as copying its vectors
+impl<M: Minimality, H: Hasher, E: Encoder> Clone for PartitionedPhf<M, H, E> {
+    fn clone(&self) -> Self {
+        PartitionedPhf {
+            inner: BackendPhf::copy(&self.inner),
+            seed: self.seed,
+            build_configuration: self.build_configuration.clone(),
+            partition_offsets: self.partition_offsets.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Functions are equal if their serialized representations are, so they return the
+/// same hash for every key
+///
+/// This serializes both functions, so it takes time proportional to their size.
+impl<M: Minimality, H: Hasher, E: Encoder> PartialEq for PartitionedPhf<M, H, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed
+            && match (self.to_bytes(), other.to_bytes()) {
+                (Ok(bytes), Ok(other_bytes)) => bytes == other_bytes,
+                _ => false,
+            }
+    }
+}
+
+impl<M: Minimality, H: Hasher, E: Encoder> Eq for PartitionedPhf<M, H, E> {}
+
+impl<M: Minimality, H: Hasher, E: Encoder> fmt::Debug for PartitionedPhf<M, H, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PartitionedPhf")
+            .field("minimal", &M::AS_BOOL)
+            .field("hash_bits", &(std::mem::size_of::<H::Hash>() * 8))
+            .field("encoder", &E::NAME)
+            .field("num_keys", &self.num_keys())
+            .field("num_partitions", &self.num_partitions())
+            .field("table_size", &self.table_size())
+            .field("bits_per_key", &crate::utils::bits_per_key(self))
+            .field("seed", &self.seed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Summary of the function, eg. `minimal PartitionedPhf (64-bit hashes,
+/// dictionary_dictionary encoder): 1000000 keys in 4 partitions, table size 1063832,
+/// 3.05 bits/key, seed 42`
+impl<M: Minimality, H: Hasher, E: Encoder> fmt::Display for PartitionedPhf<M, H, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} PartitionedPhf ({}-bit hashes, {} encoder): {} keys in {} partitions, table size {}, {:.2} bits/key, seed {}",
+            if M::AS_BOOL { "minimal" } else { "non-minimal" },
+            std::mem::size_of::<H::Hash>() * 8,
+            E::NAME,
+            self.num_keys(),
+            self.num_partitions(),
+            self.table_size(),
+            crate::utils::bits_per_key(self),
+            self.seed,
+        )
+    }
+}
+
 #[allow(clippy::new_without_default)]
 impl<M: Minimality, H: Hasher, E: Encoder> PartitionedPhf<M, H, E> {
     pub fn new() -> Self {
         PartitionedPhf {
             inner: BackendPhf::new(),
             seed: 0,
+            build_configuration: None,
+            partition_offsets: OnceLock::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the description of this type, written in files by [`Phf::save`]
+    pub fn metadata() -> PhfMetadata {
+        PhfMetadata {
+            partitioned: true,
+            minimal: M::AS_BOOL,
+            hash_bits: (std::mem::size_of::<H::Hash>() * 8) as u16,
+            hasher: H::NAME.to_owned(),
+            encoder: E::NAME.to_owned(),
+        }
+    }
+
+    /// Returns the seed of the hashes of keys, picked when building the function
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns whether hashes of keys by `other` are computed with the same seed as
+    /// `self`, so both functions can be swapped for each other if they were built from
+    /// the same keys
+    ///
+    /// Type parameters are the same by construction; see
+    /// [`AnyPhf::is_compatible`](crate::dynamic::AnyPhf::is_compatible) to compare
+    /// functions of any type. Functions which are also [equal](PartialEq) return the
+    /// same hash for every key.
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        self.seed == other.seed
+    }
+
+    /// Returns the load factor of the function, ie. its number of keys divided by its
+    /// [table size](PhfQuery::table_size)
+    ///
+    /// This is the [`alpha`](BuildConfiguration::alpha) it was built with, up to the
+    /// rounding of the table size, unless a
+    /// [`table_size`](BuildConfiguration::table_size) was configured instead.
+    pub fn alpha(&self) -> f64 {
+        self.num_keys() as f64 / self.table_size() as f64
+    }
+
+    /// Returns the [`c`](BuildConfiguration::c) parameter the function was built with,
+    /// if it was built by this process
+    ///
+    /// PTHash does not store it in functions, so this is `None` for
+    /// [loaded](Phf::load) functions.
+    pub fn c(&self) -> Option<f64> {
+        self.build_configuration.as_ref().map(|config| config.c)
+    }
+
+    /// Returns the number of partitions of the function
+    pub fn num_partitions(&self) -> u64 {
+        self.partition_offsets().len() as u64
+    }
+
+    /// Returns the partition `key` is routed to, in the range `[0; num_partitions)`
+    ///
+    /// Like [`PhfQuery::hash`], this also returns a partition for keys the function
+    /// was not built from.
+    pub fn partition_of(&self, key: impl Hashable) -> u64 {
+        self.partition_of_position(self.hash(key))
+    }
+
+    /// Returns the partition containing `position`, a value returned by
+    /// [`PhfQuery::hash`]
+    ///
+    /// Partitions cover consecutive ranges of positions, in order.
+    pub fn partition_of_position(&self, position: u64) -> u64 {
+        let offsets = self.partition_offsets();
+        offsets
+            .partition_point(|&offset| offset <= position)
+            .saturating_sub(1) as u64
+    }
+
+    fn partition_offsets(&self) -> &[u64] {
+        self.partition_offsets
+            .get_or_init(|| self.inner.partition_offsets())
+    }
+
+    /// Returns a pointer to the underlying `pthash::partitioned_phf` C++ object
+    ///
+    /// It remains owned by `self`, and must not be mutated through this pointer.
+    pub fn as_raw(&self) -> *const c_void {
+        self.inner.as_ptr() as *const c_void
+    }
+
+    /// Releases ownership of the underlying `pthash::partitioned_phf` C++ object, which
+    /// must eventually be passed back to [`Self::from_raw`] or freed with C++'s
+    /// `delete`
+    ///
+    /// The [build configuration](Phf::build_configuration) is lost.
+    pub fn into_raw(self) -> *mut c_void {
+        self.inner.into_raw() as *mut c_void
+    }
+
+    /// Takes ownership of a `pthash::partitioned_phf` C++ object
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be non-null, and returned by [`Self::into_raw`] on a function with
+    /// the same type parameters (or allocated with C++'s `new` as the matching
+    /// `pthash::partitioned_phf` instantiation); it must not be used after this call.
+    pub unsafe fn from_raw(ptr: *mut c_void) -> Self {
+        let inner = UniquePtr::from_raw(ptr as *mut _);
+        PartitionedPhf {
+            seed: inner.seed(),
+            inner,
+            build_configuration: None,
+            partition_offsets: OnceLock::new(),
             marker: PhantomData,
         }
     }
+
+    /// Returns the serialized form of this function, in the same format as
+    /// [`Phf::save`] but without the [footer](PhfMetadata)
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Exception> {
+        self.inner.save_bytes()
+    }
+
+    /// Deserializes a function returned by [`Self::to_bytes`]
+    ///
+    /// Trailing bytes are ignored, so this also accepts the contents of files written
+    /// by [`Phf::save`].
+    ///
+    /// Fails on truncated data and on inconsistent sizes (eg. fewer pilots than
+    /// buckets), like [`Phf::load`]. The internal structure of the encoder is not
+    /// checked, so corrupted pilots may still return wrong hashes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Exception> {
+        let mut f = Self::new();
+        f.inner.pin_mut().load_bytes(bytes)?;
+        f.seed = f.inner.seed();
+        Ok(f)
+    }
 }
 
+#[cfg(feature = "build")]
 macro_rules! build_in_internal_memory_from_bytes {
     ($self:expr, $keys:expr, $config:expr, $into_iter:ident) => {{
         let mut keys = $keys;
@@ -50,70 +259,144 @@ macro_rules! build_in_internal_memory_from_bytes {
         // This is a Rust rewrite of internal_memory_builder_partitioned_phf::build_from_keys
         // so we can use generics
 
+        config.validate()?;
+        if config.check_duplicates && !config.deduplicate {
+            crate::builders::check_duplicate_keys!(H, keys, $into_iter);
+        }
+
         let mut config = (*config).clone();
-        if !crate::utils::valid_seed(config.seed) {
-            let mut rng = rand::rng();
-            config.seed = rng.random();
+        let seeds = if crate::utils::valid_seed(config.seed) {
+            vec![config.seed]
+        } else if config.check_collisions {
+            crate::utils::random_seeds(10)?
+        } else {
+            crate::utils::random_seeds(1)?
+        };
+
+        // Only retried if hashes collide
+        let mut attempts = Vec::new();
+        let mut hashed = None;
+        for seed in seeds {
+            let start = std::time::Instant::now();
+            let mut hashes = crate::builders::hash_keys!(H, keys(), seed, &config, $into_iter);
+            let hashing_peak_memory = crate::builders::peak_memory();
+            let num_duplicate_keys = if config.deduplicate {
+                crate::builders::deduplicate_hashes(&mut hashes)
+            } else {
+                0
+            };
+            if config.check_collisions && crate::builders::has_collisions(&hashes) {
+                log::info!("Attempt {} failed: hash collision", attempts.len() + 1);
+                attempts.push(SeedAttempt {
+                    seed,
+                    duration: start.elapsed(),
+                    error: Some(crate::builders::HASH_COLLISION_ERROR.to_owned()),
+                });
+                continue;
+            }
+            let hashing_seconds = start.elapsed();
+            hashed = Some((
+                seed,
+                start,
+                hashes,
+                hashing_seconds,
+                num_duplicate_keys,
+                hashing_peak_memory,
+            ));
+            break;
         }
-        $self.seed = config.seed;
+        let Some((seed, start, hashes, hashing_seconds, num_duplicate_keys, hashing_peak_memory)) =
+            hashed
+        else {
+            return Err(BuildError::SeedsExhausted { attempts });
+        };
+        config.seed = seed;
+        $self.seed = seed;
 
-        let hashes: Vec<_> = keys().$into_iter().map(|key| H::hash(key, config.seed)).collect();
+        config.resolve_alpha(hashes.len() as u64)?;
+        config.adjust_for_small_set(hashes.len() as u64);
 
         let mut builder =
             <<M as SealedMinimality>::PartitionedPhfBackend<H::Hash, E> as BackendPhf>::Builder::new();
 
-        let config = config.to_ffi(M::AS_BOOL);
+        let ffi_config = config.to_ffi(M::AS_BOOL);
         let mut timings = unsafe {
             builder
                 .pin_mut()
-                .build_from_hashes(hashes.as_ptr(), hashes.len() as u64, &config)
+                .build_from_hashes(hashes.as_ptr(), hashes.len() as u64, &ffi_config)
         }?;
 
-        timings.encoding_seconds = $self.inner.pin_mut().build(&builder, &config)?;
-        Ok(BuildTimings::from_ffi(&timings))
+        config.check_cancelled()?;
+        timings.encoding_seconds = $self.inner.pin_mut().build(&builder, &ffi_config)?;
+        $self.partition_offsets = OnceLock::new();
+        if config.verify {
+            crate::builders::verify!(&*$self, keys, $into_iter);
+        }
+        $self.build_configuration = Some(config);
+        let mut timings = BuildTimings::from_ffi(&timings);
+        timings.hashing_seconds = hashing_seconds;
+        timings.num_duplicate_keys = num_duplicate_keys;
+        attempts.push(SeedAttempt {
+            seed,
+            duration: start.elapsed(),
+            error: None,
+        });
+        timings.attempts = attempts;
+        timings.hashing_peak_memory = hashing_peak_memory;
+        timings.peak_memory = crate::builders::peak_memory();
+        Ok(timings)
     }}
 }
 
 impl<M: Minimality, H: Hasher, E: Encoder> Phf for PartitionedPhf<M, H, E> {
-    const MINIMAL: bool = M::AS_BOOL;
-
+    #[cfg(feature = "build")]
     fn build_in_internal_memory_from_bytes<Keys: IntoIterator>(
         &mut self,
         keys: impl FnMut() -> Keys,
         config: &BuildConfiguration,
-    ) -> Result<BuildTimings, Exception>
+    ) -> Result<BuildTimings, BuildError>
     where
         <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
     {
         build_in_internal_memory_from_bytes!(self, keys, config, into_iter)
     }
 
-    #[cfg(feature = "rayon")]
+    #[cfg(feature = "build")]
+    fn build_in_internal_memory_from_iter<Keys: IntoIterator>(
+        &mut self,
+        keys: Keys,
+        config: &BuildConfiguration,
+    ) -> Result<BuildTimings, BuildError>
+    where
+        <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
+    {
+        config.check_keys_read_once()?;
+        let mut keys = Some(keys);
+        build_in_internal_memory_from_bytes!(self, || keys.take(), config, once)
+    }
+
+    #[cfg(all(feature = "build", feature = "rayon"))]
     fn par_build_in_internal_memory_from_bytes<Keys: IntoParallelIterator>(
         &mut self,
         keys: impl FnMut() -> Keys,
         config: &BuildConfiguration,
-    ) -> Result<BuildTimings, Exception>
+    ) -> Result<BuildTimings, BuildError>
     where
         <<Keys as IntoParallelIterator>::Iter as ParallelIterator>::Item: Hashable,
     {
         build_in_internal_memory_from_bytes!(self, keys, config, into_par_iter)
     }
 
-    fn hash(&self, key: impl Hashable) -> u64 {
-        self.inner.position(H::hash(key, self.seed))
+    fn build_configuration(&self) -> Option<&BuildConfiguration> {
+        self.build_configuration.as_ref()
     }
 
-    fn num_bits(&self) -> usize {
-        self.inner.num_bits()
+    fn memory_usage(&self) -> usize {
+        self.inner.memory_usage()
     }
 
-    fn num_keys(&self) -> u64 {
-        self.inner.num_keys()
-    }
-
-    fn table_size(&self) -> u64 {
-        self.inner.table_size()
+    fn num_bits_breakdown(&self) -> NumBitsBreakdown {
+        NumBitsBreakdown::from_ffi(&self.inner.num_bits_breakdown())
     }
 
     fn save(&mut self, path: impl AsRef<Path>) -> Result<usize, Exception> {
@@ -121,9 +404,16 @@ impl<M: Minimality, H: Hasher, E: Encoder> Phf for PartitionedPhf<M, H, E> {
         path.push(0); // null terminator
         let path = path.as_ptr() as *const i8;
 
-        unsafe { self.inner.pin_mut().save(path) }
+        let num_bytes = unsafe { self.inner.pin_mut().save(path) }?;
+        let footer = Self::metadata().to_footer();
+        Ok(num_bytes + unsafe { crate::utils::append_bytes(path as *const c_char, &footer) }?)
+    }
+    fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let path = path.as_ref();
+        PhfMetadata::read(path)?.check::<Self>(&Self::metadata())?;
+        Ok(Self::load_raw(path)?)
     }
-    fn load(path: impl AsRef<Path>) -> Result<Self, Exception> {
+    fn load_raw(path: impl AsRef<Path>) -> Result<Self, Exception> {
         let mut f = Self::new();
 
         let mut path = path.as_ref().as_os_str().to_owned().into_encoded_bytes();
@@ -136,4 +426,82 @@ impl<M: Minimality, H: Hasher, E: Encoder> Phf for PartitionedPhf<M, H, E> {
 
         Ok(f)
     }
+    fn save_to(&mut self, mut writer: impl Write) -> std::io::Result<usize> {
+        let bytes = self.to_bytes().map_err(std::io::Error::other)?;
+        let footer = Self::metadata().to_footer();
+        writer.write_all(&bytes)?;
+        writer.write_all(&footer)?;
+        Ok(bytes.len() + footer.len())
+    }
+    fn load_from(mut reader: impl Read) -> std::io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let load = || -> Result<Self, LoadError> {
+            PhfMetadata::from_suffix(&bytes)?.check::<Self>(&Self::metadata())?;
+            Ok(Self::from_bytes(&bytes)?)
+        };
+        load().map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(feature = "minimal")]
+impl<H: Hasher, E: Encoder> crate::MinimalPhf for PartitionedPhf<crate::Minimal, H, E> {}
+
+impl<M: Minimality, H: Hasher, E: Encoder> PhfQuery for PartitionedPhf<M, H, E> {
+    const MINIMAL: bool = M::AS_BOOL;
+
+    fn hash(&self, key: impl Hashable) -> u64 {
+        let position = self.inner.position(H::hash(key, self.seed));
+        #[cfg(feature = "debug-checks")]
+        crate::utils::check_position(self, position);
+        position
+    }
+
+    fn hash_many<K: Hashable>(&self, keys: impl IntoIterator<Item = K>) -> Vec<u64> {
+        let positions = crate::utils::hash_many::<_, H, _>(&*self.inner, self.seed, keys);
+        #[cfg(feature = "debug-checks")]
+        for &position in &positions {
+            crate::utils::check_position(self, position);
+        }
+        positions
+    }
+
+    fn hash_many_into<K: Hashable>(&self, keys: impl IntoIterator<Item = K>, out: &mut [u64]) {
+        crate::utils::hash_many_into::<_, H, _>(&*self.inner, self.seed, keys, out);
+        #[cfg(feature = "debug-checks")]
+        for &position in out.iter() {
+            crate::utils::check_position(self, position);
+        }
+    }
+
+    fn num_bits(&self) -> usize {
+        self.inner.num_bits()
+    }
+
+    fn num_keys(&self) -> u64 {
+        self.inner.num_keys()
+    }
+
+    fn table_size(&self) -> u64 {
+        self.inner.table_size()
+    }
+}
+
+/// Serializes the function as bytes returned by [`PartitionedPhf::to_bytes`]
+#[cfg(feature = "serde")]
+impl<M: Minimality, H: Hasher, E: Encoder> serde::Serialize for PartitionedPhf<M, H, E> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes = self.to_bytes().map_err(serde::ser::Error::custom)?;
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, M: Minimality, H: Hasher, E: Encoder> serde::Deserialize<'de>
+    for PartitionedPhf<M, H, E>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = crate::utils::deserialize_bytes(deserializer)?;
+        Self::from_bytes(&bytes).map_err(serde::de::Error::custom)
+    }
 }
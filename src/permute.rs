@@ -0,0 +1,62 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use crate::{Hashable, PhfQuery};
+
+/// Reorders `values` in place so that `values[f.hash(&keys[i])]` is the value that was
+/// at `values[i]`
+///
+/// This follows the cycles of the permutation instead of allocating a second array of
+/// values, so it only needs one bit of extra memory per value; at the cost of
+/// hashing every key once.
+///
+/// # Panics
+///
+/// If `f` is not [minimal](PhfQuery::MINIMAL), if `keys`, `values`, and `f` have
+/// different lengths, or if `f` was not built from `keys`.
+pub fn permute_by_phf<F: PhfQuery, K: Hashable, V>(f: &F, keys: &[K], values: &mut [V]) {
+    assert!(F::MINIMAL, "permute_by_phf requires a minimal function");
+    assert_eq!(
+        keys.len(),
+        values.len(),
+        "keys and values have different lengths"
+    );
+    assert_eq!(
+        keys.len() as u64,
+        f.num_keys(),
+        "function was built from a different number of keys"
+    );
+
+    let destination = |i: usize| -> usize {
+        let position = f.hash(&keys[i]);
+        assert!(
+            position < keys.len() as u64,
+            "function was not built from these keys"
+        );
+        position as usize
+    };
+
+    let mut done = vec![0u64; keys.len().div_ceil(64)];
+    for start in 0..keys.len() {
+        if done[start / 64] & (1 << (start % 64)) != 0 {
+            continue;
+        }
+        // values[start] holds the value of the cycle not yet moved to its destination
+        let mut i = start;
+        loop {
+            done[i / 64] |= 1 << (i % 64);
+            let dest = destination(i);
+            if dest == start {
+                break;
+            }
+            assert!(
+                done[dest / 64] & (1 << (dest % 64)) == 0,
+                "function was not built from these keys"
+            );
+            values.swap(start, dest);
+            i = dest;
+        }
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Python bindings, to build and query functions from Python
+//!
+//! Only available with the `python` feature. The extension module is built with
+//! [maturin](https://www.maturin.rs/):
+//!
+//! ```text
+//! maturin build --release --features python,pyo3/extension-module
+//! ```
+//!
+//! It provides a `pthash.Phf` class:
+//!
+//! ```text
+//! >>> import numpy, pthash
+//! >>> f = pthash.Phf.build([b"foo", b"bar", b"baz"])
+//! >>> sorted(f.hash(key) for key in [b"foo", b"bar", b"baz"])
+//! [0, 1, 2]
+//! >>> f.hash_many([b"foo", b"bar", b"baz"])
+//! array([...], dtype=uint64)
+//! >>> f.hash_u64_array(numpy.arange(10, dtype=numpy.uint64))
+//! array([...], dtype=uint64)
+//! >>> f.save("keys.phf")
+//! ```
+//!
+//! Like the [C interface](crate::capi), functions are non-partitioned and use
+//! [`MurmurHash2_64`], and files are compatible with [`load_any`].
+
+use std::path::PathBuf;
+
+use numpy::{PyArray1, PyReadonlyArray1};
+use pyo3::exceptions::{PyOSError, PyValueError};
+use pyo3::prelude::*;
+
+use crate::build::BuildConfiguration;
+use crate::dynamic::{load_any, AnyPhf, DynSinglePhf};
+use crate::encoders::EncoderKind;
+use crate::hashing::MurmurHash2_64;
+use crate::minimality::{Minimal, Nonminimal};
+use crate::Phf;
+
+/// Perfect-hash function on byte strings (`pthash.Phf`)
+#[pyclass(name = "Phf", module = "pthash")]
+pub struct PyPhf(AnyPhf);
+
+#[pymethods]
+impl PyPhf {
+    /// Builds a function from a list of distinct byte strings
+    ///
+    /// `encoder` is the name of an encoder (eg. `"dictionary_dictionary"`), or `None`
+    /// for the first enabled one. The GIL is released while building.
+    #[staticmethod]
+    #[pyo3(signature = (keys, minimal = true, encoder = None, num_threads = 1))]
+    fn build(
+        py: Python<'_>,
+        keys: Vec<Vec<u8>>,
+        minimal: bool,
+        encoder: Option<&str>,
+        num_threads: u64,
+    ) -> PyResult<Self> {
+        let encoder = match encoder {
+            None => EncoderKind::ALL[0],
+            Some(name) => EncoderKind::from_name(name).ok_or_else(|| {
+                PyValueError::new_err(format!("unknown or disabled encoder: {name}"))
+            })?,
+        };
+        let mut config = BuildConfiguration::default();
+        config.verbose_output = false;
+        config.num_threads = num_threads.max(1);
+        py.allow_threads(|| {
+            if minimal {
+                let mut f = DynSinglePhf::<Minimal, MurmurHash2_64>::new(encoder);
+                f.build_in_internal_memory_from_bytes(|| &keys, &config)
+                    .map(|_| AnyPhf::MinimalSingle64(f))
+            } else {
+                let mut f = DynSinglePhf::<Nonminimal, MurmurHash2_64>::new(encoder);
+                f.build_in_internal_memory_from_bytes(|| &keys, &config)
+                    .map(|_| AnyPhf::NonminimalSingle64(f))
+            }
+        })
+        .map(PyPhf)
+        .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Loads a function saved by `save` (or by Rust programs)
+    #[staticmethod]
+    fn load(path: PathBuf) -> PyResult<Self> {
+        load_any(path)
+            .map(PyPhf)
+            .map_err(|e| PyOSError::new_err(e.to_string()))
+    }
+
+    /// Writes the function to `path`, and returns the number of bytes written
+    fn save(&mut self, path: PathBuf) -> PyResult<usize> {
+        self.0
+            .save(path)
+            .map_err(|e| PyOSError::new_err(e.to_string()))
+    }
+
+    /// Returns the hash of a byte string
+    fn hash(&self, key: &[u8]) -> u64 {
+        self.0.hash(key)
+    }
+
+    /// Returns the hashes of a list of byte strings, as a numpy array
+    fn hash_many<'py>(&self, py: Python<'py>, keys: Vec<Vec<u8>>) -> Bound<'py, PyArray1<u64>> {
+        PyArray1::from_vec(py, self.0.hash_many(&keys))
+    }
+
+    /// Returns the hashes of a numpy array of 64-bit integers, as a numpy array
+    ///
+    /// Integers are hashed like `u64` keys by Rust programs.
+    fn hash_u64_array<'py>(
+        &self,
+        py: Python<'py>,
+        keys: PyReadonlyArray1<'py, u64>,
+    ) -> Bound<'py, PyArray1<u64>> {
+        PyArray1::from_vec(py, self.0.hash_many(keys.as_array().iter().copied()))
+    }
+
+    /// Largest value returned by `hash` plus 1
+    #[getter]
+    fn table_size(&self) -> u64 {
+        self.0.table_size()
+    }
+
+    /// Whether hashes are in `range(len(self))`
+    #[getter]
+    fn minimal(&self) -> bool {
+        self.0.is_minimal()
+    }
+
+    /// Number of keys the function was built from
+    fn __len__(&self) -> usize {
+        self.0.num_keys() as usize
+    }
+}
+
+#[pymodule]
+fn pthash(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyPhf>()
+}
@@ -0,0 +1,127 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Concurrent construction of many functions within a global memory and thread
+//! budget ([`BuildScheduler`])
+
+use std::collections::VecDeque;
+use std::sync::{Condvar, Mutex};
+
+pub use crate::tuning::estimate_build_ram;
+
+#[derive(Debug)]
+struct Available {
+    ram: u64,
+    num_threads: u64,
+}
+
+/// Builds many functions (eg. one per shard) concurrently, without exceeding a
+/// global memory and thread budget
+///
+/// Each job runs on a dedicated thread, which reserves the amount of memory the
+/// job is estimated to need and a share of the threads before starting it, and
+/// waits for other jobs to release them if they are not available. Builds whose
+/// estimates do not fit in the budget together are therefore staggered instead of
+/// all allocating at once, and waiting never blocks a rayon worker thread: builds
+/// themselves (eg. [`par_build_in_internal_memory_from_bytes`](crate::Phf::par_build_in_internal_memory_from_bytes))
+/// share the current rayon thread pool.
+#[derive(Debug)]
+pub struct BuildScheduler {
+    ram: u64,
+    num_threads: u64,
+    available: Mutex<Available>,
+    released: Condvar,
+}
+
+impl BuildScheduler {
+    /// Creates a scheduler allowing at most `ram` bytes and `num_threads` threads to
+    /// be used by concurrent builds
+    pub fn new(ram: u64, num_threads: u64) -> Self {
+        let num_threads = num_threads.max(1);
+        BuildScheduler {
+            ram,
+            num_threads,
+            available: Mutex::new(Available { ram, num_threads }),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Runs `build` on every job and returns their results, in the same order
+    ///
+    /// `estimate_ram` should return how much memory building a job needs (see
+    /// [`estimate_build_ram`]); jobs needing more than the whole budget run alone.
+    /// `build` is given the job and the number of threads it may use, which should
+    /// be set as [`BuildConfiguration::num_threads`](crate::BuildConfiguration::num_threads).
+    pub fn run<J: Send, T: Send>(
+        &self,
+        jobs: Vec<J>,
+        estimate_ram: impl Fn(&J) -> u64 + Sync,
+        build: impl Fn(J, u64) -> T + Sync,
+    ) -> Vec<T> {
+        let num_jobs = jobs.len();
+        let concurrency = (num_jobs as u64).min(self.num_threads).max(1);
+        let threads_per_job = self.num_threads / concurrency;
+        let queue = Mutex::new(jobs.into_iter().enumerate().collect::<VecDeque<_>>());
+        let results = Mutex::new((0..num_jobs).map(|_| None).collect::<Vec<_>>());
+
+        // Reservations are taken outside rayon, on threads of our own, so a job
+        // waiting for memory cannot hold a worker thread that other jobs' builds
+        // need to make progress
+        std::thread::scope(|scope| {
+            for _ in 0..concurrency {
+                scope.spawn(|| loop {
+                    let Some((i, job)) = queue.lock().unwrap().pop_front() else {
+                        break;
+                    };
+                    let ram = estimate_ram(&job).min(self.ram);
+                    let result = {
+                        let _reservation = self.reserve(ram, threads_per_job);
+                        build(job, threads_per_job)
+                    };
+                    results.lock().unwrap()[i] = Some(result);
+                });
+            }
+        });
+
+        results
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .map(|result| result.expect("Job did not run"))
+            .collect()
+    }
+
+    fn reserve(&self, ram: u64, num_threads: u64) -> Reservation<'_> {
+        let mut available = self
+            .released
+            .wait_while(self.available.lock().unwrap(), |available| {
+                available.ram < ram || available.num_threads < num_threads
+            })
+            .unwrap();
+        available.ram -= ram;
+        available.num_threads -= num_threads;
+        Reservation {
+            scheduler: self,
+            ram,
+            num_threads,
+        }
+    }
+}
+
+/// Resources reserved by a job, released when dropped
+struct Reservation<'a> {
+    scheduler: &'a BuildScheduler,
+    ram: u64,
+    num_threads: u64,
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        let mut available = self.scheduler.available.lock().unwrap();
+        available.ram += self.ram;
+        available.num_threads += self.num_threads;
+        self.scheduler.released.notify_all();
+    }
+}
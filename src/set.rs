@@ -0,0 +1,238 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Static sets ([`PhfSet`]), which store a fingerprint of each key next to a
+//! perfect-hash function in order to reject most keys which are not in the set
+
+use std::fs::{File, OpenOptions};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+
+use cxx::Exception;
+use thiserror::Error;
+
+#[cfg(feature = "build")]
+use crate::build::{BuildConfiguration, BuildError};
+use crate::metadata::{load_prefix, LoadError};
+use crate::{Hashable, Hasher, MurmurHash2_64, Phf, PhfQuery};
+
+/// Last bytes of files written by [`PhfSet::save`]
+pub const SET_MAGIC: &[u8; 8] = b"PTHASHst";
+
+/// Seed of the [`MurmurHash2_64`] hash fingerprints are computed from
+///
+/// It is fixed so fingerprints do not depend on the function's seed.
+const FINGERPRINT_SEED: u64 = 0x5bd1e9955bd1e995;
+
+const TRAILER_LEN: usize = 24 + SET_MAGIC.len();
+
+#[derive(Error, Debug)]
+pub enum PhfSetError {
+    #[error("Could not save function: {0}")]
+    Backend(#[from] Exception),
+    #[error("Could not load function: {0}")]
+    Load(#[from] LoadError),
+    #[error("Could not save or load fingerprints: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid set file: {0}")]
+    Invalid(&'static str),
+}
+
+/// A static set of keys of type `K`, backed by the perfect-hash function `F`
+///
+/// Each position of the function's table stores a `fingerprint_bits`-bit fingerprint
+/// of the key hashed to that position, so [`Self::contains`] returns `false` for
+/// keys which are not in the set with probability `1 - 2^-fingerprint_bits`.
+pub struct PhfSet<K, F: Phf> {
+    phf: F,
+    fingerprint_bits: u32,
+    fingerprints: Vec<u64>,
+    marker: PhantomData<fn(&K)>,
+}
+
+#[inline(always)]
+fn fingerprint(key: impl Hashable, fingerprint_bits: u32) -> u64 {
+    MurmurHash2_64::hash(key, FINGERPRINT_SEED).first() & (u64::MAX >> (64 - fingerprint_bits))
+}
+
+impl<K: Hashable, F: Phf> PhfSet<K, F> {
+    /// Builds `phf` (usually a new function) from the keys, and computes their
+    /// fingerprints
+    ///
+    /// Only available with the `build` feature (enabled by default).
+    ///
+    /// # Panics
+    ///
+    /// If `fingerprint_bits` is not in `1..=64`
+    #[cfg(feature = "build")]
+    pub fn build<Keys: IntoIterator>(
+        mut phf: F,
+        mut keys: impl FnMut() -> Keys,
+        fingerprint_bits: u32,
+        config: &BuildConfiguration,
+    ) -> Result<Self, BuildError>
+    where
+        <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
+    {
+        assert!(
+            (1..=64).contains(&fingerprint_bits),
+            "fingerprint_bits must be in 1..=64, got {fingerprint_bits}"
+        );
+        phf.build_in_internal_memory_from_bytes(&mut keys, config)?;
+        let num_words = (phf.table_size() * u64::from(fingerprint_bits)).div_ceil(64);
+        let mut set = PhfSet {
+            phf,
+            fingerprint_bits,
+            fingerprints: vec![0; num_words as usize],
+            marker: PhantomData,
+        };
+        for key in keys() {
+            let position = set.phf.hash(&key);
+            set.set_fingerprint(position, fingerprint(&key, fingerprint_bits));
+        }
+        Ok(set)
+    }
+
+    #[cfg(feature = "build")]
+    fn set_fingerprint(&mut self, position: u64, value: u64) {
+        let bit = position * u64::from(self.fingerprint_bits);
+        let (word, offset) = ((bit / 64) as usize, bit % 64);
+        // Slots are overwritten when deduplicated keys share a position
+        let mask = u64::MAX >> (64 - self.fingerprint_bits);
+        self.fingerprints[word] &= !(mask << offset);
+        self.fingerprints[word] |= value << offset;
+        if offset + u64::from(self.fingerprint_bits) > 64 {
+            self.fingerprints[word + 1] &= !(mask >> (64 - offset));
+            self.fingerprints[word + 1] |= value >> (64 - offset);
+        }
+    }
+
+    #[inline(always)]
+    fn get_fingerprint(&self, position: u64) -> u64 {
+        let bit = position * u64::from(self.fingerprint_bits);
+        let (word, offset) = ((bit / 64) as usize, bit % 64);
+        let mut value = self.fingerprints[word] >> offset;
+        if offset + u64::from(self.fingerprint_bits) > 64 {
+            value |= self.fingerprints[word + 1] << (64 - offset);
+        }
+        value & (u64::MAX >> (64 - self.fingerprint_bits))
+    }
+
+    /// Returns the hash of `key` if it is (probably) in the set, or `None` if it is
+    /// certainly not
+    #[inline(always)]
+    pub fn hash_checked(&self, key: &K) -> Option<u64> {
+        let position = self.phf.hash(key);
+        (self.get_fingerprint(position) == fingerprint(key, self.fingerprint_bits))
+            .then_some(position)
+    }
+
+    /// Returns whether `key` is (probably) in the set
+    #[inline(always)]
+    pub fn contains(&self, key: &K) -> bool {
+        self.hash_checked(key).is_some()
+    }
+
+    /// Returns the number of keys in this set
+    pub fn len(&self) -> usize {
+        self.phf.num_keys() as usize
+    }
+
+    /// Returns whether this set has no keys
+    pub fn is_empty(&self) -> bool {
+        self.phf.num_keys() == 0
+    }
+
+    /// Returns the number of bits of each fingerprint
+    pub fn fingerprint_bits(&self) -> u32 {
+        self.fingerprint_bits
+    }
+
+    /// Returns the function mapping keys to their positions
+    pub fn phf(&self) -> &F {
+        &self.phf
+    }
+
+    /// Dumps both the function and the fingerprints to a single file
+    ///
+    /// The function is written first with [`Phf::save`], followed by the fingerprints
+    /// and a trailer giving their size and where they start.
+    pub fn save(&mut self, path: impl AsRef<Path>) -> Result<usize, PhfSetError> {
+        let path = path.as_ref();
+        self.phf.save(path)?;
+
+        let file = OpenOptions::new().append(true).open(path)?;
+        let phf_len = file.metadata()?.len();
+        let mut file = BufWriter::new(file);
+        for word in &self.fingerprints {
+            file.write_all(&word.to_le_bytes())?;
+        }
+        file.write_all(&u64::from(self.fingerprint_bits).to_le_bytes())?;
+        file.write_all(&(self.fingerprints.len() as u64).to_le_bytes())?;
+        file.write_all(&phf_len.to_le_bytes())?;
+        file.write_all(SET_MAGIC)?;
+        file.flush()?;
+
+        Ok(phf_len as usize + self.fingerprints.len() * 8 + TRAILER_LEN)
+    }
+
+    /// Loads a set written by [`Self::save`]
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PhfSetError> {
+        let path = path.as_ref();
+        let mut file = File::open(path)?;
+        let file_len = file.metadata()?.len();
+        if file_len < TRAILER_LEN as u64 {
+            return Err(PhfSetError::Invalid("file is too short"));
+        }
+        file.seek(SeekFrom::End(-(TRAILER_LEN as i64)))?;
+        let mut trailer = [0; TRAILER_LEN];
+        file.read_exact(&mut trailer)?;
+        if &trailer[24..] != SET_MAGIC {
+            return Err(PhfSetError::Invalid("missing set trailer"));
+        }
+        let fingerprint_bits = u64::from_le_bytes(trailer[0..8].try_into().unwrap());
+        let num_words = u64::from_le_bytes(trailer[8..16].try_into().unwrap());
+        let phf_len = u64::from_le_bytes(trailer[16..24].try_into().unwrap());
+        let phf = load_prefix::<F>(path, phf_len)?;
+        if !(1..=64).contains(&fingerprint_bits) {
+            return Err(PhfSetError::Invalid("invalid number of fingerprint bits"));
+        }
+        let expected_num_words = phf
+            .table_size()
+            .checked_mul(fingerprint_bits)
+            .ok_or(PhfSetError::Invalid("table size is too large"))?
+            .div_ceil(64);
+        if num_words != expected_num_words {
+            return Err(PhfSetError::Invalid(
+                "size of fingerprints does not match the function",
+            ));
+        }
+        let expected_file_len = num_words
+            .checked_mul(8)
+            .and_then(|fingerprints_len| fingerprints_len.checked_add(phf_len))
+            .and_then(|len| len.checked_add(TRAILER_LEN as u64));
+        if expected_file_len != Some(file_len) {
+            return Err(PhfSetError::Invalid("unexpected file length"));
+        }
+
+        file.seek(SeekFrom::Start(phf_len))?;
+        let mut file = BufReader::new(file);
+        let mut buf = [0; 8];
+        let fingerprints = (0..num_words)
+            .map(|_| {
+                file.read_exact(&mut buf)?;
+                Ok(u64::from_le_bytes(buf))
+            })
+            .collect::<std::io::Result<_>>()?;
+
+        Ok(PhfSet {
+            phf,
+            fingerprint_bits: fingerprint_bits as u32,
+            fingerprints,
+            marker: PhantomData,
+        })
+    }
+}
@@ -5,62 +5,81 @@
 
 #![allow(non_camel_case_types)]
 
+#[cfg(feature = "build")]
+use std::any::Any;
+use std::ffi::{c_char, c_void};
+use std::fmt;
+use std::io::{ErrorKind, Read, Write};
 use std::marker::PhantomData;
 use std::path::Path;
+#[cfg(feature = "build")]
+use std::time::Duration;
 
 //use autocxx::prelude::*;
 use cxx::{Exception, UniquePtr};
-use rand::Rng;
-#[cfg(feature = "rayon")]
+#[cfg(all(feature = "build", feature = "rayon"))]
 use rayon::prelude::*;
 
 use crate::backends::BackendPhf;
-use crate::build::{BuildConfiguration, BuildTimings, Builder};
+#[cfg(feature = "build")]
+use crate::build::ffi::build_configuration;
+use crate::build::BuildConfiguration;
+#[cfg(feature = "build")]
+use crate::build::{BuildError, BuildTimings, SeedAttempt};
+#[cfg(feature = "build")]
+use crate::builders::Builder;
 use crate::encoders::Encoder;
 use crate::hashing::{Hashable, Hasher};
-use crate::{Minimality, Phf, SealedMinimality};
-
-/// Non-partitioned minimal perfect-hash function
-///
-/// This is a binding for `pthash::single_phf<H, dictionary_dictionary, true>`
-pub struct SinglePhf<M: Minimality, H: Hasher, E: Encoder> {
-    inner: UniquePtr<<M as SealedMinimality>::SinglePhfBackend<H::Hash, E>>,
-    seed: u64,
-    marker: PhantomData<H>,
-}
-
-unsafe impl<M: Minimality, H: Hasher, E: Encoder> Send for SinglePhf<M, H, E> {}
-unsafe impl<M: Minimality, H: Hasher, E: Encoder> Sync for SinglePhf<M, H, E> {}
-
-#[allow(clippy::new_without_default)]
-impl<M: Minimality, H: Hasher, E: Encoder> SinglePhf<M, H, E> {
-    pub fn new() -> Self {
-        SinglePhf {
-            inner: BackendPhf::new(),
-            seed: 0,
-            marker: PhantomData,
-        }
-    }
-}
+use crate::metadata::{LoadError, PhfMetadata};
+use crate::{Minimality, NumBitsBreakdown, Phf, PhfQuery, SealedMinimality};
 
+#[cfg(feature = "build")]
 macro_rules! build_in_internal_memory_from_bytes {
-    ($self:expr, $keys:expr, $config:expr, $into_iter:ident) => {{
+    ($self:expr, $keys:expr, $config:expr, $into_iter:ident) => {
+        build_in_internal_memory_from_bytes!($self, $keys, $config, $into_iter, encode_nothing)
+    };
+    ($self:expr, $keys:expr, $config:expr, $into_iter:ident, $encode_others:expr) => {{
         let mut keys = $keys;
         let config = $config;
 
         // This is a Rust rewrite of internal_memory_builder_single_phf::build_from_keys
         // so we can use generics
 
+        config.validate()?;
+        if config.check_duplicates && !config.deduplicate {
+            crate::builders::check_duplicate_keys!(H, keys, $into_iter);
+        }
+
         let seeds = if crate::utils::valid_seed(config.seed) {
             vec![config.seed]
         } else {
-            let mut rng = rand::rng();
-            (0..10).map(|_| rng.random()).collect()
+            crate::utils::random_seeds(crate::tuning::SMALL_SET_NUM_SEEDS)?
         };
 
-        let mut last_error = None;
-        for (i, seed) in seeds.into_iter().enumerate() {
-            let hashes: Vec<_> = keys().$into_iter().map(|key| H::hash(key, seed)).collect();
+        let mut max_attempts = 10;
+        let mut attempts = Vec::new();
+        for seed in seeds {
+            if attempts.len() >= max_attempts {
+                break;
+            }
+            let start = std::time::Instant::now();
+            let mut hashes = crate::builders::hash_keys!(H, keys(), seed, &config, $into_iter);
+            let hashing_peak_memory = crate::builders::peak_memory();
+            let num_duplicate_keys = if config.deduplicate {
+                crate::builders::deduplicate_hashes(&mut hashes)
+            } else {
+                0
+            };
+            if config.check_collisions && crate::builders::has_collisions(&hashes) {
+                log::info!("Attempt {} failed: hash collision", attempts.len() + 1);
+                attempts.push(SeedAttempt {
+                    seed,
+                    duration: start.elapsed(),
+                    error: Some(crate::builders::HASH_COLLISION_ERROR.to_owned()),
+                });
+                continue;
+            }
+            let hashing_seconds = start.elapsed();
             $self.seed = seed;
 
             let mut builder =
@@ -69,71 +88,357 @@ macro_rules! build_in_internal_memory_from_bytes {
 
             let mut config = (*config).clone();
             config.seed = seed;
+            config.resolve_alpha(hashes.len() as u64)?;
+            config.adjust_for_small_set(hashes.len() as u64);
+            if (hashes.len() as u64) < crate::tuning::SMALL_SET_NUM_KEYS {
+                max_attempts = crate::tuning::SMALL_SET_NUM_SEEDS;
+            }
 
-            let config = config.to_ffi(M::AS_BOOL);
+            let ffi_config = config.to_ffi(M::AS_BOOL);
             let res = unsafe {
-                builder
-                    .pin_mut()
-                    .build_from_hashes(hashes.as_ptr(), hashes.len() as u64, &config)
+                builder.pin_mut().build_from_hashes(
+                    hashes.as_ptr(),
+                    hashes.len() as u64,
+                    &ffi_config,
+                )
             };
             match res {
                 Ok(mut timings) => {
-                    timings.encoding_seconds = $self.inner.pin_mut().build(&builder, &config)?;
-                    return Ok(BuildTimings::from_ffi(&timings));
+                    config.check_cancelled()?;
+                    timings.encoding_seconds =
+                        $self.inner.pin_mut().build(&builder, &ffi_config)?;
+                    if !crate::utils::valid_num_buckets(config.num_buckets) {
+                        config.num_buckets =
+                            crate::tuning::num_buckets(hashes.len() as u64, config.c);
+                    }
+                    ($encode_others)(&*builder, &*ffi_config, &config)?;
+                    if config.verify {
+                        crate::builders::verify!(&*$self, keys, $into_iter);
+                    }
+                    $self.build_configuration = Some(config);
+                    attempts.push(SeedAttempt {
+                        seed,
+                        duration: start.elapsed(),
+                        error: None,
+                    });
+                    let mut timings = BuildTimings::from_ffi(&timings);
+                    timings.hashing_seconds = hashing_seconds;
+                    timings.num_duplicate_keys = num_duplicate_keys;
+                    timings.attempts = attempts;
+                    timings.hashing_peak_memory = hashing_peak_memory;
+                    timings.peak_memory = crate::builders::peak_memory();
+                    return Ok(timings);
                 }
                 Err(e) => {
-                    log::info!("Attempt {} failed", i + 1);
-                    last_error = Some(e);
+                    log::info!("Attempt {} failed", attempts.len() + 1);
+                    attempts.push(SeedAttempt {
+                        seed,
+                        duration: start.elapsed(),
+                        error: Some(e.what().to_owned()),
+                    });
                     // Try again with the next seed, if any
                 }
             }
         }
 
         // All seeds failed
-        Err(last_error.unwrap())
+        Err(BuildError::SeedsExhausted { attempts })
     }};
 }
 
-impl<M: Minimality, H: Hasher, E: Encoder> Phf for SinglePhf<M, H, E> {
-    const MINIMAL: bool = M::AS_BOOL;
+/// Non-partitioned minimal perfect-hash function
+///
+/// This is a binding for `pthash::single_phf<H, dictionary_dictionary, true>`
+pub struct SinglePhf<M: Minimality, H: Hasher, E: Encoder> {
+    inner: UniquePtr<<M as SealedMinimality>::SinglePhfBackend<H::Hash, E>>,
+    seed: u64,
+    build_configuration: Option<BuildConfiguration>,
+    marker: PhantomData<H>,
+}
+
+unsafe impl<M: Minimality, H: Hasher, E: Encoder> Send for SinglePhf<M, H, E> {}
+unsafe impl<M: Minimality, H: Hasher, E: Encoder> Sync for SinglePhf<M, H, E> {}
 
+/// Copies the underlying C++ object, which is as fast as copying its vectors
+impl<M: Minimality, H: Hasher, E: Encoder> Clone for SinglePhf<M, H, E> {
+    fn clone(&self) -> Self {
+        SinglePhf {
+            inner: BackendPhf::copy(&self.inner),
+            seed: self.seed,
+            build_configuration: self.build_configuration.clone(),
+            marker: PhantomData,
+        }
+    }
+}
+
+/// Functions are equal if their serialized representations are, so they return the
+/// same hash for every key
+///
+/// This serializes both functions, so it takes time proportional to their size.
+impl<M: Minimality, H: Hasher, E: Encoder> PartialEq for SinglePhf<M, H, E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.seed == other.seed
+            && match (self.to_bytes(), other.to_bytes()) {
+                (Ok(bytes), Ok(other_bytes)) => bytes == other_bytes,
+                _ => false,
+            }
+    }
+}
+
+impl<M: Minimality, H: Hasher, E: Encoder> Eq for SinglePhf<M, H, E> {}
+
+impl<M: Minimality, H: Hasher, E: Encoder> fmt::Debug for SinglePhf<M, H, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SinglePhf")
+            .field("minimal", &M::AS_BOOL)
+            .field("hash_bits", &(std::mem::size_of::<H::Hash>() * 8))
+            .field("encoder", &E::NAME)
+            .field("num_keys", &self.num_keys())
+            .field("table_size", &self.table_size())
+            .field("bits_per_key", &crate::utils::bits_per_key(self))
+            .field("seed", &self.seed)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Summary of the function, eg. `minimal SinglePhf (64-bit hashes,
+/// dictionary_dictionary encoder): 1000 keys, table size 1063, 3.52 bits/key, seed 42`
+impl<M: Minimality, H: Hasher, E: Encoder> fmt::Display for SinglePhf<M, H, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} SinglePhf ({}-bit hashes, {} encoder): {} keys, table size {}, {:.2} bits/key, seed {}",
+            if M::AS_BOOL { "minimal" } else { "non-minimal" },
+            std::mem::size_of::<H::Hash>() * 8,
+            E::NAME,
+            self.num_keys(),
+            self.table_size(),
+            crate::utils::bits_per_key(self),
+            self.seed,
+        )
+    }
+}
+
+#[allow(clippy::new_without_default)]
+impl<M: Minimality, H: Hasher, E: Encoder> SinglePhf<M, H, E> {
+    pub fn new() -> Self {
+        SinglePhf {
+            inner: BackendPhf::new(),
+            seed: 0,
+            build_configuration: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the description of this type, written in files by [`Phf::save`]
+    pub fn metadata() -> PhfMetadata {
+        PhfMetadata {
+            partitioned: false,
+            minimal: M::AS_BOOL,
+            hash_bits: (std::mem::size_of::<H::Hash>() * 8) as u16,
+            hasher: H::NAME.to_owned(),
+            encoder: E::NAME.to_owned(),
+        }
+    }
+
+    /// Returns the seed of the hashes of keys, picked when building the function
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns whether hashes of keys by `other` are computed with the same seed as
+    /// `self`, so both functions can be swapped for each other if they were built from
+    /// the same keys
+    ///
+    /// Type parameters are the same by construction; see
+    /// [`AnyPhf::is_compatible`](crate::dynamic::AnyPhf::is_compatible) to compare
+    /// functions of any type. Functions which are also [equal](PartialEq) return the
+    /// same hash for every key.
+    pub fn is_compatible(&self, other: &Self) -> bool {
+        self.seed == other.seed
+    }
+
+    /// Returns the load factor of the function, ie. its number of keys divided by its
+    /// [table size](PhfQuery::table_size)
+    ///
+    /// This is the [`alpha`](BuildConfiguration::alpha) it was built with, up to the
+    /// rounding of the table size, unless a
+    /// [`table_size`](BuildConfiguration::table_size) was configured instead.
+    pub fn alpha(&self) -> f64 {
+        self.num_keys() as f64 / self.table_size() as f64
+    }
+
+    /// Returns the [`c`](BuildConfiguration::c) parameter the function was built with,
+    /// if it was built by this process
+    ///
+    /// PTHash does not store it in functions, so this is `None` for
+    /// [loaded](Phf::load) functions.
+    pub fn c(&self) -> Option<f64> {
+        self.build_configuration.as_ref().map(|config| config.c)
+    }
+
+    /// Returns a pointer to the underlying `pthash::single_phf` C++ object
+    ///
+    /// It remains owned by `self`, and must not be mutated through this pointer.
+    pub fn as_raw(&self) -> *const c_void {
+        self.inner.as_ptr() as *const c_void
+    }
+
+    /// Releases ownership of the underlying `pthash::single_phf` C++ object, which
+    /// must eventually be passed back to [`Self::from_raw`] or freed with C++'s
+    /// `delete`
+    ///
+    /// The [build configuration](Phf::build_configuration) is lost.
+    pub fn into_raw(self) -> *mut c_void {
+        self.inner.into_raw() as *mut c_void
+    }
+
+    /// Takes ownership of a `pthash::single_phf` C++ object
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be non-null, and returned by [`Self::into_raw`] on a function with
+    /// the same type parameters (or allocated with C++'s `new` as the matching
+    /// `pthash::single_phf` instantiation); it must not be used after this call.
+    pub unsafe fn from_raw(ptr: *mut c_void) -> Self {
+        let inner = UniquePtr::from_raw(ptr as *mut _);
+        SinglePhf {
+            seed: inner.seed(),
+            inner,
+            build_configuration: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Returns the serialized form of this function, in the same format as
+    /// [`Phf::save`] but without the [footer](PhfMetadata)
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Exception> {
+        self.inner.save_bytes()
+    }
+
+    /// Deserializes a function returned by [`Self::to_bytes`]
+    ///
+    /// Trailing bytes are ignored, so this also accepts the contents of files written
+    /// by [`Phf::save`].
+    ///
+    /// Fails on truncated data and on inconsistent sizes (eg. fewer pilots than
+    /// buckets), like [`Phf::load`]. The internal structure of the encoder is not
+    /// checked, so corrupted pilots may still return wrong hashes.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Exception> {
+        let mut f = Self::new();
+        f.inner.pin_mut().load_bytes(bytes)?;
+        f.seed = f.inner.seed();
+        Ok(f)
+    }
+}
+
+/// PTHash builder of [`SinglePhf<M, H, E>`], which is the same for all encoders
+#[cfg(feature = "build")]
+type SingleBuilder<M, H, E> =
+    <<M as SealedMinimality>::SinglePhfBackend<<H as Hasher>::Hash, E> as BackendPhf>::Builder;
+
+#[cfg(feature = "build")]
+impl<M: Minimality, H: Hasher, E: Encoder> SinglePhf<M, H, E> {
+    /// Same as [`Phf::build_in_internal_memory_from_bytes`], but calls `encode_others`
+    /// once pilots are found, so functions with other encoders can be encoded from the
+    /// same pilots with [`Self::encode_from_builder`]
+    pub(crate) fn build_in_internal_memory_from_bytes_with<Keys: IntoIterator>(
+        &mut self,
+        keys: impl FnMut() -> Keys,
+        config: &BuildConfiguration,
+        mut encode_others: impl FnMut(
+            &SingleBuilder<M, H, E>,
+            &build_configuration,
+            &BuildConfiguration,
+        ) -> Result<(), BuildError>,
+    ) -> Result<BuildTimings, BuildError>
+    where
+        <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
+    {
+        build_in_internal_memory_from_bytes!(self, keys, config, into_iter, encode_others)
+    }
+
+    /// Encodes pilots found while building a function with another encoder, and returns
+    /// the time it took
+    ///
+    /// # Panics
+    ///
+    /// If `builder` is not the builder of a [`SinglePhf`] with the same hash type
+    pub(crate) fn encode_from_builder<B: Builder>(
+        &mut self,
+        builder: &B,
+        ffi_config: &build_configuration,
+        config: &BuildConfiguration,
+    ) -> Result<Duration, BuildError> {
+        let builder = (builder as &dyn Any)
+            .downcast_ref::<SingleBuilder<M, H, E>>()
+            .expect("builder of a different type of function");
+        let encoding_seconds = self.inner.pin_mut().build(builder, ffi_config)?;
+        self.seed = config.seed;
+        self.build_configuration = Some(config.clone());
+        Ok(Duration::from_secs_f64(encoding_seconds))
+    }
+}
+
+/// Default `encode_others` argument of [`build_in_internal_memory_from_bytes`]
+#[cfg(feature = "build")]
+fn encode_nothing<B>(
+    _builder: &B,
+    _ffi_config: &build_configuration,
+    _config: &BuildConfiguration,
+) -> Result<(), BuildError> {
+    Ok(())
+}
+
+impl<M: Minimality, H: Hasher, E: Encoder> Phf for SinglePhf<M, H, E> {
+    #[cfg(feature = "build")]
     fn build_in_internal_memory_from_bytes<Keys: IntoIterator>(
         &mut self,
         keys: impl FnMut() -> Keys,
         config: &BuildConfiguration,
-    ) -> Result<BuildTimings, Exception>
+    ) -> Result<BuildTimings, BuildError>
     where
         <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
     {
         build_in_internal_memory_from_bytes!(self, keys, config, into_iter)
     }
 
-    #[cfg(feature = "rayon")]
+    #[cfg(feature = "build")]
+    fn build_in_internal_memory_from_iter<Keys: IntoIterator>(
+        &mut self,
+        keys: Keys,
+        config: &BuildConfiguration,
+    ) -> Result<BuildTimings, BuildError>
+    where
+        <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
+    {
+        config.check_keys_read_once()?;
+        let mut keys = Some(keys);
+        build_in_internal_memory_from_bytes!(self, || keys.take(), config, once)
+    }
+
+    #[cfg(all(feature = "build", feature = "rayon"))]
     fn par_build_in_internal_memory_from_bytes<Keys: IntoParallelIterator>(
         &mut self,
         keys: impl FnMut() -> Keys,
         config: &BuildConfiguration,
-    ) -> Result<BuildTimings, Exception>
+    ) -> Result<BuildTimings, BuildError>
     where
         <<Keys as IntoParallelIterator>::Iter as ParallelIterator>::Item: Hashable,
     {
         build_in_internal_memory_from_bytes!(self, keys, config, into_par_iter)
     }
 
-    fn hash(&self, key: impl Hashable) -> u64 {
-        self.inner.position(H::hash(key, self.seed))
+    fn build_configuration(&self) -> Option<&BuildConfiguration> {
+        self.build_configuration.as_ref()
     }
 
-    fn num_bits(&self) -> usize {
-        self.inner.num_bits()
+    fn memory_usage(&self) -> usize {
+        self.inner.memory_usage()
     }
 
-    fn num_keys(&self) -> u64 {
-        self.inner.num_keys()
-    }
-
-    fn table_size(&self) -> u64 {
-        self.inner.table_size()
+    fn num_bits_breakdown(&self) -> NumBitsBreakdown {
+        NumBitsBreakdown::from_ffi(&self.inner.num_bits_breakdown())
     }
 
     fn save(&mut self, path: impl AsRef<Path>) -> Result<usize, Exception> {
@@ -141,9 +446,16 @@ impl<M: Minimality, H: Hasher, E: Encoder> Phf for SinglePhf<M, H, E> {
         path.push(0); // null terminator
         let path = path.as_ptr() as *const i8;
 
-        unsafe { self.inner.pin_mut().save(path) }
+        let num_bytes = unsafe { self.inner.pin_mut().save(path) }?;
+        let footer = Self::metadata().to_footer();
+        Ok(num_bytes + unsafe { crate::utils::append_bytes(path as *const c_char, &footer) }?)
     }
-    fn load(path: impl AsRef<Path>) -> Result<Self, Exception> {
+    fn load(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let path = path.as_ref();
+        PhfMetadata::read(path)?.check::<Self>(&Self::metadata())?;
+        Ok(Self::load_raw(path)?)
+    }
+    fn load_raw(path: impl AsRef<Path>) -> Result<Self, Exception> {
         let mut f = Self::new();
 
         let mut path = path.as_ref().as_os_str().to_owned().into_encoded_bytes();
@@ -156,4 +468,80 @@ impl<M: Minimality, H: Hasher, E: Encoder> Phf for SinglePhf<M, H, E> {
 
         Ok(f)
     }
+    fn save_to(&mut self, mut writer: impl Write) -> std::io::Result<usize> {
+        let bytes = self.to_bytes().map_err(std::io::Error::other)?;
+        let footer = Self::metadata().to_footer();
+        writer.write_all(&bytes)?;
+        writer.write_all(&footer)?;
+        Ok(bytes.len() + footer.len())
+    }
+    fn load_from(mut reader: impl Read) -> std::io::Result<Self> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        let load = || -> Result<Self, LoadError> {
+            PhfMetadata::from_suffix(&bytes)?.check::<Self>(&Self::metadata())?;
+            Ok(Self::from_bytes(&bytes)?)
+        };
+        load().map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(feature = "minimal")]
+impl<H: Hasher, E: Encoder> crate::MinimalPhf for SinglePhf<crate::Minimal, H, E> {}
+
+impl<M: Minimality, H: Hasher, E: Encoder> PhfQuery for SinglePhf<M, H, E> {
+    const MINIMAL: bool = M::AS_BOOL;
+
+    fn hash(&self, key: impl Hashable) -> u64 {
+        let position = self.inner.position(H::hash(key, self.seed));
+        #[cfg(feature = "debug-checks")]
+        crate::utils::check_position(self, position);
+        position
+    }
+
+    fn hash_many<K: Hashable>(&self, keys: impl IntoIterator<Item = K>) -> Vec<u64> {
+        let positions = crate::utils::hash_many::<_, H, _>(&*self.inner, self.seed, keys);
+        #[cfg(feature = "debug-checks")]
+        for &position in &positions {
+            crate::utils::check_position(self, position);
+        }
+        positions
+    }
+
+    fn hash_many_into<K: Hashable>(&self, keys: impl IntoIterator<Item = K>, out: &mut [u64]) {
+        crate::utils::hash_many_into::<_, H, _>(&*self.inner, self.seed, keys, out);
+        #[cfg(feature = "debug-checks")]
+        for &position in out.iter() {
+            crate::utils::check_position(self, position);
+        }
+    }
+
+    fn num_bits(&self) -> usize {
+        self.inner.num_bits()
+    }
+
+    fn num_keys(&self) -> u64 {
+        self.inner.num_keys()
+    }
+
+    fn table_size(&self) -> u64 {
+        self.inner.table_size()
+    }
+}
+
+/// Serializes the function as bytes returned by [`SinglePhf::to_bytes`]
+#[cfg(feature = "serde")]
+impl<M: Minimality, H: Hasher, E: Encoder> serde::Serialize for SinglePhf<M, H, E> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes = self.to_bytes().map_err(serde::ser::Error::custom)?;
+        serializer.serialize_bytes(&bytes)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, M: Minimality, H: Hasher, E: Encoder> serde::Deserialize<'de> for SinglePhf<M, H, E> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = crate::utils::deserialize_bytes(deserializer)?;
+        Self::from_bytes(&bytes).map_err(serde::de::Error::custom)
+    }
 }
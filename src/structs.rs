@@ -3,27 +3,80 @@
 // License: GNU General Public License version 3, or any later version
 // See top-level LICENSE file for more information
 
-#![allow(clippy::missing_safety_doc)]
+//! Rust definitions of PTHash's plain structures, which are passed by value across
+//! the FFI
+//!
+//! They are written by hand, instead of being generated from PTHash's headers, so
+//! building this crate does not need libclang. Their sizes are checked against the
+//! C++ definitions by `cpp-utils.hpp`.
 
-use autocxx::prelude::*;
+#![allow(non_camel_case_types)]
 
-include_cpp! {
-    #include "pthash.hpp"
+use cxx::{type_id, ExternType};
 
-    generate_pod!("pthash::build_timings")
-    generate_pod!("pthash::hash64")
-    generate_pod!("pthash::hash128")
+/// Binding for `pthash::build_timings`
+#[cfg(feature = "build")]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct build_timings {
+    pub partitioning_seconds: f64,
+    pub mapping_ordering_seconds: f64,
+    pub searching_seconds: f64,
+    pub encoding_seconds: f64,
 }
 
-pub(crate) use ffi::pthash::build_timings;
-pub use ffi::pthash::{hash128, hash64};
+#[cfg(feature = "build")]
+unsafe impl ExternType for build_timings {
+    type Id = type_id!("pthash::build_timings");
+    type Kind = cxx::kind::Trivial;
+}
+
+/// Binding for `pthash::hash64`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct hash64 {
+    hash: u64,
+}
+
+impl hash64 {
+    pub fn first(&self) -> u64 {
+        self.hash
+    }
+    pub fn second(&self) -> u64 {
+        self.hash
+    }
+}
+
+unsafe impl ExternType for hash64 {
+    type Id = type_id!("pthash::hash64");
+    type Kind = cxx::kind::Trivial;
+}
+
+/// Binding for `pthash::hash128`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct hash128 {
+    first: u64,
+    second: u64,
+}
+
+impl hash128 {
+    pub fn first(&self) -> u64 {
+        self.first
+    }
+    pub fn second(&self) -> u64 {
+        self.second
+    }
+}
+
+unsafe impl ExternType for hash128 {
+    type Id = type_id!("pthash::hash128");
+    type Kind = cxx::kind::Trivial;
+}
 
 impl From<u64> for hash64 {
     fn from(value: u64) -> Self {
-        moveit! {
-            let h = unsafe { hash64::new1(value) };
-        };
-        autocxx::moveit::MoveRef::into_inner(std::pin::Pin::into_inner(h))
+        hash64 { hash: value }
     }
 }
 
@@ -39,9 +92,9 @@ impl From<u128> for hash128 {
 impl From<(u64, u64)> for hash128 {
     fn from(value: (u64, u64)) -> Self {
         let (high, low) = value;
-        moveit! {
-            let h = unsafe { hash128::new1(high, low) };
-        };
-        autocxx::moveit::MoveRef::into_inner(std::pin::Pin::into_inner(h))
+        hash128 {
+            first: high,
+            second: low,
+        }
     }
 }
@@ -0,0 +1,166 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! [SWHID](https://docs.softwareheritage.org/devel/swh-model/persistent-identifiers.html)
+//! keys, hashed the same way as in swh-graph
+//!
+//! swh-graph builds its node MPHF over the textual representation of SWHIDs
+//! (eg. `swh:1:cnt:94a9ed024d3859793618152ea559a168bbcbb5e2`), so [`Swhid`] hashes
+//! to the same value as that string, without allocating it.
+
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::Hashable;
+
+/// Length of the textual representation of a SWHID, eg.
+/// `swh:1:cnt:94a9ed024d3859793618152ea559a168bbcbb5e2`
+pub const SWHID_TXT_SIZE: usize = 50;
+
+/// Type of object identified by a [`Swhid`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum NodeType {
+    Content,
+    Directory,
+    Revision,
+    Release,
+    Snapshot,
+    Origin,
+}
+
+impl NodeType {
+    /// Returns the three-letter code used in SWHIDs
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NodeType::Content => "cnt",
+            NodeType::Directory => "dir",
+            NodeType::Revision => "rev",
+            NodeType::Release => "rel",
+            NodeType::Snapshot => "snp",
+            NodeType::Origin => "ori",
+        }
+    }
+}
+
+impl FromStr for NodeType {
+    type Err = SwhidParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cnt" => Ok(NodeType::Content),
+            "dir" => Ok(NodeType::Directory),
+            "rev" => Ok(NodeType::Revision),
+            "rel" => Ok(NodeType::Release),
+            "snp" => Ok(NodeType::Snapshot),
+            "ori" => Ok(NodeType::Origin),
+            _ => Err(SwhidParseError::NodeType(s.to_owned())),
+        }
+    }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SwhidParseError {
+    #[error("SWHID should be {SWHID_TXT_SIZE} bytes long, got {0}")]
+    Length(usize),
+    #[error("SWHID should start with 'swh:', got {0:?}")]
+    Namespace(String),
+    #[error("Unsupported SWHID version {0:?}")]
+    Version(String),
+    #[error("Invalid SWHID node type {0:?}")]
+    NodeType(String),
+    #[error("Invalid SWHID hash {0:?}")]
+    Hash(String),
+}
+
+/// A core SWHID (without qualifiers)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Swhid {
+    pub namespace_version: u8,
+    pub node_type: NodeType,
+    pub hash: [u8; 20],
+}
+
+impl Swhid {
+    /// Returns the textual representation of this SWHID, as bytes
+    pub fn to_bytes(&self) -> [u8; SWHID_TXT_SIZE] {
+        const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+        let mut bytes = [0u8; SWHID_TXT_SIZE];
+        bytes[0..4].copy_from_slice(b"swh:");
+        bytes[4] = b'0' + self.namespace_version;
+        bytes[5] = b':';
+        bytes[6..9].copy_from_slice(self.node_type.as_str().as_bytes());
+        bytes[9] = b':';
+        for (i, byte) in self.hash.iter().enumerate() {
+            bytes[10 + 2 * i] = HEX_DIGITS[usize::from(byte >> 4)];
+            bytes[10 + 2 * i + 1] = HEX_DIGITS[usize::from(byte & 0xf)];
+        }
+        bytes
+    }
+}
+
+impl Display for Swhid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let bytes = self.to_bytes();
+        f.write_str(std::str::from_utf8(&bytes).expect("SWHID is not ASCII"))
+    }
+}
+
+impl FromStr for Swhid {
+    type Err = SwhidParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != SWHID_TXT_SIZE {
+            return Err(SwhidParseError::Length(s.len()));
+        }
+        if !s.starts_with("swh:") {
+            return Err(SwhidParseError::Namespace(s.to_owned()));
+        }
+        let namespace_version = match s.get(4..6) {
+            Some("1:") => 1,
+            version => {
+                return Err(SwhidParseError::Version(
+                    version.unwrap_or_default().to_owned(),
+                ))
+            }
+        };
+        let node_type = s
+            .get(6..9)
+            .ok_or_else(|| SwhidParseError::NodeType(s.to_owned()))?
+            .parse()?;
+        if s.as_bytes()[9] != b':' {
+            return Err(SwhidParseError::NodeType(s.to_owned()));
+        }
+        let hex = s
+            .get(10..)
+            .ok_or_else(|| SwhidParseError::Hash(s.to_owned()))?;
+        let mut hash = [0u8; 20];
+        for (i, byte) in hash.iter_mut().enumerate() {
+            *byte = hex
+                .get(2 * i..2 * i + 2)
+                .filter(|digits| digits.bytes().all(|digit| digit.is_ascii_hexdigit()))
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or_else(|| SwhidParseError::Hash(hex.to_owned()))?;
+        }
+        Ok(Swhid {
+            namespace_version,
+            node_type,
+            hash,
+        })
+    }
+}
+
+/// Hashes the textual representation, like swh-graph does
+impl Hashable for Swhid {
+    type Bytes<'a>
+        = [u8; SWHID_TXT_SIZE]
+    where
+        Self: 'a;
+
+    fn as_bytes(&self) -> Self::Bytes<'_> {
+        self.to_bytes()
+    }
+}
@@ -0,0 +1,157 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Helpers for tests of crates using functions: random sets of keys, checking
+//! functions are perfect, and saving and loading them
+//!
+//! Only available with the `testing` feature. Assertions panic with a message
+//! describing the failure, so they can be called directly from tests:
+//!
+//! ```no_run
+//! # use pthash::*;
+//! # use pthash::testing::*;
+//! let keys = random_byte_keys(1000, 32, 42);
+//! let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+//! f.build_in_internal_memory_from_bytes(|| &keys, &BuildConfiguration::default())
+//!     .expect("Failed to build");
+//! assert_perfect(&keys, &f);
+//! let f = assert_roundtrip(&keys, &mut f);
+//! ```
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::{check, keys_with_hash, Hashable, Phf, PhfQuery, ViolatedInvariant};
+
+/// Returns `num_keys` distinct random integers
+///
+/// For a given version of this crate, the same `seed` always returns the same keys.
+pub fn random_u64_keys(num_keys: usize, seed: u64) -> Vec<u64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut seen = HashSet::with_capacity(num_keys);
+    let mut keys = Vec::with_capacity(num_keys);
+    while keys.len() < num_keys {
+        let key = rng.random();
+        if seen.insert(key) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Returns `num_keys` distinct random byte strings, whose lengths are in `[0; max_len]`
+///
+/// For a given version of this crate, the same `seed` always returns the same keys.
+///
+/// # Panics
+///
+/// If there are fewer than `num_keys` byte strings of at most `max_len` bytes
+pub fn random_byte_keys(num_keys: usize, max_len: usize, seed: u64) -> Vec<Vec<u8>> {
+    let num_strings = (0..=max_len).fold(0u64, |num_strings, len| {
+        num_strings.saturating_add(256u64.saturating_pow(len.try_into().unwrap_or(u32::MAX)))
+    });
+    assert!(
+        num_keys as u64 <= num_strings,
+        "There are only {num_strings} byte strings of at most {max_len} bytes"
+    );
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut seen = HashSet::with_capacity(num_keys);
+    let mut keys = Vec::with_capacity(num_keys);
+    while keys.len() < num_keys {
+        let mut key = vec![0; rng.random_range(0..=max_len)];
+        rng.fill(&mut key[..]);
+        if seen.insert(key.clone()) {
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Panics if `f` is not perfect on `keys` (or not minimal, if [`PhfQuery::MINIMAL`])
+///
+/// Unlike [`check`], the message gives both keys when two of them have the same hash.
+#[track_caller]
+pub fn assert_perfect<Keys: IntoIterator + Clone, F: PhfQuery>(keys: Keys, f: &F)
+where
+    <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
+{
+    match check(keys.clone(), f) {
+        Ok(()) => {}
+        Err(ViolatedInvariant::Duplicates { duplicate_hash, .. }) => {
+            let colliding_keys: Vec<String> = keys_with_hash(keys, f, duplicate_hash)
+                .iter()
+                .map(|key| key.escape_ascii().to_string())
+                .collect();
+            panic!(
+                "Keys {} have the same hash ({duplicate_hash})",
+                colliding_keys.join(", ")
+            )
+        }
+        Err(e) => panic!("{e}"),
+    }
+}
+
+/// Panics if `f` and `g` differ in size, or hash any of `keys` differently
+#[track_caller]
+pub fn assert_same_hashes<Keys: IntoIterator, F: PhfQuery, G: PhfQuery>(keys: Keys, f: &F, g: &G)
+where
+    <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
+{
+    assert_eq!(f.num_keys(), g.num_keys(), "Different numbers of keys");
+    assert_eq!(f.table_size(), g.table_size(), "Different table sizes");
+    assert_eq!(f.num_bits(), g.num_bits(), "Different sizes");
+    for key in keys {
+        let (f_hash, g_hash) = (f.hash(&key), g.hash(&key));
+        assert_eq!(
+            f_hash,
+            g_hash,
+            "Different hashes of key {}",
+            key.as_bytes().as_ref().escape_ascii()
+        );
+    }
+}
+
+/// Saves `f` with [`Phf::save_to`] and loads it back with [`Phf::load_from`], then
+/// panics if the loaded function hashes `keys` differently
+///
+/// Returns the loaded function.
+#[track_caller]
+pub fn assert_roundtrip<Keys: IntoIterator, F: Phf>(keys: Keys, f: &mut F) -> F
+where
+    <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
+{
+    let mut buf = Vec::new();
+    let num_bytes = f.save_to(&mut buf).expect("Could not save function");
+    assert_eq!(num_bytes, buf.len(), "save_to() returned a wrong length");
+    let loaded = F::load_from(&buf[..]).expect("Could not load function");
+    assert_same_hashes(keys, f, &loaded);
+    loaded
+}
+
+/// Same as [`assert_roundtrip`], but saves `f` to the file at `path` with
+/// [`Phf::save`], and loads it with [`Phf::load`]
+#[track_caller]
+pub fn assert_file_roundtrip<Keys: IntoIterator, F: Phf>(
+    keys: Keys,
+    f: &mut F,
+    path: impl AsRef<Path>,
+) -> F
+where
+    <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
+{
+    let path = path.as_ref();
+    let num_bytes = f.save(path).expect("Could not save function");
+    let file_len = std::fs::metadata(path)
+        .expect("Could not read size of saved function")
+        .len();
+    assert_eq!(num_bytes as u64, file_len, "save() returned a wrong length");
+    let loaded = F::load(path).expect("Could not load function");
+    assert_same_hashes(keys, f, &loaded);
+    loaded
+}
@@ -0,0 +1,318 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Helpers to choose [`BuildConfiguration`] parameters ([`KeySample`], [`predict`],
+//! [`estimate_bits_per_key`], [`BuildConfiguration::tuned_for`])
+
+#[cfg(feature = "rand")]
+use rand::Rng;
+
+use crate::build::BuildConfiguration;
+use crate::encoders::EncoderKind;
+#[cfg(feature = "rand")]
+use crate::hashing::Hashable;
+use crate::hashing::Hasher;
+use crate::minimality::Minimality;
+
+/// Number of keys per partition recommended for partitioned functions
+pub const KEYS_PER_PARTITION: u64 = 2_500_000;
+
+/// Number of keys under which builds are adjusted to be more robust
+///
+/// For such small sets, builds use a single partition and an explicit number of
+/// buckets (avoiding degenerate values for PTHash's formula), and non-partitioned
+/// functions try up to [`SMALL_SET_NUM_SEEDS`] seeds instead of 10.
+///
+/// This is not a fallback to another structure: builds on small sets do not switch
+/// to a trivial table found by exhaustive search, and this crate does not provide
+/// one. Saved functions must remain PTHash functions, which the C++ library and
+/// [`Phf::load_raw`](crate::Phf::load_raw) can read, so small sets go through
+/// PTHash like any other, and builds failing with every seed return
+/// [`BuildError::SeedsExhausted`](crate::BuildError::SeedsExhausted).
+pub const SMALL_SET_NUM_KEYS: u64 = 256;
+
+/// Number of seeds tried when building a non-partitioned function on fewer than
+/// [`SMALL_SET_NUM_KEYS`] keys without a configured seed
+pub const SMALL_SET_NUM_SEEDS: usize = 100;
+
+/// Returns a rough upper bound of the memory needed to build a function on
+/// `num_keys` keys with the given hasher
+///
+/// This accounts for the hashes computed on the Rust side, and for the pairs of
+/// bucket ids and payloads allocated by the C++ builders.
+pub fn estimate_build_ram<H: Hasher>(num_keys: u64) -> u64 {
+    num_keys * (std::mem::size_of::<H::Hash>() as u64 + 24)
+}
+
+/// Number of chunks per thread targeted by [`hashing_max_len`]
+pub const HASHING_CHUNKS_PER_THREAD: usize = 64;
+
+/// Number of keys under which [`hashing_max_len`] does not split the hashing pass
+/// further, as the overhead of scheduling chunks would dominate
+pub const HASHING_MIN_CHUNK_LEN: usize = 1024;
+
+/// Returns the maximum number of keys a thread should hash at once when hashing
+/// `num_keys` keys in parallel with `num_threads` threads
+///
+/// [`par_build_in_internal_memory_from_bytes`](crate::Phf::par_build_in_internal_memory_from_bytes)
+/// hashes keys as split by their parallel iterator, which for indexed iterators
+/// (slices, vectors, ranges) is only as many chunks as threads until one runs out
+/// of work. When keys' lengths are skewed (eg. a few very long keys clustered
+/// together), threads which got the short keys then wait; capping chunks to this
+/// value with rayon's `IndexedParallelIterator::with_max_len` gives each thread
+/// about [`HASHING_CHUNKS_PER_THREAD`] chunks to balance the load with.
+pub fn hashing_max_len(num_keys: usize, num_threads: usize) -> usize {
+    (num_keys / (num_threads.max(1) * HASHING_CHUNKS_PER_THREAD)).max(HASHING_MIN_CHUNK_LEN)
+}
+
+/// Uniform random sample of a stream of keys, for when the whole set of keys is too
+/// large to be inspected
+#[cfg(feature = "rand")]
+#[derive(Clone, Debug)]
+pub struct KeySample<K> {
+    keys: Vec<K>,
+    num_keys: u64,
+}
+
+#[cfg(feature = "rand")]
+impl<K> KeySample<K> {
+    /// Draws up to `sample_size` keys uniformly from `keys`, in a single pass and
+    /// with `O(sample_size)` memory (reservoir sampling)
+    pub fn new<R: Rng>(keys: impl IntoIterator<Item = K>, sample_size: usize, rng: &mut R) -> Self {
+        let mut sample = Vec::with_capacity(sample_size);
+        let mut num_keys = 0u64;
+        for key in keys {
+            if sample.len() < sample_size {
+                sample.push(key);
+            } else {
+                let i = rng.random_range(0..=num_keys);
+                if let Ok(i) = usize::try_from(i) {
+                    if i < sample_size {
+                        sample[i] = key;
+                    }
+                }
+            }
+            num_keys += 1;
+        }
+        KeySample {
+            keys: sample,
+            num_keys,
+        }
+    }
+
+    /// Returns the keys in the sample
+    pub fn keys(&self) -> &[K] {
+        &self.keys
+    }
+
+    /// Returns the total number of keys the sample was drawn from
+    pub fn num_keys(&self) -> u64 {
+        self.num_keys
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<K: Hashable> KeySample<K> {
+    /// Returns the average length (in bytes) of keys in the sample, or `None` if it
+    /// is empty
+    pub fn average_key_len(&self) -> Option<f64> {
+        if self.keys.is_empty() {
+            return None;
+        }
+        let total_len: usize = self
+            .keys
+            .iter()
+            .map(|key| key.as_bytes().as_ref().len())
+            .sum();
+        Some(total_len as f64 / self.keys.len() as f64)
+    }
+
+    /// Returns a copy of `config` with `c`, `alpha` and `num_partitions` set to
+    /// values suited to the number of keys the sample was drawn from
+    ///
+    /// These are heuristics: small sets favor fast and reliable builds
+    /// (`c = 7`, `alpha = 0.94`), larger sets use PTHash's defaults (`c = 4.5`,
+    /// `alpha = 0.98`), and partitioned functions should have about
+    /// [`KEYS_PER_PARTITION`] keys per partition.
+    pub fn recommended_configuration(&self, config: &BuildConfiguration) -> BuildConfiguration {
+        let mut config = config.clone();
+        if self.num_keys < 1_000_000 {
+            config.c = 7.0;
+            config.alpha = 0.94;
+        } else {
+            config.c = 4.5;
+            config.alpha = 0.98;
+        }
+        config.num_partitions = self.num_keys.div_ceil(KEYS_PER_PARTITION).max(1);
+        config
+    }
+}
+
+/// What [`BuildConfiguration::tuned_for`] optimizes for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SpaceOrSpeed {
+    /// Smaller functions, at the cost of slower builds (`c = 4.5`, `alpha = 0.99`)
+    Space,
+    /// Faster builds and lookups, at the cost of a few more bits per key (`c = 7`,
+    /// `alpha = 0.94`)
+    Speed,
+}
+
+impl BuildConfiguration {
+    /// Returns a configuration suited to building a function on `num_keys` keys,
+    /// with temporary files in [`std::env::temp_dir`]
+    ///
+    /// This follows PTHash's recommendations: `c` and `alpha` are picked according
+    /// to `target`, partitioned functions get about [`KEYS_PER_PARTITION`] keys per
+    /// partition, and builds use as many threads as partitions (up to the available
+    /// parallelism). Sets smaller than a partition use a single thread, which is
+    /// faster than synchronizing threads. `num_buckets` is left to PTHash, which
+    /// derives it from `c`.
+    ///
+    /// `num_partitions` is ignored by non-partitioned functions.
+    pub fn tuned_for(num_keys: u64, target: SpaceOrSpeed) -> BuildConfiguration {
+        let mut config = BuildConfiguration::default();
+        (config.c, config.alpha) = match target {
+            SpaceOrSpeed::Space => (4.5, 0.99),
+            SpaceOrSpeed::Speed => (7.0, 0.94),
+        };
+        config.num_partitions = num_keys.div_ceil(KEYS_PER_PARTITION).max(1);
+        config.num_threads = if num_keys < KEYS_PER_PARTITION {
+            1
+        } else {
+            let available = std::thread::available_parallelism().map_or(1, |n| n.get());
+            (available as u64).min(config.num_partitions)
+        };
+        config
+    }
+}
+
+/// Returns the table size PTHash picks for a single (non-partitioned) function on
+/// `num_keys` keys
+pub fn table_size(num_keys: u64, alpha: f64) -> u64 {
+    let table_size = (num_keys as f64 / alpha) as u64;
+    // Like PTHash, avoid powers of two which are bad for the modulo
+    if table_size & table_size.wrapping_sub(1) == 0 {
+        table_size + 1
+    } else {
+        table_size
+    }
+}
+
+/// Returns the number of buckets PTHash picks for a single (non-partitioned)
+/// function on `num_keys` keys, when [`BuildConfiguration::num_buckets`] is not set
+pub fn num_buckets(num_keys: u64, c: f64) -> u64 {
+    ((c * num_keys as f64) / (num_keys as f64).log2()).ceil() as u64
+}
+
+/// Returns the expected number of pilot values tried while searching pilots for
+/// `num_keys` keys in a table of size `table_size`
+///
+/// This assumes every bucket contains a single key; as buckets are actually larger
+/// (especially the first ones, which are searched while the table is empty), it is
+/// a lower bound of the actual effort, but it shows how fast it grows as `alpha`
+/// gets close to 1.
+pub fn expected_search_trials(num_keys: u64, table_size: u64) -> f64 {
+    let n = num_keys as f64;
+    let m = table_size as f64;
+    if num_keys == 0 {
+        0.
+    } else if table_size > num_keys {
+        // sum of m / (m - i) for i in [0; n)
+        m * (m / (m - n)).ln()
+    } else {
+        // harmonic number H_m
+        const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+        m * (m.ln() + EULER_GAMMA)
+    }
+}
+
+/// Properties of a function, predicted before building it by [`predict`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Prediction {
+    /// Largest value returned by [`PhfQuery::hash`](crate::PhfQuery::hash) plus 1, for
+    /// non-minimal functions
+    pub table_size: u64,
+    /// Number of buckets, summed over all partitions
+    pub num_buckets: u64,
+    /// See [`expected_search_trials`]
+    pub expected_search_trials: f64,
+}
+
+/// Predicts the table size, number of buckets and search effort of a function
+/// built on `num_keys` keys with the given parameters, using the same formulas as
+/// PTHash
+///
+/// If `num_partitions` is greater than 1, this assumes keys are evenly distributed
+/// across partitions.
+pub fn predict(num_keys: u64, c: f64, alpha: f64, num_partitions: u64) -> Prediction {
+    let num_partitions = num_partitions.max(1);
+    let mut prediction = Prediction {
+        table_size: 0,
+        num_buckets: 0,
+        expected_search_trials: 0.,
+    };
+    for partition in 0..num_partitions {
+        let partition_size =
+            num_keys / num_partitions + u64::from(partition < num_keys % num_partitions);
+        let partition_table_size = table_size(partition_size, alpha);
+        prediction.table_size += partition_table_size;
+        prediction.num_buckets += num_buckets(partition_size, c);
+        prediction.expected_search_trials +=
+            expected_search_trials(partition_size, partition_table_size);
+    }
+    prediction
+}
+
+/// Approximate number of bits used by each encoder to store a pilot, derived from
+/// the bits per key reported by the PTHash papers with `c` in `[5; 7]` and `alpha`
+/// close to 1
+///
+/// `tests/tuning.rs` checks the resulting estimates against functions it builds.
+fn bits_per_pilot(encoder: EncoderKind) -> f64 {
+    match encoder {
+        #[cfg(feature = "dictionary_dictionary")]
+        EncoderKind::DictionaryDictionary => 14.0,
+        #[cfg(feature = "partitioned_compact")]
+        EncoderKind::PartitionedCompact => 15.0,
+        #[cfg(feature = "elias_fano")]
+        EncoderKind::EliasFano => 9.8,
+        #[cfg(feature = "compact")]
+        EncoderKind::Compact => 20.0,
+        #[cfg(feature = "dictionary")]
+        EncoderKind::Dictionary => 15.5,
+    }
+}
+
+/// Size of the header of each partition (offset, seed, sizes, bucketer)
+const BITS_PER_PARTITION: f64 = 512.;
+
+/// Estimates the number of bits per key of a function built on `num_keys` keys with
+/// the given configuration and encoder, before building it
+///
+/// This uses a model of the pilots' sizes fitted on the PTHash papers' experiments,
+/// plus the size of the Elias-Fano encoding of free slots for minimal functions, so
+/// it is only an approximation; it does not account for
+/// [`BuildConfiguration::num_buckets`] being set.
+pub fn estimate_bits_per_key<M: Minimality>(
+    num_keys: u64,
+    config: &BuildConfiguration,
+    encoder: EncoderKind,
+) -> f64 {
+    if num_keys == 0 {
+        return 0.;
+    }
+    let prediction = predict(num_keys, config.c, config.alpha, config.num_partitions);
+    let n = num_keys as f64;
+
+    let mut bits = prediction.num_buckets as f64 * bits_per_pilot(encoder);
+    if M::AS_BOOL && prediction.table_size > num_keys {
+        let num_free_slots = (prediction.table_size - num_keys) as f64;
+        bits += num_free_slots * (2. + (n / num_free_slots).log2().ceil().max(0.));
+    }
+    bits += config.num_partitions.max(1) as f64 * BITS_PER_PARTITION;
+
+    bits / n
+}
@@ -6,9 +6,14 @@
 #[cfg(feature = "check")]
 use thiserror::Error;
 
+use crate::backends::BackendPhf;
+#[cfg(feature = "build")]
+use crate::build::BuildError;
+use crate::hashing::{Hashable, Hasher};
 #[cfg(feature = "check")]
-use crate::{Hashable, Phf};
+use crate::PhfQuery;
 
+#[cfg_attr(not(feature = "build"), allow(dead_code))]
 #[cxx::bridge]
 mod ffi {
     #[namespace = "pthash_rs::utils"]
@@ -16,34 +21,228 @@ mod ffi {
         include!("cpp-utils.hpp");
 
         fn valid_seed(seed: u64) -> bool;
+        fn valid_num_buckets(num_buckets: u64) -> bool;
+
+        unsafe fn append_bytes(filename: *const c_char, data: &[u8]) -> Result<usize>;
+    }
+}
+
+#[cfg(feature = "build")]
+pub(crate) use ffi::valid_num_buckets;
+pub(crate) use ffi::{append_bytes, valid_seed};
+
+/// Deterministically derives per-function seeds (eg. one per shard) from a single
+/// master seed, so a family of functions can be rebuilt from one recorded value
+///
+/// Seeds are derived with [SplitMix64](https://prng.di.unimi.it/splitmix64.c), and
+/// are always valid [`BuildConfiguration::seed`](crate::BuildConfiguration::seed) values.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SeedFamily {
+    master_seed: u64,
+}
+
+impl SeedFamily {
+    pub fn new(master_seed: u64) -> Self {
+        SeedFamily { master_seed }
+    }
+
+    pub fn master_seed(&self) -> u64 {
+        self.master_seed
+    }
+
+    /// Returns the seed of the `index`-th function of the family
+    pub fn seed(&self, index: u64) -> u64 {
+        let mut state = self
+            .master_seed
+            .wrapping_add(index.wrapping_mul(0x9e3779b97f4a7c15));
+        loop {
+            state = state.wrapping_add(0x9e3779b97f4a7c15);
+            let mut z = state;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+            z ^= z >> 31;
+            if valid_seed(z) {
+                return z;
+            }
+        }
+    }
+
+    /// Returns the seeds of the first functions of the family, in order
+    pub fn seeds(&self) -> impl Iterator<Item = u64> + '_ {
+        (0..).map(|index| self.seed(index))
+    }
+}
+
+/// Deserializes a byte array, from either bytes or a sequence of integers depending
+/// on how the format represents byte arrays
+#[cfg(feature = "serde")]
+pub(crate) fn deserialize_bytes<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<u8>, D::Error> {
+    struct BytesVisitor;
+
+    impl<'de> serde::de::Visitor<'de> for BytesVisitor {
+        type Value = Vec<u8>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a serialized PTHash function")
+        }
+
+        fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+            Ok(v.to_vec())
+        }
+
+        fn visit_byte_buf<E: serde::de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+            Ok(v)
+        }
+
+        fn visit_seq<A: serde::de::SeqAccess<'de>>(
+            self,
+            mut seq: A,
+        ) -> Result<Self::Value, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            Ok(bytes)
+        }
+    }
+
+    deserializer.deserialize_bytes(BytesVisitor)
+}
+
+/// Number of keys hashed before each call to [`BackendPhf::positions`]
+const HASH_BATCH_SIZE: usize = 1024;
+
+/// Implementation of [`PhfQuery::hash_many`](crate::PhfQuery::hash_many) for
+/// functions backed by `backend`
+pub(crate) fn hash_many<B: BackendPhf, H: Hasher<Hash = B::Hash>, K: Hashable>(
+    backend: &B,
+    seed: u64,
+    keys: impl IntoIterator<Item = K>,
+) -> Vec<u64> {
+    let mut keys = keys.into_iter();
+    let mut positions = Vec::with_capacity(keys.size_hint().0);
+    let mut hashes = Vec::with_capacity(HASH_BATCH_SIZE);
+    loop {
+        hashes.clear();
+        H::hash_batch(keys.by_ref().take(HASH_BATCH_SIZE), seed, &mut hashes);
+        if hashes.is_empty() {
+            return positions;
+        }
+        let start = positions.len();
+        positions.resize(start + hashes.len(), 0);
+        backend
+            .positions(&hashes, &mut positions[start..])
+            .expect("Could not compute positions");
+    }
+}
+
+/// Implementation of [`PhfQuery::hash_many_into`](crate::PhfQuery::hash_many_into)
+/// for functions backed by `backend`
+pub(crate) fn hash_many_into<B: BackendPhf, H: Hasher<Hash = B::Hash>, K: Hashable>(
+    backend: &B,
+    seed: u64,
+    keys: impl IntoIterator<Item = K>,
+    out: &mut [u64],
+) {
+    let mut keys = keys.into_iter();
+    let mut hashes = Vec::with_capacity(HASH_BATCH_SIZE.min(out.len()));
+    for positions in out.chunks_mut(HASH_BATCH_SIZE) {
+        hashes.clear();
+        H::hash_batch(keys.by_ref().take(positions.len()), seed, &mut hashes);
+        assert_eq!(hashes.len(), positions.len(), "Fewer keys than positions");
+        backend
+            .positions(&hashes, positions)
+            .expect("Could not compute positions");
+    }
+    assert!(keys.next().is_none(), "More keys than positions");
+}
+
+/// Returns the average number of bits per key of a function, or 0 if it has no keys
+pub(crate) fn bits_per_key<F: PhfQuery>(f: &F) -> f64 {
+    if f.num_keys() == 0 {
+        0.
+    } else {
+        f.num_bits() as f64 / f.num_keys() as f64
+    }
+}
+
+/// Panics if `position` can't be a value returned by `f`
+#[cfg(feature = "debug-checks")]
+#[inline(always)]
+pub(crate) fn check_position<F: crate::PhfQuery>(f: &F, position: u64) {
+    assert!(
+        position < f.table_size(),
+        "Hash is {position} but it should be lower than the table size ({})",
+        f.table_size()
+    );
+    if F::MINIMAL {
+        assert!(
+            position < f.num_keys(),
+            "Hash is {position} but it should be lower than the number of keys ({}), as the function is minimal",
+            f.num_keys()
+        );
     }
 }
 
-pub(crate) use ffi::valid_seed;
+/// Returns `n` random seeds
+#[cfg(all(feature = "build", feature = "rand"))]
+pub(crate) fn random_seeds(n: usize) -> Result<Vec<u64>, BuildError> {
+    use rand::Rng;
+
+    let mut rng = rand::rng();
+    Ok((0..n).map(|_| rng.random()).collect())
+}
 
+/// Fails, as seeds can't be picked at random without the `rand` feature
+#[cfg(all(feature = "build", not(feature = "rand")))]
+pub(crate) fn random_seeds(_n: usize) -> Result<Vec<u64>, BuildError> {
+    Err(BuildError::MissingSeed)
+}
+
+/// Error returned by [`check`]
+///
+/// `key` is the [bytes](Hashable::as_bytes) of the key whose hash violates the
+/// invariant.
 #[cfg(feature = "check")]
 #[derive(Error, Debug)]
 pub enum ViolatedInvariant {
-    #[error("Hash is {position} but it should be lower than {table_size}")]
-    PositionOutOfRange { position: u64, table_size: u64 },
+    #[error(
+        "Hash of key {} is {position} but it should be lower than {table_size}",
+        .key.escape_ascii()
+    )]
+    PositionOutOfRange {
+        key: Vec<u8>,
+        position: u64,
+        table_size: u64,
+    },
 
-    #[error("Hash is {position} but the function has only {num_keys} and should be minimal")]
+    #[error(
+        "Hash of key {} is {position} but the function has only {num_keys} and should be minimal",
+        .key.escape_ascii()
+    )]
     NotMinimal {
+        key: Vec<u8>,
         position: u64,
         table_size: u64,
         num_keys: u64,
     },
 
-    #[error("Two keys have the same hash ({duplicate_hash})")]
-    Duplicates { duplicate_hash: u64 },
+    /// `key` has the same hash as a key before it; [`keys_with_hash`] returns both
+    #[error(
+        "Key {} has the same hash ({duplicate_hash}) as a previous key",
+        .key.escape_ascii()
+    )]
+    Duplicates { key: Vec<u8>, duplicate_hash: u64 },
 
-    #[error("Table size ({table_size}) is lower thannumber of keys ({num_keys})")]
+    #[error("Table size ({table_size}) is lower than number of keys ({num_keys})")]
     MismatchedTableSize { table_size: u64, num_keys: u64 },
 }
 
 #[cfg(feature = "check")]
-/// Checks the function is injective (and bijective in `[0; num_keys)`, if [`Phf::MINIMAL`])
-pub fn check<Keys: IntoIterator, F: Phf>(keys: Keys, f: &F) -> Result<(), ViolatedInvariant>
+/// Checks the function is injective (and bijective in `[0; num_keys)`, if [`PhfQuery::MINIMAL`])
+pub fn check<Keys: IntoIterator, F: PhfQuery>(keys: Keys, f: &F) -> Result<(), ViolatedInvariant>
 where
     <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
 {
@@ -61,22 +260,26 @@ where
             .expect("function's table_size overflowed usize"),
     );
     for key in keys {
-        let position = f.hash(key);
+        let position = f.hash(&key);
+        let key_bytes = || key.as_bytes().as_ref().to_vec();
         let position_usize: usize =
             position
                 .try_into()
                 .map_err(|_| ViolatedInvariant::PositionOutOfRange {
+                    key: key_bytes(),
                     position: usize::MAX as u64,
                     table_size: f.table_size(),
                 })?;
         if position >= f.table_size() {
             return Err(ViolatedInvariant::PositionOutOfRange {
+                key: key_bytes(),
                 position,
                 table_size: f.table_size(),
             });
         }
         if F::MINIMAL && position >= f.num_keys() {
             return Err(ViolatedInvariant::NotMinimal {
+                key: key_bytes(),
                 position,
                 table_size: f.table_size(),
                 num_keys: f.num_keys(),
@@ -84,6 +287,7 @@ where
         }
         if present.get(position_usize) {
             return Err(ViolatedInvariant::Duplicates {
+                key: key_bytes(),
                 duplicate_hash: position,
             });
         }
@@ -92,3 +296,19 @@ where
 
     Ok(())
 }
+
+#[cfg(feature = "check")]
+/// Returns the [bytes](Hashable::as_bytes) of the keys whose hash by `f` is `hash`, in
+/// order
+///
+/// This finds both keys of a [`ViolatedInvariant::Duplicates`] returned by [`check`]
+/// on the same keys.
+pub fn keys_with_hash<Keys: IntoIterator, F: PhfQuery>(keys: Keys, f: &F, hash: u64) -> Vec<Vec<u8>>
+where
+    <<Keys as IntoIterator>::IntoIter as Iterator>::Item: Hashable,
+{
+    keys.into_iter()
+        .filter(|key| f.hash(key) == hash)
+        .map(|key| key.as_bytes().as_ref().to_vec())
+        .collect()
+}
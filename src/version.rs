@@ -0,0 +1,50 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::fmt;
+
+use crate::encoders::EncoderKind;
+
+/// What this crate was built from, as returned by [`version`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionInfo {
+    /// Version of this crate
+    pub crate_version: &'static str,
+    /// Commit of the vendored PTHash, or `unknown` if it was not built from a git
+    /// checkout
+    pub pthash_commit: &'static str,
+    /// Enabled Cargo features of this crate, as reported by Cargo to build scripts
+    /// (lowercase, with dashes replaced by underscores)
+    pub features: Vec<&'static str>,
+    /// Names of the enabled encoders (see [`Encoder::NAME`](crate::Encoder::NAME))
+    pub encoders: Vec<&'static str>,
+}
+
+impl fmt::Display for VersionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "pthash-rs {} (PTHash {}; features: {}; encoders: {})",
+            self.crate_version,
+            self.pthash_commit,
+            self.features.join(","),
+            self.encoders.join(",")
+        )
+    }
+}
+
+/// Returns the crate version, vendored PTHash commit, and enabled features and
+/// encoders, to identify what produced a function
+pub fn version() -> VersionInfo {
+    VersionInfo {
+        crate_version: env!("CARGO_PKG_VERSION"),
+        pthash_commit: env!("PTHASH_COMMIT"),
+        features: env!("PTHASH_RS_FEATURES")
+            .split(',')
+            .filter(|feature| !feature.is_empty())
+            .collect(),
+        encoders: EncoderKind::ALL.iter().map(|kind| kind.name()).collect(),
+    }
+}
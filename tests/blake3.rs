@@ -0,0 +1,49 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests building and calling functions with [`Blake3Hasher128`]
+
+#![cfg(all(
+    feature = "build",
+    feature = "blake3",
+    feature = "minimal",
+    feature = "nonminimal",
+    feature = "dictionary_dictionary"
+))]
+
+use anyhow::Result;
+
+use pthash::*;
+
+mod common;
+use common::*;
+
+#[test]
+fn test_blake3_minimal() -> Result<()> {
+    test_hasher::<Minimal, Blake3Hasher128, _>(&int_keys())
+}
+
+#[test]
+fn test_blake3_nonminimal() -> Result<()> {
+    test_hasher::<Nonminimal, Blake3Hasher128, _>(&int_keys())
+}
+
+#[test]
+fn test_blake3_load_as_murmurhash() -> Result<()> {
+    test_load_other_hasher::<Blake3Hasher128, MurmurHash2_128>()
+}
+
+#[test]
+fn test_blake3_hash() {
+    // BLAKE3 in keyed mode, truncated to 128 bits
+    let mut key = [0; 32];
+    key[..8].copy_from_slice(&42u64.to_le_bytes());
+    let expected = blake3::keyed_hash(&key, b"abc");
+    let hash = Blake3Hasher128::hash(b"abc".as_slice(), 42);
+    assert_eq!(hash.first().to_le_bytes(), expected.as_bytes()[0..8]);
+    assert_eq!(hash.second().to_le_bytes(), expected.as_bytes()[8..16]);
+
+    assert_ne!(hash, Blake3Hasher128::hash(b"abc".as_slice(), 43));
+}
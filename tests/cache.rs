@@ -0,0 +1,56 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(all(
+    feature = "build",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use pthash::cache::PhfCache;
+use pthash::*;
+
+type F = SinglePhf<Minimal, MurmurHash2_64, DictionaryDictionary>;
+
+#[test]
+fn test_cache() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let mut f = F::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    let path = temp_dir.path().join("phf.bin");
+    f.save(&path).context("Could not save function")?;
+
+    let cache = PhfCache::new(None);
+    let f1 = cache.get_or_load::<F>(&path)?;
+    let f2 = cache.get_or_load::<F>(&path)?;
+    assert!(Arc::ptr_eq(&f1, &f2));
+    assert_eq!(f1.hash(42u64), f.hash(42u64));
+    assert!(cache.num_bytes() > 0);
+
+    // Functions still in use are not evicted
+    cache.shrink_to(0);
+    assert!(Arc::ptr_eq(&f1, &cache.get_or_load::<F>(&path)?));
+
+    drop(f1);
+    drop(f2);
+    cache.shrink_to(0);
+    assert_eq!(cache.num_bytes(), 0);
+
+    let f3 = cache.get_or_load::<F>(&path)?;
+    cache.evict(&path);
+    assert!(!Arc::ptr_eq(&f3, &cache.get_or_load::<F>(&path)?));
+
+    Ok(())
+}
@@ -0,0 +1,92 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(all(
+    feature = "build",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+
+use anyhow::{Context, Result};
+
+use pthash::cached::*;
+use pthash::*;
+
+fn build(keys: &[u64]) -> Result<SinglePhf<Minimal, MurmurHash2_64, DictionaryDictionary>> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| keys, &config)
+        .context("Failed to build")?;
+    Ok(f)
+}
+
+#[test]
+fn test_cached_phf() -> Result<()> {
+    let keys: Vec<u64> = (0..1000).collect();
+    let f = build(&keys)?;
+
+    let cached = CachedPhf::new(&f, 64);
+    for _ in 0..3 {
+        for key in &keys[0..10] {
+            assert_eq!(cached.hash(key), f.hash(key));
+        }
+    }
+    assert_eq!(cached.misses(), 10);
+    assert_eq!(cached.hits(), 20);
+
+    // Evicted keys are still hashed correctly
+    for key in &keys {
+        assert_eq!(cached.hash(key), f.hash(key));
+        assert_eq!(cached.hash(&key.to_ne_bytes()[..]), f.hash(key));
+    }
+
+    cached.clear();
+    let misses = cached.misses();
+    assert_eq!(cached.hash(keys[0]), f.hash(keys[0]));
+    assert_eq!(cached.misses(), misses + 1);
+
+    Ok(())
+}
+
+#[test]
+fn test_cached_phf_no_capacity() -> Result<()> {
+    let keys: Vec<u64> = (0..1000).collect();
+    let f = build(&keys)?;
+
+    let cached = CachedPhf::new(&f, 0);
+    for key in &keys {
+        assert_eq!(cached.hash(key), f.hash(key));
+    }
+    assert_eq!(cached.hits(), 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_cached_phf_concurrent() -> Result<()> {
+    let keys: Vec<u64> = (0..1000).collect();
+    let f = build(&keys)?;
+
+    let cached = CachedPhf::new(&f, 16);
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                for _ in 0..100 {
+                    for key in &keys[0..4] {
+                        assert_eq!(cached.hash(key), f.hash(key));
+                    }
+                }
+            });
+        }
+    });
+    assert_eq!(cached.hits() + cached.misses(), 1600);
+    assert!(cached.hits() >= 1600 - 16);
+
+    Ok(())
+}
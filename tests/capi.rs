@@ -0,0 +1,139 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(feature = "capi")]
+
+use std::ffi::{CStr, CString};
+
+use anyhow::{Context, Result};
+
+use pthash::capi::*;
+use pthash::*;
+
+#[test]
+fn test_capi() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let path = CString::new(
+        temp_dir
+            .path()
+            .join("f.bin")
+            .into_os_string()
+            .into_encoded_bytes(),
+    )?;
+
+    let keys: Vec<Vec<u8>> = (0..1000u64)
+        .map(|i| format!("key{i}").into_bytes())
+        .collect();
+    let key_ptrs: Vec<*const u8> = keys.iter().map(|key| key.as_ptr()).collect();
+    let key_lens: Vec<usize> = keys.iter().map(|key| key.len()).collect();
+
+    unsafe {
+        let f = pthash_build(
+            key_ptrs.as_ptr(),
+            key_lens.as_ptr(),
+            keys.len(),
+            true,
+            std::ptr::null(),
+            1,
+        );
+        assert!(!f.is_null());
+        assert_eq!(pthash_num_keys(f), 1000);
+        let mut hashes: Vec<u64> = keys
+            .iter()
+            .map(|key| pthash_hash(f, key.as_ptr(), key.len()))
+            .collect();
+        hashes.sort();
+        assert_eq!(hashes, (0..1000).collect::<Vec<_>>());
+
+        assert_eq!(pthash_save(f, path.as_ptr()), 0);
+        let loaded = pthash_load(path.as_ptr());
+        assert!(!loaded.is_null());
+        for key in &keys {
+            assert_eq!(
+                pthash_hash(loaded, key.as_ptr(), key.len()),
+                pthash_hash(f, key.as_ptr(), key.len())
+            );
+        }
+        pthash_free(f);
+        pthash_free(loaded);
+
+        // Functions built through the C API can be loaded by Rust programs
+        let f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::load(
+            temp_dir.path().join("f.bin"),
+        )
+        .context("Could not load")?;
+        assert_eq!(f.num_keys(), 1000);
+
+        // Errors
+        let missing = CString::new("/nonexistent/f.bin")?;
+        assert!(pthash_load(missing.as_ptr()).is_null());
+        assert!(!pthash_last_error().is_null());
+        let encoder = CString::new("not_an_encoder")?;
+        assert!(pthash_build(
+            key_ptrs.as_ptr(),
+            key_lens.as_ptr(),
+            keys.len(),
+            true,
+            encoder.as_ptr(),
+            1
+        )
+        .is_null());
+        assert_eq!(
+            CStr::from_ptr(pthash_last_error()).to_str()?,
+            "unknown or disabled encoder: not_an_encoder"
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_capi_null_keys() -> Result<()> {
+    let keys: Vec<Vec<u8>> = (0..1000u64)
+        .map(|i| format!("key{i}").into_bytes())
+        .collect();
+    let mut key_ptrs: Vec<*const u8> = keys.iter().map(|key| key.as_ptr()).collect();
+    let mut key_lens: Vec<usize> = keys.iter().map(|key| key.len()).collect();
+
+    unsafe {
+        // NULL with a length of 0 is the empty key
+        key_ptrs.push(std::ptr::null());
+        key_lens.push(0);
+        let f = pthash_build(
+            key_ptrs.as_ptr(),
+            key_lens.as_ptr(),
+            key_ptrs.len(),
+            true,
+            std::ptr::null(),
+            1,
+        );
+        assert!(!f.is_null());
+        let mut hashes: Vec<u64> = keys
+            .iter()
+            .map(|key| pthash_hash(f, key.as_ptr(), key.len()))
+            .chain([pthash_hash(f, std::ptr::null(), 0)])
+            .collect();
+        hashes.sort();
+        assert_eq!(hashes, (0..1001).collect::<Vec<_>>());
+        pthash_free(f);
+
+        *key_lens.last_mut().unwrap() = 3;
+        assert!(pthash_build(
+            key_ptrs.as_ptr(),
+            key_lens.as_ptr(),
+            key_ptrs.len(),
+            true,
+            std::ptr::null(),
+            1
+        )
+        .is_null());
+        assert_eq!(
+            CStr::from_ptr(pthash_last_error()).to_str()?,
+            "keys[1000] is NULL but key_lens[1000] is 3"
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,76 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(all(
+    feature = "build",
+    feature = "check",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+
+use anyhow::{Context, Result};
+
+use pthash::*;
+
+#[test]
+fn test_check_reports_keys() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<Vec<u8>> = (0..1000).map(|i| format!("key{i}").into_bytes()).collect();
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    check(&keys, &f).context("Invalid function")?;
+
+    // A key which was not in the set collides with one which was
+    let mut checked_keys = keys.clone();
+    checked_keys.push(b"not a key".to_vec());
+    match check(&checked_keys, &f) {
+        Err(ViolatedInvariant::Duplicates {
+            key,
+            duplicate_hash,
+        }) => {
+            assert_eq!(key, b"not a key");
+            assert_eq!(duplicate_hash, f.hash(b"not a key"));
+            let colliding_keys = keys_with_hash(&checked_keys, &f, duplicate_hash);
+            assert_eq!(colliding_keys.len(), 2);
+            assert_eq!(colliding_keys[1], b"not a key");
+            assert!(keys.contains(&colliding_keys[0]));
+        }
+        Err(e) => panic!("Unexpected error: {e}"),
+        Ok(()) => panic!("Check of a colliding key succeeded"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_verify() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.verify = true;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    let mut f = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    config.num_partitions = 2;
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+
+    // Deduplicated keys share positions, so the function cannot be checked on them
+    config.deduplicate = true;
+    assert!(matches!(
+        f.build_in_internal_memory_from_bytes(|| &keys, &config),
+        Err(BuildError::VerifyWithDeduplicate)
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(all(feature = "cli", feature = "elias_fano"))]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use anyhow::{ensure, Context, Result};
+
+use pthash::dynamic::*;
+use pthash::*;
+
+#[test]
+fn test_cli_build() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let path = temp_dir.path().join("f.bin");
+
+    let keys: Vec<String> = (0..1000).map(|i| format!("key{i}")).collect();
+
+    let mut child = Command::new(env!("CARGO_BIN_EXE_pthash"))
+        .arg("build")
+        .arg("-o")
+        .arg(&path)
+        .arg("--tmp-dir")
+        .arg(temp_dir.path())
+        .args(["-p", "2", "-e", "elias_fano", "--minimal"])
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("Could not run pthash")?;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(keys.join("\n").as_bytes())
+        .context("Could not write keys")?;
+    ensure!(child.wait()?.success(), "pthash failed");
+
+    let f = load_any(&path).context("Could not load")?;
+    assert!(matches!(f, AnyPhf::MinimalPartitioned64(_)));
+    assert_eq!(f.metadata().encoder, "elias_fano");
+    let mut hashes: Vec<u64> = keys.iter().map(|key| f.hash(key.as_bytes())).collect();
+    hashes.sort();
+    assert_eq!(hashes, (0..1000).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[test]
+fn test_cli_query_stats() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let path = temp_dir.path().join("f.bin");
+    let keys_path = temp_dir.path().join("keys.txt");
+
+    let keys: Vec<String> = (0..1000).map(|i| format!("key{i}")).collect();
+    std::fs::write(&keys_path, keys.join("\n")).context("Could not write keys")?;
+
+    let status = Command::new(env!("CARGO_BIN_EXE_pthash"))
+        .arg("build")
+        .arg("-i")
+        .arg(&keys_path)
+        .arg("-o")
+        .arg(&path)
+        .arg("--tmp-dir")
+        .arg(temp_dir.path())
+        .args(["-e", "elias_fano", "--seed", "42"])
+        .status()
+        .context("Could not run pthash build")?;
+    ensure!(status.success(), "pthash build failed");
+    let f = load_any(&path).context("Could not load")?;
+
+    let output = Command::new(env!("CARGO_BIN_EXE_pthash"))
+        .arg("query")
+        .arg(&path)
+        .arg("-i")
+        .arg(&keys_path)
+        .output()
+        .context("Could not run pthash query")?;
+    ensure!(output.status.success(), "pthash query failed");
+    let hashes: Vec<u64> = String::from_utf8(output.stdout)?
+        .lines()
+        .map(|line| line.parse())
+        .collect::<Result<_, _>>()?;
+    let expected: Vec<u64> = keys.iter().map(|key| f.hash(key.as_bytes())).collect();
+    assert_eq!(hashes, expected);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_pthash"))
+        .arg("stats")
+        .arg(&path)
+        .output()
+        .context("Could not run pthash stats")?;
+    ensure!(output.status.success(), "pthash stats failed");
+    let stats = String::from_utf8(output.stdout)?;
+    assert!(stats.contains("num_keys: 1000\n"), "{stats}");
+    assert!(stats.contains("minimal: false\n"), "{stats}");
+    assert!(stats.contains("encoder: elias_fano\n"), "{stats}");
+    assert!(stats.contains("seed: 42\n"), "{stats}");
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests shared by the hashers other than MurmurHash2
+
+use anyhow::{Context, Result};
+
+use pthash::*;
+
+/// Returns the integers in `[0; 1000)`
+pub fn int_keys() -> Vec<u64> {
+    (0..1000).collect()
+}
+
+/// Builds a function with `H` on `keys`, and checks it is perfect (and minimal if
+/// `M` is)
+pub fn test_hasher<M: Minimality, H: Hasher, K>(keys: &[K]) -> Result<()>
+where
+    for<'a> &'a K: Hashable,
+{
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let mut f = SinglePhf::<M, H, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| keys, &config)
+        .context("Failed to build")?;
+
+    let mut hashes: Vec<u64> = keys.iter().map(|key| f.hash(key)).collect();
+    hashes.sort();
+    if M::AS_BOOL {
+        assert_eq!(hashes, (0..keys.len() as u64).collect::<Vec<_>>());
+    } else {
+        hashes.dedup();
+        assert_eq!(hashes.len(), keys.len());
+    }
+
+    Ok(())
+}
+
+/// Saves a function built with `H`, and checks it can only be loaded with `H`, not
+/// with `G` which has the same hash size
+pub fn test_load_other_hasher<H: Hasher, G: Hasher<Hash = H::Hash>>() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys = int_keys();
+    let path = temp_dir.path().join("f.bin");
+
+    let mut f = SinglePhf::<Minimal, H, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    f.save(&path).context("Could not save")?;
+
+    let loaded =
+        SinglePhf::<Minimal, H, DictionaryDictionary>::load(&path).context("Could not load")?;
+    for key in &keys {
+        assert_eq!(loaded.hash(key), f.hash(key));
+    }
+    assert!(matches!(
+        SinglePhf::<Minimal, G, DictionaryDictionary>::load(&path),
+        Err(metadata::LoadError::TypeMismatch { .. })
+    ));
+
+    Ok(())
+}
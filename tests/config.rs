@@ -0,0 +1,98 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use anyhow::Result;
+
+use pthash::config::*;
+use pthash::*;
+
+#[test]
+fn test_overrides_from_vars() -> Result<()> {
+    let vars = [
+        ("PTHASH_NUM_THREADS", "8"),
+        ("PTHASH_ALPHA", "0.9"),
+        ("PTHASH_TMP_DIR", "/tmp/pthash"),
+        ("PTHASH_DEDUPLICATE", "true"),
+        ("PTHASH_EXTRA_ENCODERS", "compact_compact"),
+        ("HOME", "/root"),
+    ]
+    .map(|(name, value)| (name.to_owned(), value.to_owned()));
+    let overrides = BuildConfigurationOverrides::from_vars(vars)?;
+
+    let mut config = BuildConfiguration::new("/tmp".into());
+    let default_c = config.c;
+    overrides.apply(&mut config);
+    assert_eq!(config.num_threads, 8);
+    assert_eq!(config.alpha, 0.9);
+    assert_eq!(config.tmp_dir, std::path::Path::new("/tmp/pthash"));
+    assert!(config.deduplicate);
+    assert_eq!(config.c, default_c);
+
+    let vars = [("PTHASH_NUM_THREADS".to_owned(), "many".to_owned())];
+    assert!(matches!(
+        BuildConfigurationOverrides::from_vars(vars),
+        Err(ConfigError::InvalidVar { .. })
+    ));
+
+    Ok(())
+}
+
+#[cfg(feature = "toml")]
+#[test]
+fn test_from_toml() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    let path = temp_dir.path().join("pthash.toml");
+    std::fs::write(&path, "c = 5.0\nnum_partitions = 4\nseed = 42\n")?;
+
+    let config = BuildConfiguration::from_toml(&path)?;
+    assert_eq!(config.c, 5.0);
+    assert_eq!(config.num_partitions, 4);
+    assert_eq!(config.seed, 42);
+    assert_eq!(config.tmp_dir, std::env::temp_dir());
+
+    std::fs::write(&path, "not_a_field = 1\n")?;
+    assert!(matches!(
+        BuildConfiguration::from_toml(&path),
+        Err(ConfigError::Toml { .. })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_builder() -> Result<()> {
+    let config = BuildConfiguration::builder("/tmp".into())
+        .c(5.0)
+        .alpha(0.9)
+        .num_threads(4)
+        .num_partitions(2)
+        .build()?;
+    assert_eq!(config.c, 5.0);
+    assert_eq!(config.alpha, 0.9);
+    assert_eq!(config.num_threads, 4);
+    assert_eq!(config.num_partitions, 2);
+
+    for builder in [
+        BuildConfiguration::builder("/tmp".into()).alpha(0.),
+        BuildConfiguration::builder("/tmp".into()).alpha(1.5),
+        BuildConfiguration::builder("/tmp".into()).c(-1.),
+        BuildConfiguration::builder("/tmp".into()).num_threads(0),
+        BuildConfiguration::builder("/tmp".into()).num_partitions(0),
+    ] {
+        assert!(matches!(
+            builder.build(),
+            Err(ConfigError::OutOfRange { .. })
+        ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_default() {
+    let config = BuildConfiguration::default();
+    assert_eq!(config.tmp_dir, std::env::temp_dir());
+    assert_eq!(config, BuildConfiguration::new(std::env::temp_dir()));
+}
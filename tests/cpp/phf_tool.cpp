@@ -0,0 +1,57 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+// Builds and queries functions with PTHash's C++ implementation, for tests/load.rs.
+// Unlike PTHash's `build` tool, it can load functions (eg. written by Rust) and
+// supports every type; it is compiled once per type, with PHF_TYPE set to a
+// pthash::single_phf or pthash::partitioned_phf and PHF_MINIMAL to whether it is
+// minimal.
+//
+// Keys are read from stdin, one per line:
+//
+//     phf_tool build <output> <num_partitions> <seed> < keys
+//     phf_tool query <input> < keys
+//
+// `query` prints the position of each key, one per line.
+
+#include <iostream>
+#include <string>
+#include <vector>
+
+#include <pthash.hpp>
+
+int main(int argc, char** argv) {
+    std::string command = argc > 1 ? argv[1] : "";
+    if (!((command == "build" && argc == 5) || (command == "query" && argc == 3))) {
+        std::cerr << "Usage: " << argv[0] << " build <output> <num_partitions> <seed>\n"
+                  << "       " << argv[0] << " query <input>" << std::endl;
+        return 1;
+    }
+
+    std::vector<std::string> keys;
+    for (std::string key; std::getline(std::cin, key);) {
+        keys.push_back(key);
+    }
+
+    PHF_TYPE f;
+    if (command == "build") {
+        pthash::build_configuration config;
+        config.c = 3.0;
+        config.alpha = 0.94;
+        config.num_partitions = std::stoull(argv[3]);
+        config.seed = std::stoull(argv[4]);
+        config.minimal_output = PHF_MINIMAL;
+        config.verbose_output = false;
+        f.build_in_internal_memory(keys.begin(), keys.size(), config);
+        essentials::save(f, argv[2]);
+    } else {
+        // Trailing bytes, such as the footer written by Rust, are ignored
+        essentials::load(f, argv[2]);
+        for (auto const& key : keys) {
+            std::cout << f(key) << '\n';
+        }
+    }
+    return 0;
+}
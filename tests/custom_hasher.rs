@@ -5,6 +5,8 @@
 
 //! Tests building and calling a [`SinglePhf`] with a custom [`Hasher`] defined in Rust
 
+#![cfg(feature = "build")]
+
 use std::hash::Hasher;
 
 use anyhow::{Context, Result};
@@ -17,6 +19,7 @@ struct CustomHasher64;
 #[cfg(feature = "hash64")]
 impl pthash::Hasher for CustomHasher64 {
     type Hash = hashing::hash64;
+    const NAME: &'static str = "custom64";
 
     fn hash(val: impl Hashable, seed: u64) -> Self::Hash {
         // Reuse Rust's hashing algorithm
@@ -33,6 +36,7 @@ struct CustomHasher128;
 #[cfg(feature = "hash128")]
 impl pthash::Hasher for CustomHasher128 {
     type Hash = hashing::hash128;
+    const NAME: &'static str = "custom128";
 
     fn hash(val: impl Hashable, seed: u64) -> Self::Hash {
         let mut high_hasher = std::hash::DefaultHasher::new();
@@ -0,0 +1,54 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests queries panic with the `debug-checks` feature when a corrupted function
+//! returns positions out of its range
+
+#![cfg(all(
+    feature = "build",
+    feature = "debug-checks",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+
+use pthash::*;
+
+#[test]
+#[should_panic(expected = "but it should be lower than the")]
+fn test_corrupted_partition_offset() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.num_partitions = 4;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let mut f = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .expect("Failed to build");
+    assert_eq!(f.num_partitions(), 4);
+    let bytes = f.to_bytes().expect("Could not serialize");
+
+    // Partitions are laid out in order, so the last one starts after the keys of the
+    // others
+    let last_offset = keys.iter().filter(|&key| f.partition_of(key) < 3).count() as u64;
+
+    // Move the last partition past the last key. Offsets are not checked when
+    // loading, so the corrupted function loads, with position 999 now in partition 2.
+    let corrupted = (0..bytes.len() - 8)
+        .filter(|&i| bytes[i..i + 8] == last_offset.to_le_bytes())
+        .find_map(|i| {
+            let mut corrupted = bytes.clone();
+            corrupted[i..i + 8].copy_from_slice(&1000u64.to_le_bytes());
+            PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::from_bytes(&corrupted)
+                .ok()
+                .filter(|g| g.num_partitions() == 4 && g.partition_of_position(999) == 2)
+        })
+        .expect("Could not find the offset of the last partition");
+
+    for key in &keys {
+        corrupted.hash(key);
+    }
+}
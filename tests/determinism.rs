@@ -0,0 +1,97 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests building a function twice from the same keys, seed, and configuration
+//! produces byte-identical serialized functions on the same platform.
+
+#![cfg(feature = "build")]
+
+use anyhow::{Context, Result};
+
+use pthash::*;
+
+macro_rules! impl_test {
+    ($test_name:ident, $struct_name:ident) => {
+        fn $test_name<M: Minimality, H: Hasher, E: Encoder>(num_partitions: u64) -> Result<()> {
+            let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+            let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+            config.verbose_output = false;
+            config.seed = 42;
+            config.num_partitions = num_partitions;
+
+            let keys: Vec<Vec<u8>> = (0..10000u64)
+                .map(|i| format!("key{}", i).into_bytes())
+                .collect();
+
+            let mut serialized = Vec::new();
+            for i in 0..2 {
+                let mut f = $struct_name::<M, H, E>::new();
+                f.build_in_internal_memory_from_bytes(|| &keys, &config)
+                    .context("Failed to build")?;
+                let path = temp_dir.path().join(format!("phf{}.bin", i));
+                f.save(&path).context("Could not save function")?;
+                serialized.push(std::fs::read(&path).context("Could not read function")?);
+            }
+            assert!(
+                serialized[0] == serialized[1],
+                "Serialized functions differ"
+            );
+
+            #[cfg(feature = "rayon")]
+            {
+                let mut f = $struct_name::<M, H, E>::new();
+                f.par_build_in_internal_memory_from_bytes(|| &keys, &config)
+                    .context("Failed to build")?;
+                let path = temp_dir.path().join("phf_par.bin");
+                f.save(&path).context("Could not save function")?;
+                assert!(
+                    std::fs::read(&path).context("Could not read function")? == serialized[0],
+                    "Serialized functions differ when hashing in parallel"
+                );
+            }
+
+            Ok(())
+        }
+    };
+}
+
+impl_test!(test_single, SinglePhf);
+impl_test!(test_partitioned, PartitionedPhf);
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_minimal_hash64_dictionary_dictionary() -> Result<()> {
+    test_single::<Minimal, MurmurHash2_64, DictionaryDictionary>(1)
+}
+
+#[cfg(all(feature = "nonminimal", feature = "hash128", feature = "elias_fano"))]
+#[test]
+fn test_single_nonminimal_hash128_elias_fano() -> Result<()> {
+    test_single::<Nonminimal, MurmurHash2_128, EliasFano>(1)
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_partitioned_minimal_hash64_dictionary_dictionary() -> Result<()> {
+    test_partitioned::<Minimal, MurmurHash2_64, DictionaryDictionary>(4)
+}
+
+#[cfg(all(
+    feature = "nonminimal",
+    feature = "hash128",
+    feature = "partitioned_compact"
+))]
+#[test]
+fn test_partitioned_nonminimal_hash128_partitioned_compact() -> Result<()> {
+    test_partitioned::<Nonminimal, MurmurHash2_128, PartitionedCompact>(4)
+}
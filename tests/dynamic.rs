@@ -0,0 +1,113 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(all(
+    feature = "build",
+    feature = "minimal",
+    feature = "hash64",
+    any(
+        feature = "dictionary_dictionary",
+        feature = "partitioned_compact",
+        feature = "elias_fano",
+        feature = "compact",
+        feature = "dictionary"
+    )
+))]
+
+use anyhow::{Context, Result};
+
+use pthash::dynamic::*;
+use pthash::*;
+
+#[test]
+fn test_dyn_single_phf() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<u64> = (0..1000).collect();
+
+    for &encoder in EncoderKind::ALL {
+        let mut f = DynSinglePhf::<Minimal, MurmurHash2_64>::new(encoder);
+        assert_eq!(f.encoder(), encoder);
+        f.build_in_internal_memory_from_bytes(|| &keys, &config)
+            .with_context(|| format!("Failed to build with {}", encoder.name()))?;
+        let mut hashes: Vec<u64> = keys.iter().map(|key| f.hash(key)).collect();
+        hashes.sort();
+        assert_eq!(hashes, keys);
+
+        let path = temp_dir.path().join(encoder.name());
+        f.save(&path).context("Could not save")?;
+        let loaded = DynSinglePhf::<Minimal, MurmurHash2_64>::load(&path, encoder)
+            .context("Could not load")?;
+        assert_eq!(loaded.encoder(), encoder);
+        for key in &keys {
+            assert_eq!(loaded.hash(key), f.hash(key));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_dyn_single_phf_smallest() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<u64> = (0..10000).collect();
+
+    let (f, _timings, sizes) =
+        DynSinglePhf::<Minimal, MurmurHash2_64>::build_smallest_in_internal_memory_from_bytes(
+            || &keys,
+            &config,
+        )
+        .context("Failed to build")?;
+    let encoders: Vec<_> = sizes.iter().map(|size| size.encoder).collect();
+    assert_eq!(encoders, EncoderKind::ALL);
+    assert_eq!(
+        f.num_bits(),
+        sizes.iter().map(|size| size.num_bits).min().unwrap()
+    );
+    let mut hashes: Vec<u64> = keys.iter().map(|key| f.hash(key)).collect();
+    hashes.sort();
+    assert_eq!(hashes, keys);
+
+    // Every encoding is of the same pilots as a build with the resolved configuration
+    let resolved = f.build_configuration().unwrap().clone();
+    for size in &sizes {
+        let mut g = DynSinglePhf::<Minimal, MurmurHash2_64>::new(size.encoder);
+        g.build_in_internal_memory_from_bytes(|| &keys, &resolved)
+            .with_context(|| format!("Failed to build with {}", size.encoder.name()))?;
+        assert_eq!(g.num_bits(), size.num_bits);
+        for key in &keys {
+            assert_eq!(g.hash(key), f.hash(key));
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_dyn_partitioned_phf() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.num_partitions = 4;
+
+    let keys: Vec<u64> = (0..10000).collect();
+
+    for &encoder in EncoderKind::ALL {
+        let mut f = DynPartitionedPhf::<Minimal, MurmurHash2_64>::new(encoder);
+        f.build_in_internal_memory_from_bytes(|| &keys, &config)
+            .with_context(|| format!("Failed to build with {}", encoder.name()))?;
+        assert_eq!(f.num_keys(), 10000);
+        let mut hashes: Vec<u64> = keys.iter().map(|key| f.hash(key)).collect();
+        hashes.sort();
+        assert_eq!(hashes, keys);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests the build script rejects invalid `PTHASH_EXTRA_ENCODERS` lists
+
+use std::process::Command;
+
+/// Runs `cargo check` on this crate with the given `PTHASH_EXTRA_ENCODERS`, and
+/// returns its stderr after checking it failed
+fn check_with_extra_encoders(extra_encoders: &str) -> String {
+    let output = Command::new(env!("CARGO"))
+        .args(["check", "--lib"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        // Not the target directory of the current build, to keep its build script's
+        // output
+        .env("CARGO_TARGET_DIR", env!("CARGO_TARGET_TMPDIR"))
+        .env("PTHASH_EXTRA_ENCODERS", extra_encoders)
+        .output()
+        .expect("Could not run cargo");
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    assert!(!output.status.success(), "Build succeeded:\n{stderr}");
+    stderr
+}
+
+#[test]
+fn test_duplicate_extra_encoders() {
+    let stderr = check_with_extra_encoders("compact_compact, compact_compact");
+    assert!(
+        stderr.contains(
+            "encoder \"compact_compact\" is listed more than once in PTHASH_EXTRA_ENCODERS"
+        ),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn test_builtin_extra_encoders() {
+    let stderr = check_with_extra_encoders("elias_fano");
+    assert!(
+        stderr.contains(
+            "encoder \"elias_fano\" in PTHASH_EXTRA_ENCODERS is built in, enable its feature instead"
+        ),
+        "{stderr}"
+    );
+}
@@ -0,0 +1,112 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests [`Hasher::hash_batch`] returns the same hashes as [`Hasher::hash`]
+
+#![cfg(feature = "hash128")]
+
+use pthash::*;
+
+fn check_batch<H: Hasher, K: Hashable>(keys: &[K])
+where
+    H::Hash: PartialEq + std::fmt::Debug,
+{
+    for seed in [0, 42, u64::MAX] {
+        let expected: Vec<_> = keys.iter().map(|key| H::hash(key, seed)).collect();
+        let mut hashes = Vec::new();
+        H::hash_batch(keys.iter(), seed, &mut hashes);
+        assert_eq!(hashes, expected);
+    }
+}
+
+fn check_all_hashers<K: Hashable>(keys: &[K]) {
+    check_batch::<MurmurHash2_64, _>(keys);
+    check_batch::<MurmurHash2_128, _>(keys);
+}
+
+#[test]
+fn test_hash_batch_u64() {
+    let keys: Vec<u64> = (0..1001).map(|i| i * 0x9e3779b97f4a7c15).collect();
+    check_all_hashers(&keys);
+    check_all_hashers::<u64>(&[]);
+}
+
+#[test]
+fn test_hash_batch_fixed_width() {
+    for len in [0, 1, 3, 7, 8, 9, 13, 16, 32, 33] {
+        let keys: Vec<Vec<u8>> = (0..100u8)
+            .map(|i| {
+                (0..len)
+                    .map(|j| i.wrapping_mul(31).wrapping_add(j))
+                    .collect()
+            })
+            .collect();
+        check_all_hashers(&keys);
+    }
+}
+
+#[test]
+fn test_hash_batch_mixed_lengths() {
+    let keys: Vec<Vec<u8>> = (0..100u8)
+        .map(|i| (0..i % 20).map(|j| i ^ j).collect())
+        .collect();
+    check_all_hashers(&keys);
+}
+
+#[test]
+fn test_hashable_arrays_and_tuples() {
+    let digest: [u8; 20] = std::array::from_fn(|i| i as u8);
+    assert_eq!(
+        MurmurHash2_64::hash(digest, 42),
+        MurmurHash2_64::hash(&digest[..], 42)
+    );
+
+    let key = (42u64, digest);
+    let mut concatenated = 42u64.as_bytes().to_vec();
+    concatenated.extend_from_slice(&digest);
+    assert_eq!(
+        MurmurHash2_64::hash(key, 42),
+        MurmurHash2_64::hash(&concatenated, 42)
+    );
+    assert_eq!(
+        MurmurHash2_128::hash((1u64, 2u64, 3u64), 42),
+        MurmurHash2_128::hash([1u64, 2, 3].map(|n| n.as_bytes()).concat(), 42)
+    );
+
+    let keys: Vec<[u8; 20]> = (0..100u8).map(|i| [i; 20]).collect();
+    check_all_hashers(&keys);
+    let keys: Vec<(u64, [u8; 4])> = (0..100u64).map(|i| (i, [i as u8; 4])).collect();
+    check_all_hashers(&keys);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_hashable_paths_and_strings() {
+    use std::ffi::{CString, OsStr, OsString};
+    use std::path::{Path, PathBuf};
+
+    let expected = MurmurHash2_64::hash(b"dir/file.txt".as_slice(), 42);
+    assert_eq!(
+        MurmurHash2_64::hash(OsStr::new("dir/file.txt"), 42),
+        expected
+    );
+    assert_eq!(
+        MurmurHash2_64::hash(OsString::from("dir/file.txt"), 42),
+        expected
+    );
+    assert_eq!(
+        MurmurHash2_64::hash(Path::new("dir/file.txt"), 42),
+        expected
+    );
+    assert_eq!(
+        MurmurHash2_64::hash(PathBuf::from("dir").join("file.txt"), 42),
+        expected
+    );
+    assert_eq!(
+        MurmurHash2_64::hash(CString::new("dir/file.txt").unwrap(), 42),
+        expected
+    );
+    assert_eq!(MurmurHash2_64::hash(c"dir/file.txt", 42), expected);
+}
@@ -0,0 +1,64 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(all(
+    feature = "build",
+    feature = "hot-reload",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+
+use pthash::hot::HotPhf;
+use pthash::*;
+
+type F = SinglePhf<Minimal, MurmurHash2_64, DictionaryDictionary>;
+
+#[test]
+fn test_hot_reload() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let build = |num_keys: u64| -> Result<F> {
+        let keys: Vec<u64> = (0..num_keys).collect();
+        let mut f = F::new();
+        f.build_in_internal_memory_from_bytes(|| &keys, &config)
+            .context("Failed to build")?;
+        Ok(f)
+    };
+
+    let path = temp_dir.path().join("phf.bin");
+    build(100)?.save(&path).context("Could not save function")?;
+
+    let hot = HotPhf::<F>::load(&path)?;
+    assert_eq!(hot.num_keys(), 100);
+    assert!(!hot.reload_if_changed()?);
+
+    let previous = hot.swap(build(200)?);
+    assert_eq!(previous.num_keys(), 100);
+    assert_eq!(hot.num_keys(), 200);
+
+    // Replace the file atomically, with a distinct modification time
+    let new_path = temp_dir.path().join("phf.bin.new");
+    build(300)?
+        .save(&new_path)
+        .context("Could not save function")?;
+    std::fs::File::options()
+        .write(true)
+        .open(&new_path)?
+        .set_modified(SystemTime::now() + Duration::from_secs(10))?;
+    std::fs::rename(&new_path, &path)?;
+
+    assert!(hot.reload_if_changed()?);
+    assert_eq!(hot.num_keys(), 300);
+    assert!(!hot.reload_if_changed()?);
+
+    Ok(())
+}
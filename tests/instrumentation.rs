@@ -0,0 +1,114 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(all(
+    feature = "build",
+    feature = "metrics",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+
+use anyhow::{Context, Result};
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+
+use pthash::instrumentation::*;
+use pthash::*;
+
+#[test]
+fn test_instrumented_phf() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<u64> = (0..1000).collect();
+
+    let mut f = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        let instrumented = InstrumentedPhf::new(&f, "test", 4);
+        for key in &keys {
+            assert_eq!(instrumented.hash(key), f.hash(key));
+        }
+    });
+
+    let mut num_queries = 0;
+    let mut num_regions = 0;
+    let mut num_durations = 0;
+    for (key, _unit, _description, value) in snapshotter.snapshot().into_vec() {
+        match (key.key().name(), value) {
+            (QUERIES_METRIC, DebugValue::Counter(count)) => {
+                num_regions += 1;
+                num_queries += count;
+            }
+            (QUERY_DURATION_METRIC, DebugValue::Histogram(values)) => {
+                num_durations += values.len();
+            }
+            (name, value) => panic!("Unexpected metric {name}: {value:?}"),
+        }
+    }
+    assert_eq!(num_regions, 4);
+    assert_eq!(num_queries, 1000);
+    // Only some queries are timed
+    let num_samples = 1000 / DEFAULT_DURATION_SAMPLE_INTERVAL as usize;
+    assert!((num_samples..=num_samples + 1).contains(&num_durations));
+
+    Ok(())
+}
+
+#[test]
+fn test_instrumented_phf_per_partition() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.num_partitions = 4;
+
+    let keys: Vec<u64> = (0..1000).collect();
+
+    let mut f = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    let mut expected = vec![0u64; f.num_partitions() as usize];
+    for key in &keys {
+        expected[f.partition_of(key) as usize] += 1;
+    }
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    metrics::with_local_recorder(&recorder, || {
+        let instrumented =
+            InstrumentedPhf::per_partition(&f, "test").with_duration_sample_interval(1);
+        for key in &keys {
+            assert_eq!(instrumented.hash(key), f.hash(key));
+        }
+    });
+
+    let mut counts = vec![0; f.num_partitions() as usize];
+    let mut num_durations = 0;
+    for (key, _unit, _description, value) in snapshotter.snapshot().into_vec() {
+        match (key.key().name(), value) {
+            (QUERIES_METRIC, DebugValue::Counter(count)) => {
+                let region = key
+                    .key()
+                    .labels()
+                    .find(|label| label.key() == "region")
+                    .expect("Missing region label");
+                counts[region.value().parse::<usize>()?] = count;
+            }
+            (QUERY_DURATION_METRIC, DebugValue::Histogram(values)) => {
+                num_durations += values.len();
+            }
+            (name, value) => panic!("Unexpected metric {name}: {value:?}"),
+        }
+    }
+    assert_eq!(counts, expected);
+    assert_eq!(num_durations, 1000);
+
+    Ok(())
+}
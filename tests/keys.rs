@@ -0,0 +1,85 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests key sources from [`pthash::keys`]
+
+use anyhow::{Context, Result};
+
+use pthash::keys::*;
+
+#[test]
+fn test_merged_key_files() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let paths = [
+        temp_dir.path().join("1.txt"),
+        temp_dir.path().join("2.txt"),
+        temp_dir.path().join("3.txt"),
+    ];
+    std::fs::write(&paths[0], "abc\ndef\ndef\nxyz\n").context("Could not write keys")?;
+    std::fs::write(&paths[1], "def\nghi\n").context("Could not write keys")?;
+    std::fs::write(&paths[2], "").context("Could not write keys")?;
+
+    let files = MergedKeyFiles::new(&paths);
+    for _ in 0..2 {
+        let keys = files
+            .iter()
+            .context("Could not open keys")?
+            .collect::<std::io::Result<Vec<_>>>()
+            .context("Could not read keys")?;
+        assert_eq!(
+            keys,
+            vec![
+                b"abc".to_vec(),
+                b"def".to_vec(),
+                b"ghi".to_vec(),
+                b"xyz".to_vec()
+            ]
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_merged_key_files_unsorted() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let path = temp_dir.path().join("keys.txt");
+    std::fs::write(&path, "def\nabc\n").context("Could not write keys")?;
+
+    let files = MergedKeyFiles::new([path]);
+    assert!(files
+        .iter()
+        .context("Could not open keys")?
+        .any(|key| key.is_err()));
+
+    Ok(())
+}
+
+#[test]
+fn test_key_snapshot() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let set: std::collections::HashSet<u64> = (0..10000).collect();
+    let mut expected: Vec<Vec<u8>> = set.iter().map(|key| key.to_ne_bytes().to_vec()).collect();
+    expected.sort();
+
+    // In memory, and spilled to disk
+    for memory_limit in [usize::MAX, 1000] {
+        let snapshot = KeySnapshot::new(&set, memory_limit, temp_dir.path())
+            .context("Could not snapshot keys")?;
+        for _ in 0..2 {
+            let keys = snapshot
+                .iter()
+                .context("Could not open keys")?
+                .collect::<std::io::Result<Vec<_>>>()
+                .context("Could not read keys")?;
+            assert_eq!(keys, expected);
+        }
+    }
+
+    // Spilled runs are removed
+    assert_eq!(std::fs::read_dir(temp_dir.path())?.count(), 0);
+
+    Ok(())
+}
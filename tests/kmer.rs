@@ -0,0 +1,57 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests building a [`SinglePhf`] over [`Kmer`] keys
+
+use anyhow::{Context, Result};
+
+use pthash::kmer::Kmer;
+use pthash::*;
+
+#[test]
+fn test_kmer_encoding() {
+    let kmer = Kmer::<5>::from_ascii(b"ACGTa").unwrap();
+    assert_eq!(kmer.packed(), 0b00_01_10_11_00);
+    assert_eq!(&kmer.to_ascii(), b"ACGTA");
+    assert_eq!(kmer.as_bytes().as_ref(), &[0b01_10_11_00, 0b00]);
+
+    assert_eq!(
+        Kmer::<64>::from_packed(u128::MAX).as_bytes().as_ref().len(),
+        16
+    );
+    assert!(Kmer::<3>::from_ascii(b"ACN").is_none());
+    assert!(Kmer::<3>::from_ascii(b"ACGT").is_none());
+}
+
+#[cfg(all(
+    feature = "build",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_kmer_keys() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let seq = b"ACGTTGCAAGGCTTACGATCGATCGGATTACA";
+    let mut kmers: Vec<Kmer<21>> = seq
+        .windows(21)
+        .map(|window| Kmer::from_ascii(window).unwrap())
+        .collect();
+    kmers.sort();
+    kmers.dedup();
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &kmers, &config)
+        .context("Failed to build")?;
+
+    let mut hashes: Vec<u64> = kmers.iter().map(|kmer| f.hash(kmer)).collect();
+    hashes.sort();
+    assert_eq!(hashes, Vec::from_iter(0..kmers.len() as u64));
+
+    Ok(())
+}
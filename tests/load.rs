@@ -4,14 +4,18 @@
 // See top-level LICENSE file for more information
 
 //! Tests functions generated purely with the C++ implementation can be read from
-//! Rust and compute the same values.
+//! Rust and compute the same values, and that functions generated by Rust can be
+//! read by the C++ implementation.
 //!
-//! This assumes PTHash's executable is available as `pthash/build/build`
-//! (built with `mkdir pthash/build; cd pthash/build; cmake ..; make`)
+//! This builds PTHash's executable as `pthash/build/build`, and compiles
+//! `tests/cpp/phf_tool.cpp` for each type which is cross-validated, with the
+//! compiler in the `CXX` environment variable (or `c++`).
 
 use std::collections::HashSet;
 use std::env;
 use std::io::{Read, Write};
+#[cfg(feature = "build")]
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::sync::Once;
 
@@ -20,6 +24,8 @@ use rand::distr::Alphanumeric;
 use rand::rngs::StdRng;
 use rand::{Rng, SeedableRng};
 
+#[cfg(feature = "build")]
+use pthash::metadata::PhfMetadata;
 use pthash::*;
 
 static INIT: Once = Once::new();
@@ -40,6 +46,121 @@ pub fn setup_test() {
     });
 }
 
+/// Returns at most `num_keys` distinct keys, which may be empty but do not contain
+/// newlines
+fn random_keys(num_keys: u64) -> Vec<Vec<u8>> {
+    let mut rng = StdRng::seed_from_u64(42);
+    let mut keys = Vec::new();
+    let mut key_set = HashSet::new();
+    for _ in 0..num_keys {
+        let len: u8 = rng.random();
+        let key: Vec<u8> = (0..len).map(|_| rng.sample(&Alphanumeric)).collect();
+        if !key_set.contains(&key) {
+            // Cannot have duplicates in the set of keys.
+            // We push to a vec in order for the test to be deterministic
+            key_set.insert(key.clone());
+            keys.push(key);
+        }
+    }
+    keys
+}
+
+/// Compiles `tests/cpp/phf_tool.cpp` to `dir` for the C++ type described by
+/// `metadata`, and returns the path to the executable
+#[cfg(feature = "build")]
+fn compile_phf_tool(metadata: &PhfMetadata, dir: &Path) -> Result<PathBuf> {
+    let phf_type = format!(
+        "pthash::{}<pthash::{}, pthash::{}, {}>",
+        if metadata.partitioned {
+            "partitioned_phf"
+        } else {
+            "single_phf"
+        },
+        metadata.hasher,
+        metadata.encoder,
+        metadata.minimal
+    );
+    let tool_path = dir.join("phf_tool");
+    let mut cmd = Command::new(env::var("CXX").unwrap_or_else(|_| "c++".to_owned()));
+    cmd.arg("-std=c++17")
+        .arg("-O2")
+        .arg(format!("-DPHF_TYPE={phf_type}"))
+        .arg(format!("-DPHF_MINIMAL={}", metadata.minimal))
+        .arg("-Ipthash")
+        .arg("-Ipthash/include")
+        .arg("-Ipthash/external/essentials/include")
+        .arg("tests/cpp/phf_tool.cpp")
+        .arg("-o")
+        .arg(&tool_path)
+        .arg("-pthread");
+    let output = cmd.output().context("Could not run C++ compiler")?;
+    if !output.status.success() {
+        bail!(
+            "{:?} failed with:\n{}",
+            cmd,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(tool_path)
+}
+
+/// Runs `phf_tool` with the given arguments and keys as stdin, and returns its stdout
+#[cfg(feature = "build")]
+fn run_phf_tool(tool_path: &Path, args: &[&std::ffi::OsStr], keys: &[Vec<u8>]) -> Result<String> {
+    let mut proc = Command::new(tool_path)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Could not spawn phf_tool")?;
+    let mut stdin = proc.stdin.take().unwrap();
+    let keys = keys.to_vec();
+    // Written from another thread, so phf_tool does not block on a full stdout
+    let writer = std::thread::spawn(move || -> std::io::Result<()> {
+        for key in &keys {
+            stdin.write_all(key)?;
+            stdin.write_all(b"\n")?;
+        }
+        Ok(())
+    });
+    let output = proc.wait_with_output().context("Could not wait phf_tool")?;
+    writer
+        .join()
+        .expect("writer thread panicked")
+        .context("Could not write to phf_tool's stdin")?;
+    if !output.status.success() {
+        bail!(
+            "phf_tool {:?} failed with:\n{}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    String::from_utf8(output.stdout).context("phf_tool's output is not UTF-8")
+}
+
+/// Returns the positions of `keys` computed by PTHash with the function at `path`
+#[cfg(feature = "build")]
+fn cpp_positions(tool_path: &Path, path: &Path, keys: &[Vec<u8>]) -> Result<Vec<u64>> {
+    run_phf_tool(tool_path, &["query".as_ref(), path.as_os_str()], keys)?
+        .lines()
+        .map(|line| line.parse().context("Could not parse phf_tool's output"))
+        .collect()
+}
+
+/// Checks positions are distinct, and in `[0; num_keys)` if `minimal`
+#[cfg(feature = "build")]
+fn check_positions(mut positions: Vec<u64>, minimal: bool) {
+    let num_keys = positions.len() as u64;
+    positions.sort();
+    if minimal {
+        assert_eq!(positions, Vec::from_iter(0..num_keys));
+    } else {
+        positions.dedup();
+        assert_eq!(positions.len() as u64, num_keys, "Duplicate positions");
+    }
+}
+
 macro_rules! impl_test {
     ($test_name:ident, $struct_name:ident) => {
         fn $test_name<M: Minimality, H: Hasher, E: Encoder>(
@@ -50,20 +171,7 @@ macro_rules! impl_test {
             let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
             let phf_path = temp_dir.path().join("phf.bin");
 
-            let mut rng = StdRng::seed_from_u64(42);
-            let mut keys = Vec::new();
-            let mut key_set = HashSet::new();
-            for _ in (0..num_keys) {
-                let len: u8 = rng.random();
-                let key: Vec<u8> = (0..len).map(|_| rng.sample(&Alphanumeric)).collect();
-                if !key_set.contains(&key) {
-                    // Cannot have duplicates in the set of keys.
-                    // We push to a vec in order for the test to be deterministic
-                    key_set.insert(key.clone());
-                    keys.push(key);
-                }
-            }
-            drop(key_set);
+            let keys = random_keys(num_keys);
             num_keys = keys.len() as u64;
 
             let log_path = temp_dir.path().join("logs.txt");
@@ -122,7 +230,7 @@ macro_rules! impl_test {
                 bail!("{:?} failed with:\n{}", cmd, logs,);
             }
 
-            let f = $struct_name::<M, H, E>::load(&phf_path).context("Failed to load PHF")?;
+            let f = $struct_name::<M, H, E>::load_raw(&phf_path).context("Failed to load PHF")?;
 
             if M::AS_BOOL {
                 // Hashes are unique and in the [0; num_keys) segment
@@ -218,3 +326,196 @@ fn test_2partitions_minimal_hash64_dictionary_dictionary() -> Result<()> {
 fn test_2partitions_nonminimal_hash64_dictionary_dictionary() -> Result<()> {
     test_partitioned::<Nonminimal, MurmurHash2_64, DictionaryDictionary>(200000, 2)
 }
+
+#[cfg(all(
+    feature = "nonminimal",
+    feature = "hash64",
+    feature = "partitioned_compact"
+))]
+#[test]
+fn test_single_nonminimal_hash64_partitioned_compact() -> Result<()> {
+    test_single::<Nonminimal, MurmurHash2_64, PartitionedCompact>(100, 1)
+}
+
+#[cfg(all(feature = "nonminimal", feature = "hash64", feature = "elias_fano"))]
+#[test]
+fn test_single_nonminimal_hash64_elias_fano() -> Result<()> {
+    test_single::<Nonminimal, MurmurHash2_64, EliasFano>(100, 1)
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_32partitions_minimal_hash64_dictionary_dictionary() -> Result<()> {
+    test_partitioned::<Minimal, MurmurHash2_64, DictionaryDictionary>(200000, 32)
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "partitioned_compact"
+))]
+#[test]
+fn test_32partitions_minimal_hash64_partitioned_compact() -> Result<()> {
+    test_partitioned::<Minimal, MurmurHash2_64, PartitionedCompact>(200000, 32)
+}
+
+#[cfg(all(feature = "minimal", feature = "hash64", feature = "elias_fano"))]
+#[test]
+fn test_32partitions_minimal_hash64_elias_fano() -> Result<()> {
+    test_partitioned::<Minimal, MurmurHash2_64, EliasFano>(200000, 32)
+}
+
+#[cfg(all(feature = "nonminimal", feature = "hash64", feature = "elias_fano"))]
+#[test]
+fn test_32partitions_nonminimal_hash64_elias_fano() -> Result<()> {
+    test_partitioned::<Nonminimal, MurmurHash2_64, EliasFano>(200000, 32)
+}
+
+macro_rules! impl_cross_validation {
+    ($test_name:ident, $struct_name:ident) => {
+        /// Checks functions built by Rust are read by PTHash, and conversely, by
+        /// comparing the positions they compute for each key
+        fn $test_name<M: Minimality, H: Hasher, E: Encoder>(
+            num_keys: u64,
+            num_partitions: u64,
+        ) -> Result<()> {
+            let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+            let tool_path =
+                compile_phf_tool(&$struct_name::<M, H, E>::metadata(), temp_dir.path())?;
+            let keys = random_keys(num_keys);
+
+            // Built by Rust, read by PTHash
+            let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+            config.verbose_output = false;
+            config.num_partitions = num_partitions;
+            config.c = 3.0;
+            config.seed = 42;
+            let rust_path = temp_dir.path().join("rust.bin");
+            let mut f = $struct_name::<M, H, E>::new();
+            f.build_in_internal_memory_from_bytes(|| &keys, &config)
+                .context("Failed to build")?;
+            f.save(&rust_path).context("Could not save")?;
+            let positions = cpp_positions(&tool_path, &rust_path, &keys)?;
+            assert_eq!(positions, f.hash_many(&keys));
+            check_positions(positions, M::AS_BOOL);
+
+            // Built by PTHash, read by Rust
+            let cpp_path = temp_dir.path().join("cpp.bin");
+            run_phf_tool(
+                &tool_path,
+                &[
+                    "build".as_ref(),
+                    cpp_path.as_os_str(),
+                    num_partitions.to_string().as_ref(),
+                    "42".as_ref(),
+                ],
+                &keys,
+            )?;
+            let f = $struct_name::<M, H, E>::load_raw(&cpp_path).context("Failed to load PHF")?;
+            let positions = cpp_positions(&tool_path, &cpp_path, &keys)?;
+            assert_eq!(positions, f.hash_many(&keys));
+            check_positions(positions, M::AS_BOOL);
+
+            Ok(())
+        }
+    };
+}
+
+#[cfg(feature = "build")]
+impl_cross_validation!(cross_validate_single, SinglePhf);
+#[cfg(feature = "build")]
+impl_cross_validation!(cross_validate_partitioned, PartitionedPhf);
+
+/// Generates a test cross-validating each type
+macro_rules! cross_validation_tests {
+    ($(
+        $name:ident: $cross_validate:ident::<$minimality:ident, $hasher:ident, $encoder:ident>(
+            $num_keys:expr, $num_partitions:expr
+        ) if [$($feature:literal),*];
+    )*) => {$(
+        #[cfg(all(feature = "build", $(feature = $feature),*))]
+        #[test]
+        fn $name() -> Result<()> {
+            $cross_validate::<$minimality, $hasher, $encoder>($num_keys, $num_partitions)
+        }
+    )*};
+}
+
+cross_validation_tests! {
+    test_cross_validate_single_minimal_hash64_dictionary_dictionary:
+        cross_validate_single::<Minimal, MurmurHash2_64, DictionaryDictionary>(10000, 1)
+        if ["minimal", "hash64", "dictionary_dictionary"];
+    test_cross_validate_single_minimal_hash64_partitioned_compact:
+        cross_validate_single::<Minimal, MurmurHash2_64, PartitionedCompact>(10000, 1)
+        if ["minimal", "hash64", "partitioned_compact"];
+    test_cross_validate_single_minimal_hash64_elias_fano:
+        cross_validate_single::<Minimal, MurmurHash2_64, EliasFano>(10000, 1)
+        if ["minimal", "hash64", "elias_fano"];
+    test_cross_validate_single_minimal_hash128_dictionary_dictionary:
+        cross_validate_single::<Minimal, MurmurHash2_128, DictionaryDictionary>(10000, 1)
+        if ["minimal", "hash128", "dictionary_dictionary"];
+    test_cross_validate_single_minimal_hash128_partitioned_compact:
+        cross_validate_single::<Minimal, MurmurHash2_128, PartitionedCompact>(10000, 1)
+        if ["minimal", "hash128", "partitioned_compact"];
+    test_cross_validate_single_minimal_hash128_elias_fano:
+        cross_validate_single::<Minimal, MurmurHash2_128, EliasFano>(10000, 1)
+        if ["minimal", "hash128", "elias_fano"];
+    test_cross_validate_single_nonminimal_hash64_dictionary_dictionary:
+        cross_validate_single::<Nonminimal, MurmurHash2_64, DictionaryDictionary>(10000, 1)
+        if ["nonminimal", "hash64", "dictionary_dictionary"];
+    test_cross_validate_single_nonminimal_hash64_partitioned_compact:
+        cross_validate_single::<Nonminimal, MurmurHash2_64, PartitionedCompact>(10000, 1)
+        if ["nonminimal", "hash64", "partitioned_compact"];
+    test_cross_validate_single_nonminimal_hash64_elias_fano:
+        cross_validate_single::<Nonminimal, MurmurHash2_64, EliasFano>(10000, 1)
+        if ["nonminimal", "hash64", "elias_fano"];
+    test_cross_validate_single_nonminimal_hash128_dictionary_dictionary:
+        cross_validate_single::<Nonminimal, MurmurHash2_128, DictionaryDictionary>(10000, 1)
+        if ["nonminimal", "hash128", "dictionary_dictionary"];
+    test_cross_validate_single_nonminimal_hash128_partitioned_compact:
+        cross_validate_single::<Nonminimal, MurmurHash2_128, PartitionedCompact>(10000, 1)
+        if ["nonminimal", "hash128", "partitioned_compact"];
+    test_cross_validate_single_nonminimal_hash128_elias_fano:
+        cross_validate_single::<Nonminimal, MurmurHash2_128, EliasFano>(10000, 1)
+        if ["nonminimal", "hash128", "elias_fano"];
+    test_cross_validate_32partitions_minimal_hash64_dictionary_dictionary:
+        cross_validate_partitioned::<Minimal, MurmurHash2_64, DictionaryDictionary>(200000, 32)
+        if ["minimal", "hash64", "dictionary_dictionary"];
+    test_cross_validate_32partitions_minimal_hash64_partitioned_compact:
+        cross_validate_partitioned::<Minimal, MurmurHash2_64, PartitionedCompact>(200000, 32)
+        if ["minimal", "hash64", "partitioned_compact"];
+    test_cross_validate_32partitions_minimal_hash64_elias_fano:
+        cross_validate_partitioned::<Minimal, MurmurHash2_64, EliasFano>(200000, 32)
+        if ["minimal", "hash64", "elias_fano"];
+    test_cross_validate_32partitions_minimal_hash128_dictionary_dictionary:
+        cross_validate_partitioned::<Minimal, MurmurHash2_128, DictionaryDictionary>(200000, 32)
+        if ["minimal", "hash128", "dictionary_dictionary"];
+    test_cross_validate_32partitions_minimal_hash128_partitioned_compact:
+        cross_validate_partitioned::<Minimal, MurmurHash2_128, PartitionedCompact>(200000, 32)
+        if ["minimal", "hash128", "partitioned_compact"];
+    test_cross_validate_32partitions_minimal_hash128_elias_fano:
+        cross_validate_partitioned::<Minimal, MurmurHash2_128, EliasFano>(200000, 32)
+        if ["minimal", "hash128", "elias_fano"];
+    test_cross_validate_32partitions_nonminimal_hash64_dictionary_dictionary:
+        cross_validate_partitioned::<Nonminimal, MurmurHash2_64, DictionaryDictionary>(200000, 32)
+        if ["nonminimal", "hash64", "dictionary_dictionary"];
+    test_cross_validate_32partitions_nonminimal_hash64_partitioned_compact:
+        cross_validate_partitioned::<Nonminimal, MurmurHash2_64, PartitionedCompact>(200000, 32)
+        if ["nonminimal", "hash64", "partitioned_compact"];
+    test_cross_validate_32partitions_nonminimal_hash64_elias_fano:
+        cross_validate_partitioned::<Nonminimal, MurmurHash2_64, EliasFano>(200000, 32)
+        if ["nonminimal", "hash64", "elias_fano"];
+    test_cross_validate_32partitions_nonminimal_hash128_dictionary_dictionary:
+        cross_validate_partitioned::<Nonminimal, MurmurHash2_128, DictionaryDictionary>(200000, 32)
+        if ["nonminimal", "hash128", "dictionary_dictionary"];
+    test_cross_validate_32partitions_nonminimal_hash128_partitioned_compact:
+        cross_validate_partitioned::<Nonminimal, MurmurHash2_128, PartitionedCompact>(200000, 32)
+        if ["nonminimal", "hash128", "partitioned_compact"];
+    test_cross_validate_32partitions_nonminimal_hash128_elias_fano:
+        cross_validate_partitioned::<Nonminimal, MurmurHash2_128, EliasFano>(200000, 32)
+        if ["nonminimal", "hash128", "elias_fano"];
+}
@@ -0,0 +1,89 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(all(
+    feature = "build",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+
+use anyhow::{Context, Result};
+
+use pthash::map::*;
+use pthash::*;
+
+type F = SinglePhf<Minimal, MurmurHash2_64, DictionaryDictionary>;
+
+#[test]
+fn test_map_get() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let pairs: Vec<(u64, u32)> = (0..1000).map(|i| (i * 7, i as u32)).collect();
+    let map = PhfMap::<u64, u32, F>::build(F::new(), pairs.clone(), &config)
+        .context("Failed to build")?;
+
+    assert_eq!(map.len(), pairs.len());
+    for (key, value) in &pairs {
+        assert_eq!(map.get(key), value);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_map_save_load() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let pairs: Vec<(u64, [u8; 3])> = (0..1000u64)
+        .map(|i| (i, [i as u8, (i >> 8) as u8, 42]))
+        .collect();
+    let mut map = PhfMap::<u64, [u8; 3], F>::build(F::new(), pairs.clone(), &config)
+        .context("Failed to build")?;
+
+    let path = temp_dir.path().join("map.bin");
+    let num_bytes = map.save(&path).context("Could not save")?;
+    assert_eq!(num_bytes as u64, std::fs::metadata(&path)?.len());
+
+    let loaded = PhfMap::<u64, [u8; 3], F>::load(&path).context("Could not load")?;
+    assert_eq!(loaded.values(), map.values());
+    for (key, value) in &pairs {
+        assert_eq!(loaded.get(key), value);
+    }
+
+    // Values of a different size do not match the file
+    assert!(matches!(
+        PhfMap::<u64, u64, F>::load(&path),
+        Err(PhfMapError::Invalid(_))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_map_duplicate_keys() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let mut pairs: Vec<(u64, u32)> = (0..1000).map(|i| (i, i as u32)).collect();
+    pairs.push((42, 0));
+
+    // Even when deduplicating, there is no way to tell which value to keep
+    for deduplicate in [false, true] {
+        config.deduplicate = deduplicate;
+        match PhfMap::<u64, u32, F>::build(F::new(), pairs.clone(), &config) {
+            Err(BuildError::DuplicateKey { key }) => assert_eq!(key, 42u64.as_bytes()),
+            Err(e) => panic!("Unexpected error: {e}"),
+            Ok(_) => panic!("Built map with duplicate keys"),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,323 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(all(
+    feature = "build",
+    feature = "minimal",
+    feature = "nonminimal",
+    feature = "hash128",
+    feature = "dictionary_dictionary",
+    feature = "elias_fano"
+))]
+
+use anyhow::{Context, Result};
+
+use pthash::dynamic::*;
+use pthash::metadata::*;
+use pthash::*;
+
+#[test]
+fn test_metadata_roundtrip() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.num_partitions = 2;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let path = temp_dir.path().join("f.bin");
+
+    let mut f = PartitionedPhf::<Nonminimal, MurmurHash2_128, EliasFano>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    f.save(&path).context("Could not save")?;
+
+    let metadata = PhfMetadata::read(&path).context("Could not read metadata")?;
+    assert_eq!(
+        metadata,
+        PhfMetadata {
+            partitioned: true,
+            minimal: false,
+            hash_bits: 128,
+            hasher: "murmurhash2_128".to_owned(),
+            encoder: "elias_fano".to_owned(),
+        }
+    );
+    assert_eq!(
+        metadata,
+        PartitionedPhf::<Nonminimal, MurmurHash2_128, EliasFano>::metadata()
+    );
+
+    Ok(())
+}
+
+#[test]
+fn test_load_any() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let path = temp_dir.path().join("f.bin");
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    f.save(&path).context("Could not save")?;
+
+    let loaded = load_any(&path).context("Could not load")?;
+    assert!(matches!(loaded, AnyPhf::MinimalSingle64(_)));
+    assert!(loaded.is_minimal());
+    assert_eq!(loaded.num_keys(), f.num_keys());
+    for key in &keys {
+        assert_eq!(loaded.hash(key), f.hash(key));
+    }
+
+    let loaded = loaded
+        .downcast::<SinglePhf<Minimal, MurmurHash2_64, DictionaryDictionary>>()
+        .context("Could not downcast")?;
+    for key in &keys {
+        assert_eq!(loaded.hash(key), f.hash(key));
+    }
+
+    Ok(())
+}
+
+/// Hasher unknown to [`load_any`]
+struct OtherHasher;
+
+impl pthash::Hasher for OtherHasher {
+    type Hash = hashing::hash64;
+    const NAME: &'static str = "other";
+
+    fn hash(val: impl Hashable, seed: u64) -> Self::Hash {
+        MurmurHash2_64::hash(val, !seed)
+    }
+}
+
+#[test]
+fn test_load_any_other_hasher() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let path = temp_dir.path().join("f.bin");
+
+    let mut f = SinglePhf::<Minimal, OtherHasher, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    f.save(&path).context("Could not save")?;
+
+    match load_any(&path) {
+        Err(LoadError::Unsupported(found)) => assert_eq!(found.hasher, "other"),
+        Err(e) => panic!("Unexpected error: {e}"),
+        Ok(_) => panic!("Loaded function with an unsupported hasher"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_load_any_type_mismatch() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let path = temp_dir.path().join("f.bin");
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    f.save(&path).context("Could not save")?;
+
+    let res = load_any(&path)
+        .context("Could not load")?
+        .downcast::<SinglePhf<Minimal, MurmurHash2_64, EliasFano>>();
+    match res {
+        Err(LoadError::TypeMismatch { found, .. }) => {
+            assert_eq!(
+                found,
+                SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::metadata()
+            )
+        }
+        Err(e) => panic!("Unexpected error: {e}"),
+        Ok(_) => panic!("Downcast to the wrong type succeeded"),
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_missing_metadata() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let path = temp_dir.path().join("f.bin");
+    std::fs::write(&path, b"not a pthash file").context("Could not write file")?;
+
+    assert!(matches!(
+        PhfMetadata::read(&path),
+        Err(MetadataError::Missing)
+    ));
+    assert!(matches!(
+        load_any(&path),
+        Err(LoadError::Metadata(MetadataError::Missing))
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn test_load_checks_type() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let path = temp_dir.path().join("f.bin");
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    f.save(&path).context("Could not save")?;
+
+    SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::load(&path)
+        .context("Could not load")?;
+    assert!(matches!(
+        SinglePhf::<Nonminimal, MurmurHash2_64, DictionaryDictionary>::load(&path),
+        Err(LoadError::TypeMismatch { .. })
+    ));
+    assert!(matches!(
+        SinglePhf::<Minimal, MurmurHash2_128, DictionaryDictionary>::load(&path),
+        Err(LoadError::TypeMismatch { .. })
+    ));
+    assert!(matches!(
+        PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::load(&path),
+        Err(LoadError::TypeMismatch { .. })
+    ));
+
+    // Same checks when loading from a reader
+    let mut buf = Vec::new();
+    f.save_to(&mut buf).context("Could not save")?;
+    SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::load_from(&buf[..])
+        .context("Could not load")?;
+    let err = SinglePhf::<Nonminimal, MurmurHash2_64, DictionaryDictionary>::load_from(&buf[..])
+        .err()
+        .context("Loaded function of the wrong type")?;
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    Ok(())
+}
+
+#[test]
+fn test_load_raw() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let path = temp_dir.path().join("f.bin");
+
+    // Same format as files written by PTHash
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    std::fs::write(&path, f.to_bytes()?).context("Could not write file")?;
+
+    assert!(matches!(
+        SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::load(&path),
+        Err(LoadError::Metadata(MetadataError::Missing))
+    ));
+    let loaded = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::load_raw(&path)
+        .context("Could not load")?;
+    for key in &keys {
+        assert_eq!(loaded.hash(key), f.hash(key));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_any_phf_equivalence() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.seed = 42;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let path = temp_dir.path().join("f.bin");
+    let other_path = temp_dir.path().join("g.bin");
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    f.save(&path).context("Could not save")?;
+    let mut g = SinglePhf::<Nonminimal, MurmurHash2_64, DictionaryDictionary>::new();
+    g.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    g.save(&other_path).context("Could not save")?;
+
+    let loaded = load_any(&path).context("Could not load")?;
+    let reloaded = load_any(&path).context("Could not load")?;
+    assert!(loaded.is_compatible(&reloaded));
+    assert!(loaded.is_equivalent(&reloaded));
+
+    let other = load_any(&other_path).context("Could not load")?;
+    assert!(!loaded.is_compatible(&other));
+    assert!(!loaded.is_equivalent(&other));
+
+    Ok(())
+}
+
+#[test]
+fn test_load_corrupted() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let path = temp_dir.path().join("f.bin");
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    let bytes = f.to_bytes()?;
+    for len in 0..bytes.len() {
+        assert!(
+            SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::from_bytes(&bytes[..len])
+                .is_err(),
+            "Loaded function from {len} out of {} bytes",
+            bytes.len()
+        );
+    }
+    std::fs::write(&path, &bytes[..bytes.len() / 2]).context("Could not write file")?;
+    assert!(SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::load_raw(&path).is_err());
+
+    // Table size (after the seed and number of keys) lower than the number of keys
+    let mut corrupted = bytes.clone();
+    corrupted[16..24].copy_from_slice(&1u64.to_le_bytes());
+    assert!(
+        SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::from_bytes(&corrupted).is_err()
+    );
+    std::fs::write(&path, &corrupted).context("Could not write file")?;
+    assert!(SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::load_raw(&path).is_err());
+
+    config.num_partitions = 4;
+    let mut f = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    let bytes = f.to_bytes()?;
+    for len in 0..bytes.len() {
+        assert!(
+            PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::from_bytes(
+                &bytes[..len]
+            )
+            .is_err(),
+            "Loaded function from {len} out of {} bytes",
+            bytes.len()
+        );
+    }
+
+    Ok(())
+}
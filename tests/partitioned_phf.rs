@@ -6,6 +6,8 @@
 //! Tests building a [`PartitionedPhf`] with every possible type parameter, then
 //! hashing a few keys.
 
+#![cfg(feature = "build")]
+
 use anyhow::{Context, Result};
 
 use pthash::*;
@@ -20,6 +22,7 @@ fn test_partitioned<M: Minimality, H: Hasher, E: Encoder>() -> Result<()> {
     let check = |f: PartitionedPhf<M, H, E>| -> Result<()> {
         // Hashes are unique
         let mut hashes: Vec<u64> = keys.iter().map(|key| f.hash(key)).collect();
+        assert_eq!(f.hash_many(&keys), hashes);
         hashes.sort();
         assert_eq!(hashes, vec![0, 1, 2]);
 
@@ -148,3 +151,269 @@ fn test_partitioned_nonminimal_hash128_partitioned_compact() -> Result<()> {
 fn test_partitioned_nonminimal_hash128_elias_fano() -> Result<()> {
     test_partitioned::<Nonminimal, MurmurHash2_128, EliasFano>()
 }
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_partitioned_small_sets() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.num_partitions = 8;
+
+    for num_keys in [1u64, 2, 10, 100] {
+        let keys: Vec<u64> = (0..num_keys).collect();
+        let mut f = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+        f.build_in_internal_memory_from_bytes(|| &keys, &config)
+            .with_context(|| format!("Failed to build with {num_keys} keys"))?;
+        let mut hashes: Vec<u64> = keys.iter().map(|key| f.hash(key)).collect();
+        hashes.sort();
+        assert_eq!(hashes, keys);
+    }
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_partitioned_check_collisions() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.check_collisions = true;
+
+    let keys: Vec<u64> = (0..10000).collect();
+    let mut f = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    let timings = f
+        .build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    assert_eq!(timings.attempts.len(), 1);
+
+    // Duplicate keys are indistinguishable from colliding keys
+    let keys: Vec<u64> = (0..10000).chain([42]).collect();
+    config.seed = 42;
+    config.check_duplicates = false;
+    let mut f = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    match f.build_in_internal_memory_from_bytes(|| &keys, &config) {
+        Err(BuildError::SeedsExhausted { attempts }) => assert_eq!(attempts.len(), 1),
+        Err(e) => panic!("Unexpected error: {e}"),
+        Ok(_) => panic!("Build with duplicate keys succeeded"),
+    }
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_partitioned_concurrent_builds_tmp_dir() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().join("shared"));
+    config.verbose_output = false;
+    config.num_partitions = 4;
+
+    std::thread::scope(|scope| -> Result<()> {
+        let threads: Vec<_> = (0..4u64)
+            .map(|i| {
+                let config = &config;
+                scope.spawn(move || -> Result<()> {
+                    let keys: Vec<u64> = (i * 10000..(i + 1) * 10000).collect();
+                    let mut f =
+                        PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+                    f.build_in_internal_memory_from_bytes(|| &keys, config)
+                        .context("Failed to build")?;
+                    Ok(())
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().expect("Build thread panicked")?;
+        }
+        Ok(())
+    })?;
+
+    // Internal-memory builds write no intermediate files
+    assert!(!temp_dir.path().join("shared").exists());
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_partitioned_raw() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let mut f = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    let hashes: Vec<u64> = keys.iter().map(|key| f.hash(key)).collect();
+
+    let raw = f.into_raw();
+    assert!(!raw.is_null());
+    let f =
+        unsafe { PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::from_raw(raw) };
+    assert_eq!(f.as_raw(), raw as *const _);
+    assert!(f.build_configuration().is_none());
+    assert_eq!(
+        keys.iter().map(|key| f.hash(key)).collect::<Vec<_>>(),
+        hashes
+    );
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_partitioned_bytes() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.num_partitions = 4;
+
+    let keys: Vec<u64> = (0..10000).collect();
+    let mut f = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+
+    let bytes = f.to_bytes().context("Could not serialize")?;
+    let path = temp_dir.path().join("f.bin");
+    f.save(&path).context("Could not save")?;
+    let file = std::fs::read(&path).context("Could not read file")?;
+    assert!(file.starts_with(&bytes), "to_bytes() differs from save()");
+
+    let f2 = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::from_bytes(&file)
+        .context("Could not deserialize")?;
+    for key in &keys {
+        assert_eq!(f2.hash(key), f.hash(key));
+    }
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "rayon",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_partitioned_build_in_pool() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.num_partitions = 4;
+    config.num_threads = 16;
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .context("Could not build thread pool")?;
+    let keys: Vec<u64> = (0..10000).collect();
+    let mut f = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.par_build_in_internal_memory_from_bytes_in_pool(&pool, || &keys, &config)
+        .context("Failed to build")?;
+
+    // PTHash does not use more threads than the pool
+    assert_eq!(f.build_configuration().unwrap().num_threads, 2);
+    let mut hashes: Vec<u64> = keys.iter().map(|key| f.hash(key)).collect();
+    hashes.sort();
+    assert_eq!(hashes, keys);
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_partitioned_num_bits_breakdown() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.num_partitions = 4;
+
+    let keys: Vec<u64> = (0..100_000).collect();
+    let mut f = PartitionedPhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+
+    let breakdown = f.num_bits_breakdown();
+    assert!(breakdown.overhead_bits > 0);
+    assert!(breakdown.bucketer_bits > 0);
+    assert!(breakdown.pilots_bits > breakdown.overhead_bits);
+    assert!(breakdown.free_slots_bits > 0);
+    // Serialized sizes include the lengths of vectors, which num_bits does not count
+    let num_bits = f.num_bits() as u64;
+    assert!(breakdown.total_bits() >= num_bits);
+    assert!(breakdown.total_bits() < num_bits + num_bits / 10);
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "nonminimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_partitioned_partition_of() -> Result<()> {
+    fn check<M: Minimality>(keys: &[u64], config: &BuildConfiguration) -> Result<()> {
+        let mut f = PartitionedPhf::<M, MurmurHash2_64, DictionaryDictionary>::new();
+        f.build_in_internal_memory_from_bytes(|| keys, config)
+            .context("Failed to build")?;
+        assert_eq!(f.num_partitions(), config.num_partitions);
+
+        let mut keys_per_partition = vec![0; config.num_partitions as usize];
+        for key in keys {
+            let partition = f.partition_of(key);
+            assert_eq!(partition, f.partition_of_position(f.hash(key)));
+            keys_per_partition[partition as usize] += 1;
+        }
+        assert!(keys_per_partition.iter().all(|&num_keys| num_keys > 0));
+
+        // Partitions are found again after reloading
+        let f =
+            PartitionedPhf::<M, MurmurHash2_64, DictionaryDictionary>::from_bytes(&f.to_bytes()?)
+                .context("Could not deserialize")?;
+        assert_eq!(f.num_partitions(), config.num_partitions);
+        for key in keys.iter().take(100) {
+            assert!(f.partition_of(key) < config.num_partitions);
+        }
+        Ok(())
+    }
+
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.num_partitions = 8;
+
+    let keys: Vec<u64> = (0..100_000).collect();
+    check::<Minimal>(&keys, &config)?;
+    check::<Nonminimal>(&keys, &config)?;
+
+    Ok(())
+}
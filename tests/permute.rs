@@ -0,0 +1,37 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(all(
+    feature = "build",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+
+use anyhow::{Context, Result};
+
+use pthash::*;
+
+#[test]
+fn test_permute_by_phf() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<u64> = (0..10000).map(|i| i * 7 + 3).collect();
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+
+    let mut values: Vec<String> = keys.iter().map(|key| format!("value{key}")).collect();
+    permute_by_phf(&f, &keys, &mut values);
+
+    for key in &keys {
+        assert_eq!(values[f.hash(key) as usize], format!("value{key}"));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(feature = "python")]
+
+use anyhow::Result;
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+
+use pthash::python::PyPhf;
+
+#[test]
+fn test_python() -> Result<()> {
+    let temp_dir = tempfile::tempdir()?;
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| -> PyResult<()> {
+        let globals = PyDict::new(py);
+        globals.set_item("Phf", py.get_type::<PyPhf>())?;
+        globals.set_item("path", temp_dir.path().join("f.bin"))?;
+        py.run(
+            c"
+import numpy
+
+keys = [b'key%d' % i for i in range(1000)]
+f = Phf.build(keys)
+assert len(f) == 1000 and f.minimal
+assert sorted(f.hash(key) for key in keys) == list(range(1000))
+assert list(f.hash_many(keys)) == [f.hash(key) for key in keys]
+
+ints = numpy.arange(1000, dtype=numpy.uint64)
+g = Phf.build([int(i).to_bytes(8, 'little') for i in ints], minimal=False)
+assert g.table_size >= 1000 and not g.minimal
+assert len(set(g.hash_u64_array(ints))) == 1000
+
+f.save(path)
+loaded = Phf.load(path)
+assert [loaded.hash(key) for key in keys] == [f.hash(key) for key in keys]
+
+try:
+    Phf.build(keys, encoder='not_an_encoder')
+except ValueError:
+    pass
+else:
+    raise AssertionError('unknown encoder was accepted')
+",
+            Some(&globals),
+            None,
+        )
+    })?;
+    Ok(())
+}
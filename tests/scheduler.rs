@@ -0,0 +1,102 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests building many functions with a [`BuildScheduler`]
+
+#![cfg(all(
+    feature = "build",
+    feature = "rayon",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+
+use pthash::scheduler::*;
+use pthash::*;
+
+type F = SinglePhf<Minimal, MurmurHash2_64, DictionaryDictionary>;
+
+#[test]
+fn test_scheduler() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let shards: Vec<Vec<u64>> = (0..8u64)
+        .map(|shard| (shard * 1000..(shard + 1) * 1000).collect())
+        .collect();
+
+    // Only enough memory for about two builds at once
+    let scheduler = BuildScheduler::new(2 * estimate_build_ram::<MurmurHash2_64>(1000), 4);
+    let functions = scheduler.run(
+        shards.iter().collect(),
+        |keys| estimate_build_ram::<MurmurHash2_64>(keys.len() as u64),
+        |keys, num_threads| {
+            let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+            config.verbose_output = false;
+            config.num_threads = num_threads;
+            let mut f = F::new();
+            f.build_in_internal_memory_from_bytes(|| keys, &config)
+                .map(|_| f)
+        },
+    );
+
+    for (keys, f) in shards.iter().zip(functions) {
+        let f = f.context("Failed to build")?;
+        let mut hashes: Vec<u64> = keys.iter().map(|&key| f.hash(key)).collect();
+        hashes.sort();
+        assert_eq!(hashes, Vec::from_iter(0..1000));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_scheduler_over_budget() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let shards: Vec<Vec<u64>> = (0..6u64)
+        .map(|shard| (shard * 1000..(shard + 1) * 1000).collect())
+        .collect();
+    let estimate = |keys: &&Vec<u64>| estimate_build_ram::<MurmurHash2_64>(keys.len() as u64);
+
+    // The sum of estimates is three times the budget, and the pool has a single
+    // thread, which builds hash keys on while other jobs wait for memory
+    let budget = 2 * estimate_build_ram::<MurmurHash2_64>(1000);
+    let scheduler = BuildScheduler::new(budget, 4);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .context("Could not build thread pool")?;
+    let in_use = AtomicU64::new(0);
+    let peak = AtomicU64::new(0);
+    let functions = pool.install(|| {
+        scheduler.run(shards.iter().collect(), estimate, |keys, num_threads| {
+            let ram = estimate(&keys);
+            let used = in_use.fetch_add(ram, Ordering::SeqCst) + ram;
+            peak.fetch_max(used, Ordering::SeqCst);
+            let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+            config.verbose_output = false;
+            config.num_threads = num_threads;
+            let mut f = F::new();
+            let result = f
+                .par_build_in_internal_memory_from_bytes(|| keys.par_iter(), &config)
+                .map(|_| f);
+            in_use.fetch_sub(ram, Ordering::SeqCst);
+            result
+        })
+    });
+
+    assert!(peak.load(Ordering::SeqCst) <= budget);
+    for (keys, f) in shards.iter().zip(functions) {
+        let f = f.context("Failed to build")?;
+        let mut hashes: Vec<u64> = keys.iter().map(|&key| f.hash(key)).collect();
+        hashes.sort();
+        assert_eq!(hashes, Vec::from_iter(0..1000));
+    }
+
+    Ok(())
+}
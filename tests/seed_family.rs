@@ -0,0 +1,28 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use std::collections::HashSet;
+
+use pthash::*;
+
+#[test]
+fn test_seed_family() {
+    let family = SeedFamily::new(42);
+    let seeds: Vec<u64> = family.seeds().take(1000).collect();
+
+    // Deterministic
+    assert_eq!(
+        seeds,
+        SeedFamily::new(42).seeds().take(1000).collect::<Vec<_>>()
+    );
+    assert_eq!(family.seed(123), seeds[123]);
+
+    // Distinct across indices and master seeds
+    assert_eq!(seeds.iter().collect::<HashSet<_>>().len(), 1000);
+    assert_ne!(SeedFamily::new(43).seed(0), seeds[0]);
+
+    // Never PTHash's invalid seed
+    assert!(!seeds.contains(&u64::MAX));
+}
@@ -0,0 +1,49 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(all(
+    feature = "toml",
+    feature = "build",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+
+use pthash::*;
+
+#[derive(Serialize, Deserialize)]
+struct IndexHeader {
+    name: String,
+    phf: PartitionedPhf<Minimal, MurmurHash2_64, DictionaryDictionary>,
+}
+
+#[test]
+fn test_serde_roundtrip() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.num_partitions = 2;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let mut phf = PartitionedPhf::new();
+    phf.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    let header = IndexHeader {
+        name: "test".to_owned(),
+        phf,
+    };
+
+    let serialized = toml::to_string(&header).context("Could not serialize")?;
+    let deserialized: IndexHeader = toml::from_str(&serialized).context("Could not deserialize")?;
+    assert_eq!(deserialized.name, "test");
+    for key in &keys {
+        assert_eq!(deserialized.phf.hash(key), header.phf.hash(key));
+    }
+
+    Ok(())
+}
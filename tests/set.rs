@@ -0,0 +1,85 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(all(
+    feature = "build",
+    feature = "minimal",
+    feature = "nonminimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+
+use anyhow::{Context, Result};
+
+use pthash::set::*;
+use pthash::*;
+
+type F = SinglePhf<Minimal, MurmurHash2_64, DictionaryDictionary>;
+
+#[test]
+fn test_set_contains() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys: Vec<u64> = (0..10000).collect();
+    let set =
+        PhfSet::<u64, F>::build(F::new(), || &keys, 16, &config).context("Failed to build")?;
+
+    assert_eq!(set.len(), keys.len());
+    for key in &keys {
+        assert_eq!(set.hash_checked(key), Some(set.phf().hash(key)));
+        assert!(set.contains(key));
+    }
+
+    // With 16-bit fingerprints, about 10000 * 2^-16 ≈ 0.15 false positives are expected
+    let false_positives = (10000..20000u64).filter(|key| set.contains(key)).count();
+    assert!(false_positives <= 5, "{false_positives} false positives");
+
+    Ok(())
+}
+
+#[test]
+fn test_set_save_load() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.alpha = 0.9;
+
+    type F = SinglePhf<Nonminimal, MurmurHash2_64, DictionaryDictionary>;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    // 13 bits, so fingerprints span two words
+    let mut set =
+        PhfSet::<u64, F>::build(F::new(), || &keys, 13, &config).context("Failed to build")?;
+
+    let path = temp_dir.path().join("set.bin");
+    let num_bytes = set.save(&path).context("Could not save")?;
+    assert_eq!(num_bytes as u64, std::fs::metadata(&path)?.len());
+
+    let loaded = PhfSet::<u64, F>::load(&path).context("Could not load")?;
+    assert_eq!(loaded.fingerprint_bits(), 13);
+    for key in &keys {
+        assert_eq!(loaded.hash_checked(key), set.hash_checked(key));
+        assert!(loaded.contains(key));
+    }
+    for key in 1000..2000u64 {
+        assert_eq!(loaded.contains(&key), set.contains(&key));
+    }
+
+    // Sizes in the trailer (number of words, then offset of the fingerprints) are
+    // checked instead of overflowing
+    let bytes = std::fs::read(&path).context("Could not read file")?;
+    let trailer_start = bytes.len() - 32;
+    for field in [1, 2] {
+        let mut corrupted = bytes.clone();
+        corrupted[trailer_start + field * 8..trailer_start + (field + 1) * 8]
+            .copy_from_slice(&u64::MAX.to_le_bytes());
+        std::fs::write(&path, &corrupted).context("Could not write file")?;
+        assert!(PhfSet::<u64, F>::load(&path).is_err());
+    }
+
+    Ok(())
+}
@@ -6,20 +6,29 @@
 //! Tests building a [`SinglePhf`] with every possible type parameter, then
 //! hashing a few keys.
 
+#![cfg(feature = "build")]
+
 use anyhow::{Context, Result};
 
 use pthash::*;
 
-fn test_single<M: Minimality, H: Hasher, E: Encoder>() -> Result<()> {
+/// Returns a configuration without verbose output, and the directory it uses
+fn quiet_config() -> Result<(tempfile::TempDir, BuildConfiguration)> {
     let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
     let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
     config.verbose_output = false;
+    Ok((temp_dir, config))
+}
+
+fn test_single<M: Minimality, H: Hasher, E: Encoder>() -> Result<()> {
+    let (_temp_dir, config) = quiet_config()?;
 
     let keys: Vec<&[u8]> = vec!["abc".as_bytes(), "def".as_bytes(), "ghikl".as_bytes()];
 
     let check = |f: SinglePhf<M, H, E>| -> Result<()> {
         // Hashes are unique
         let mut hashes: Vec<u64> = keys.iter().map(|key| f.hash(key)).collect();
+        assert_eq!(f.hash_many(&keys), hashes);
         hashes.sort();
         assert_eq!(hashes, vec![0, 1, 2]);
 
@@ -148,3 +157,850 @@ fn test_single_nonminimal_hash128_partitioned_compact() -> Result<()> {
 fn test_single_nonminimal_hash128_elias_fano() -> Result<()> {
     test_single::<Nonminimal, MurmurHash2_128, EliasFano>()
 }
+
+#[cfg(all(feature = "minimal", feature = "hash64", feature = "compact"))]
+#[test]
+fn test_single_minimal_hash64_compact() -> Result<()> {
+    test_single::<Minimal, MurmurHash2_64, Compact>()
+}
+
+#[cfg(all(feature = "nonminimal", feature = "hash128", feature = "dictionary"))]
+#[test]
+fn test_single_nonminimal_hash128_dictionary() -> Result<()> {
+    test_single::<Nonminimal, MurmurHash2_128, Dictionary>()
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_build_configuration() -> Result<()> {
+    let (_temp_dir, config) = quiet_config()?;
+
+    let keys: Vec<u64> = (0..1000).collect();
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    assert_eq!(f.build_configuration(), None);
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+
+    let resolved = f.build_configuration().unwrap().clone();
+    assert_ne!(resolved.seed, config.seed);
+    assert_eq!(resolved.num_buckets, tuning::num_buckets(1000, config.c));
+
+    // Building again with the resolved configuration gives the same function
+    let mut f2 = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f2.build_in_internal_memory_from_bytes(|| &keys, &resolved)
+        .context("Failed to build")?;
+    for key in &keys {
+        assert_eq!(f.hash(key), f2.hash(key));
+    }
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "nonminimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_table_size() -> Result<()> {
+    let (_temp_dir, mut config) = quiet_config()?;
+    config.table_size = Some(1500);
+
+    let keys: Vec<u64> = (0..1000).collect();
+
+    let mut f = SinglePhf::<Nonminimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    assert_eq!(f.table_size(), 1500);
+    for key in &keys {
+        assert!(f.hash(key) < 1500);
+    }
+
+    config.table_size = Some(999);
+    let mut f = SinglePhf::<Nonminimal, MurmurHash2_64, DictionaryDictionary>::new();
+    assert!(matches!(
+        f.build_in_internal_memory_from_bytes(|| &keys, &config),
+        Err(BuildError::TableSizeTooSmall {
+            table_size: 999,
+            num_keys: 1000
+        })
+    ));
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_query_through_pointers() -> Result<()> {
+    use std::sync::Arc;
+
+    fn hashes<F: PhfQuery>(f: F, keys: &[u64]) -> Vec<u64> {
+        keys.iter().map(|key| f.hash(key)).collect()
+    }
+
+    let (_temp_dir, config) = quiet_config()?;
+
+    let keys: Vec<u64> = (0..1000).collect();
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+
+    let expected = hashes(&f, &keys);
+    let f = Arc::new(f);
+    assert_eq!(hashes(Arc::clone(&f), &keys), expected);
+    assert_eq!(hashes(&*f, &keys), expected);
+    assert!(<Arc<SinglePhf<Minimal, MurmurHash2_64, DictionaryDictionary>>>::MINIMAL);
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "bytes",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_bytes_keys() -> Result<()> {
+    let (_temp_dir, config) = quiet_config()?;
+
+    let keys: Vec<bytes::Bytes> = ["abc", "def", "ghikl"]
+        .into_iter()
+        .map(|key| bytes::Bytes::from_static(key.as_bytes()))
+        .collect();
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+
+    for key in &keys {
+        assert_eq!(f.hash(key), f.hash(&key[..]));
+        assert_eq!(f.hash(key), f.hash(bytes::BytesMut::from(&key[..])));
+    }
+
+    Ok(())
+}
+
+#[cfg(all(feature = "minimal", feature = "dictionary_dictionary"))]
+fn test_single_integer_keys<H: Hasher>() -> Result<()> {
+    let (_temp_dir, config) = quiet_config()?;
+
+    let keys: Vec<u64> = (0..10000u64)
+        .map(|i| i.wrapping_mul(0x9E3779B97F4A7C15))
+        .collect();
+
+    let mut f = SinglePhf::<Minimal, H, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+
+    // Integers are hashed without going through the byte slice, but must get the
+    // same hashes
+    let mut hashes = Vec::new();
+    for key in &keys {
+        let bytes = key.to_ne_bytes();
+        assert_eq!(f.hash(key), f.hash(&bytes[..]));
+        hashes.push(f.hash(&bytes[..]));
+    }
+    hashes.sort();
+    assert_eq!(hashes, (0..10000).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_integer_keys_hash64() -> Result<()> {
+    test_single_integer_keys::<MurmurHash2_64>()
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash128",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_integer_keys_hash128() -> Result<()> {
+    test_single_integer_keys::<MurmurHash2_128>()
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_deduplicate() -> Result<()> {
+    let (_temp_dir, mut config) = quiet_config()?;
+    config.deduplicate = true;
+
+    let keys: Vec<u64> = (0..1000).chain(0..100).collect();
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    let timings = f
+        .build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    assert_eq!(timings.num_duplicate_keys, 100);
+    assert_eq!(f.num_keys(), 1000);
+
+    let mut hashes: Vec<u64> = (0..1000u64).map(|key| f.hash(key)).collect();
+    hashes.sort();
+    assert_eq!(hashes, (0..1000).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_clone() -> Result<()> {
+    let (_temp_dir, config) = quiet_config()?;
+
+    let keys: Vec<u64> = (0..1000).collect();
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    let hashes = f.hash_many(&keys);
+
+    let clone = f.clone();
+    assert_ne!(clone.as_raw(), f.as_raw());
+    assert_eq!(clone.seed(), f.seed());
+    assert_eq!(clone.build_configuration(), f.build_configuration());
+    assert_eq!(clone.to_bytes()?, f.to_bytes()?);
+
+    // The clone does not depend on the original
+    drop(f);
+    assert_eq!(clone.hash_many(&keys), hashes);
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_equality() -> Result<()> {
+    let (temp_dir, mut config) = quiet_config()?;
+    config.seed = 42;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let path = temp_dir.path().join("f.bin");
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    f.save(&path).context("Could not save")?;
+    let loaded = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::load(&path)
+        .context("Could not load")?;
+    assert!(loaded.is_compatible(&f));
+    assert_eq!(loaded, f);
+
+    // Same seed, other keys
+    let other_keys: Vec<u64> = (1000..2000).collect();
+    let mut g = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    g.build_in_internal_memory_from_bytes(|| &other_keys, &config)
+        .context("Failed to build")?;
+    assert!(g.is_compatible(&f));
+    assert_ne!(g, f);
+
+    // Same keys, other seed
+    config.seed = 43;
+    let mut g = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    g.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    assert!(!g.is_compatible(&f));
+    assert_ne!(g, f);
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_display() -> Result<()> {
+    let (_temp_dir, mut config) = quiet_config()?;
+    config.seed = 42;
+    assert!(config.to_string().contains("seed=42"));
+
+    let keys: Vec<u64> = (0..1000).collect();
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    let summary = f.to_string();
+    assert!(summary.starts_with(
+        "minimal SinglePhf (64-bit hashes, dictionary_dictionary encoder): 1000 keys, "
+    ));
+    assert!(summary.ends_with(", seed 42"));
+    let debug = format!("{f:?}");
+    assert!(debug.starts_with("SinglePhf { minimal: true, hash_bits: 64, "));
+    assert!(debug.contains("num_keys: 1000"));
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_parameters() -> Result<()> {
+    let (temp_dir, mut config) = quiet_config()?;
+    config.alpha = 0.9;
+    config.c = 6.0;
+
+    let keys: Vec<u64> = (0..10_000).collect();
+    let path = temp_dir.path().join("f.bin");
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    assert!((f.alpha() - 0.9).abs() < 0.001);
+    assert_eq!(f.c(), Some(6.0));
+    f.save(&path).context("Could not save")?;
+
+    let loaded = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::load(&path)
+        .context("Could not load")?;
+    assert_eq!(loaded.seed(), f.seed());
+    assert_eq!(loaded.alpha(), f.alpha());
+    assert_eq!(loaded.c(), None);
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "nonminimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_num_bits_breakdown() -> Result<()> {
+    let (_temp_dir, config) = quiet_config()?;
+
+    let keys: Vec<u64> = (0..10_000).collect();
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    let minimal = f.num_bits_breakdown();
+    assert!(minimal.pilots_bits > minimal.overhead_bits);
+    // Serialized sizes include the lengths of vectors, which num_bits does not count
+    let num_bits = f.num_bits() as u64;
+    assert!(minimal.total_bits() >= num_bits);
+    assert!(minimal.total_bits() < num_bits + num_bits / 10);
+
+    let mut f = SinglePhf::<Nonminimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    let nonminimal = f.num_bits_breakdown();
+    assert!(nonminimal.free_slots_bits < minimal.free_slots_bits);
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_peak_memory() -> Result<()> {
+    let (_temp_dir, config) = quiet_config()?;
+
+    let keys: Vec<u64> = (0..1000).collect();
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    let timings = f
+        .build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    if cfg!(target_os = "linux") {
+        let hashing_peak_memory = timings.hashing_peak_memory.context("No hashing peak")?;
+        let peak_memory = timings.peak_memory.context("No peak")?;
+        assert!(hashing_peak_memory > 0);
+        assert!(peak_memory >= hashing_peak_memory);
+    } else {
+        assert_eq!(timings.hashing_peak_memory, None);
+        assert_eq!(timings.peak_memory, None);
+    }
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_timings() -> Result<()> {
+    let (_temp_dir, config) = quiet_config()?;
+
+    let keys: Vec<u64> = (0..100000).collect();
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    let timings = f
+        .build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    assert!(timings.hashing_seconds > std::time::Duration::ZERO);
+    assert_eq!(
+        timings.total(),
+        timings.hashing_seconds
+            + timings.partitioning_seconds
+            + timings.mapping_ordering_seconds
+            + timings.searching_seconds
+            + timings.encoding_seconds
+    );
+    assert!(timings.total() <= timings.attempts.last().unwrap().duration);
+    let display = timings.to_string();
+    assert!(display.starts_with(&format!("{:.3}s (hashing: ", timings.total().as_secs_f64())));
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_seed_attempts() -> Result<()> {
+    let (_temp_dir, config) = quiet_config()?;
+
+    let keys: Vec<u64> = (0..1000).collect();
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    let timings = f
+        .build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    let last_attempt = timings.attempts.last().expect("No attempt recorded");
+    assert_eq!(last_attempt.error, None);
+    assert_eq!(last_attempt.seed, f.build_configuration().unwrap().seed);
+    assert!(timings.attempts[..timings.attempts.len() - 1]
+        .iter()
+        .all(|attempt| attempt.error.is_some()));
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_memory_usage() -> Result<()> {
+    let (_temp_dir, config) = quiet_config()?;
+
+    let keys: Vec<u64> = (0..100000).collect();
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    let empty_memory_usage = f.memory_usage();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    assert!(f.memory_usage() > empty_memory_usage);
+    assert!(f.memory_usage() >= f.num_bits() / 8);
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "nonminimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_invalid_alpha() -> Result<()> {
+    let (_temp_dir, mut config) = quiet_config()?;
+
+    let keys: Vec<u64> = (0..1000).collect();
+
+    for alpha in [0., 1., 1.5, f64::NAN] {
+        config.alpha = alpha;
+        let mut f = SinglePhf::<Nonminimal, MurmurHash2_64, DictionaryDictionary>::new();
+        let mut num_reads = 0;
+        let read_keys = || {
+            num_reads += 1;
+            &keys
+        };
+        assert!(matches!(
+            f.build_in_internal_memory_from_bytes(read_keys, &config),
+            Err(BuildError::InvalidAlpha { .. })
+        ));
+        // Rejected before hashing keys
+        assert_eq!(num_reads, 0);
+    }
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_raw() -> Result<()> {
+    let (_temp_dir, config) = quiet_config()?;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    let hashes: Vec<u64> = keys.iter().map(|key| f.hash(key)).collect();
+
+    let raw = f.into_raw();
+    assert!(!raw.is_null());
+    let f = unsafe { SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::from_raw(raw) };
+    assert_eq!(f.as_raw(), raw as *const _);
+    assert!(f.build_configuration().is_none());
+    assert_eq!(
+        keys.iter().map(|key| f.hash(key)).collect::<Vec<_>>(),
+        hashes
+    );
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_bytes() -> Result<()> {
+    let (temp_dir, config) = quiet_config()?;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+
+    let bytes = f.to_bytes().context("Could not serialize")?;
+    let path = temp_dir.path().join("f.bin");
+    f.save(&path).context("Could not save")?;
+    let file = std::fs::read(&path).context("Could not read file")?;
+    assert!(file.starts_with(&bytes), "to_bytes() differs from save()");
+
+    let f2 = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::from_bytes(&bytes)
+        .context("Could not deserialize")?;
+    for key in &keys {
+        assert_eq!(f2.hash(key), f.hash(key));
+    }
+
+    assert!(
+        SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::from_bytes(
+            &bytes[..bytes.len() / 2]
+        )
+        .is_err(),
+        "Truncated function was deserialized"
+    );
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_save_to_load_from() -> Result<()> {
+    use std::io::Read;
+
+    let (temp_dir, config) = quiet_config()?;
+
+    let keys: Vec<u64> = (0..1000).collect();
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+
+    // Same bytes as save()
+    let mut buf = Vec::new();
+    let num_bytes = f.save_to(&mut buf).context("Could not save")?;
+    assert_eq!(num_bytes, buf.len());
+    let path = temp_dir.path().join("f.bin");
+    f.save(&path).context("Could not save")?;
+    assert_eq!(buf, std::fs::read(&path).context("Could not read file")?);
+
+    // Followed by other data in the same stream
+    buf.extend(b"trailing data");
+    let mut reader = &buf[..];
+    let f2 = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::load_from(
+        (&mut reader).take(num_bytes as u64),
+    )
+    .context("Could not load")?;
+    assert_eq!(reader, b"trailing data");
+    for key in &keys {
+        assert_eq!(f2.hash(key), f.hash(key));
+    }
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_check_duplicates() -> Result<()> {
+    let (_temp_dir, mut config) = quiet_config()?;
+    config.check_duplicates = true;
+
+    let keys: Vec<&[u8]> = vec![b"abc", b"def", b"ghi", b"def"];
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    match f.build_in_internal_memory_from_bytes(|| &keys, &config) {
+        Err(BuildError::DuplicateKey { key }) => assert_eq!(key, b"def"),
+        Err(e) => panic!("Unexpected error: {e}"),
+        Ok(_) => panic!("Build with duplicate keys succeeded"),
+    }
+
+    // Deduplication takes precedence
+    config.deduplicate = true;
+    let timings = f
+        .build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    assert_eq!(timings.num_duplicate_keys, 1);
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash128",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_hash_many() -> Result<()> {
+    let (_temp_dir, config) = quiet_config()?;
+
+    // More keys than a batch
+    let keys: Vec<u64> = (0..5000).collect();
+    let mut f = SinglePhf::<Minimal, MurmurHash2_128, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+
+    let expected: Vec<u64> = keys.iter().map(|key| f.hash(key)).collect();
+    assert_eq!(f.hash_many(&keys), expected);
+    let mut hashes = vec![0; keys.len()];
+    f.hash_many_into(&keys, &mut hashes);
+    assert_eq!(hashes, expected);
+    assert_eq!(f.hash_many(&keys[..0]), Vec::<u64>::new());
+
+    #[cfg(feature = "rayon")]
+    {
+        assert_eq!(f.par_hash_many(&keys), expected);
+        assert_eq!(f.par_hash_many::<u64>(&[]), Vec::<u64>::new());
+    }
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+#[should_panic(expected = "Fewer keys than positions")]
+fn test_single_hash_many_into_too_few_keys() {
+    let (_temp_dir, config) = quiet_config().unwrap();
+
+    let keys: Vec<u64> = (0..100).collect();
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .unwrap();
+    f.hash_many_into(&keys, &mut [0; 101]);
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_build_from_iter() -> Result<()> {
+    use std::io::BufRead;
+
+    let (_temp_dir, mut config) = quiet_config()?;
+
+    let keys: Vec<Vec<u8>> = (0..1000).map(|i| format!("key{i}").into_bytes()).collect();
+
+    // Neither Clone nor ExactSizeIterator
+    let reader = std::io::Cursor::new(keys.join(&b'\n'));
+    let one_shot_keys = reader.split(b'\n').map(|key| key.unwrap());
+
+    // Options which need to read keys again are rejected
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    config.check_duplicates = false;
+    assert!(matches!(
+        f.build_in_internal_memory_from_iter(keys.iter(), &config),
+        Err(BuildError::KeysReadOnce(_))
+    ));
+    config.seed = 42;
+    config.check_duplicates = true;
+    assert!(matches!(
+        f.build_in_internal_memory_from_iter(keys.iter(), &config),
+        Err(BuildError::KeysReadOnce(_))
+    ));
+    config.check_duplicates = false;
+    config.verify = true;
+    assert!(matches!(
+        f.build_in_internal_memory_from_iter(keys.iter(), &config),
+        Err(BuildError::KeysReadOnce(_))
+    ));
+    config.verify = false;
+
+    let timings = f
+        .build_in_internal_memory_from_iter(one_shot_keys, &config)
+        .context("Failed to build")?;
+    assert_eq!(timings.attempts.len(), 1);
+
+    let mut hashes: Vec<u64> = keys.iter().map(|key| f.hash(key)).collect();
+    hashes.sort();
+    assert_eq!(hashes, (0..1000).collect::<Vec<_>>());
+
+    // Keys can't be hashed again with another seed after a collision
+    config.check_collisions = true;
+    let keys = (0..1000u64).chain([42]);
+    match f.build_in_internal_memory_from_iter(keys, &config) {
+        Err(BuildError::SeedsExhausted { attempts }) => assert_eq!(attempts.len(), 1),
+        Err(e) => panic!("Unexpected error: {e}"),
+        Ok(_) => panic!("Build with duplicate keys succeeded"),
+    }
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_cancellation() -> Result<()> {
+    let (_temp_dir, mut config) = quiet_config()?;
+    let token = CancellationToken::new();
+    config.cancellation = Some(token.clone());
+
+    let keys: Vec<u64> = (0..100_000).collect();
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+
+    token.cancel();
+    assert!(config.cancellation.as_ref().unwrap().is_cancelled());
+    assert!(matches!(
+        f.build_in_internal_memory_from_bytes(|| &keys, &config),
+        Err(BuildError::Cancelled)
+    ));
+    #[cfg(feature = "rayon")]
+    assert!(matches!(
+        f.par_build_in_internal_memory_from_bytes(|| &keys, &config),
+        Err(BuildError::Cancelled)
+    ));
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_ram_budget() -> Result<()> {
+    let (_temp_dir, mut config) = quiet_config()?;
+    config.ram = 1000;
+
+    let keys: Vec<u64> = (0..10_000).collect();
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    match f.build_in_internal_memory_from_bytes(|| &keys, &config) {
+        Err(BuildError::RamBudgetExceeded { ram, estimated, .. }) => {
+            assert_eq!(ram, 1000);
+            assert!(estimated > ram);
+        }
+        Err(e) => panic!("Unexpected error: {e}"),
+        Ok(_) => panic!("Build over the RAM budget succeeded"),
+    }
+    #[cfg(feature = "rayon")]
+    assert!(matches!(
+        f.par_build_in_internal_memory_from_bytes(|| &keys, &config),
+        Err(BuildError::RamBudgetExceeded { .. })
+    ));
+
+    config.ram = pthash::tuning::estimate_build_ram::<MurmurHash2_64>(keys.len() as u64);
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build within the RAM budget")?;
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "rayon",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_single_par_ram_budget() -> Result<()> {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use rayon::prelude::*;
+
+    let (_temp_dir, mut config) = quiet_config()?;
+    config.ram = 1000;
+    // Would read keys before hashing them
+    config.check_duplicates = false;
+
+    let keys: Vec<u64> = (0..1_000_000).collect();
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+
+    // The number of keys is known, so the build fails before hashing them
+    let num_read = AtomicU64::new(0);
+    let read_key = |key| {
+        num_read.fetch_add(1, Ordering::Relaxed);
+        key
+    };
+    assert!(matches!(
+        f.par_build_in_internal_memory_from_bytes(|| keys.par_iter().map(read_key), &config),
+        Err(BuildError::RamBudgetExceeded { .. })
+    ));
+    assert_eq!(num_read.load(Ordering::Relaxed), 0);
+
+    // Filtered keys have an unknown length, so the budget is checked while hashing
+    assert!(matches!(
+        f.par_build_in_internal_memory_from_bytes(
+            || keys.par_iter().filter(|_| true).map(read_key),
+            &config
+        ),
+        Err(BuildError::RamBudgetExceeded { .. })
+    ));
+    assert!(num_read.load(Ordering::Relaxed) < keys.len() as u64 / 2);
+
+    Ok(())
+}
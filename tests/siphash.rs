@@ -0,0 +1,63 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests building and calling functions with the SipHash hashers
+
+#![cfg(all(
+    feature = "build",
+    feature = "siphash",
+    feature = "minimal",
+    feature = "hash128",
+    feature = "dictionary_dictionary"
+))]
+
+use anyhow::Result;
+
+use pthash::*;
+
+mod common;
+use common::*;
+
+#[test]
+fn test_siphash13_64() -> Result<()> {
+    test_hasher::<Minimal, SipHash13_64, _>(&int_keys())
+}
+
+#[test]
+fn test_siphash24_64() -> Result<()> {
+    test_hasher::<Minimal, SipHash24_64, _>(&int_keys())
+}
+
+#[test]
+fn test_siphash13_128() -> Result<()> {
+    test_hasher::<Minimal, SipHash13_128, _>(&int_keys())
+}
+
+#[test]
+fn test_siphash24_128() -> Result<()> {
+    test_hasher::<Minimal, SipHash24_128, _>(&int_keys())
+}
+
+#[test]
+fn test_siphash13_64_load_as_siphash24() -> Result<()> {
+    test_load_other_hasher::<SipHash13_64, SipHash24_64>()
+}
+
+#[test]
+fn test_siphash24_128_load_as_murmurhash() -> Result<()> {
+    test_load_other_hasher::<SipHash24_128, MurmurHash2_128>()
+}
+
+#[test]
+fn test_siphash_keyed_by_seed() {
+    let key = b"abc".as_slice();
+    assert_ne!(SipHash13_64::hash(key, 0), SipHash13_64::hash(key, 1));
+    assert_ne!(SipHash24_64::hash(key, 0), SipHash24_64::hash(key, 1));
+    assert_ne!(SipHash13_128::hash(key, 0), SipHash13_128::hash(key, 1));
+    assert_ne!(SipHash24_128::hash(key, 0), SipHash24_128::hash(key, 1));
+
+    // Both rounds counts are different functions
+    assert_ne!(SipHash13_64::hash(key, 42), SipHash24_64::hash(key, 42));
+}
@@ -0,0 +1,69 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests [`Swhid`] keys hash like their textual representation
+
+#![cfg(feature = "swhid")]
+
+use anyhow::{Context, Result};
+
+use pthash::swhid::*;
+use pthash::*;
+
+const SWHIDS: [&str; 3] = [
+    "swh:1:cnt:94a9ed024d3859793618152ea559a168bbcbb5e2",
+    "swh:1:dir:d198bc9d7a6bcf6db04f476d29314f157507d505",
+    "swh:1:rev:309cf2674ee7a0749978cf8265ab91a60aea0f7d",
+];
+
+#[test]
+fn test_swhid_roundtrip() -> Result<()> {
+    for s in SWHIDS {
+        let swhid: Swhid = s.parse().context("Could not parse SWHID")?;
+        assert_eq!(swhid.to_string(), s);
+        assert_eq!(swhid.as_bytes(), s.as_bytes());
+    }
+
+    assert!("swh:1:cnt:94a9ed024d3859793618152ea559a168bbcbb5e"
+        .parse::<Swhid>()
+        .is_err());
+    assert!("swh:1:foo:94a9ed024d3859793618152ea559a168bbcbb5e2"
+        .parse::<Swhid>()
+        .is_err());
+    assert!("swh:1:cnt:94a9ed024d3859793618152ea559a168bbcbb5eg"
+        .parse::<Swhid>()
+        .is_err());
+
+    Ok(())
+}
+
+#[cfg(all(
+    feature = "build",
+    feature = "minimal",
+    feature = "hash128",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_swhid_hash_like_text() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let swhids: Vec<Swhid> = SWHIDS
+        .iter()
+        .map(|s| s.parse())
+        .collect::<Result<_, _>>()
+        .context("Could not parse SWHIDs")?;
+
+    let mut f = SinglePhf::<Minimal, MurmurHash2_128, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &swhids, &config)
+        .context("Failed to build")?;
+
+    for (swhid, s) in swhids.iter().zip(SWHIDS) {
+        assert_eq!(f.hash(swhid), f.hash(s.as_bytes()));
+    }
+
+    Ok(())
+}
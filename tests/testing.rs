@@ -0,0 +1,62 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+#![cfg(all(
+    feature = "build",
+    feature = "testing",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+
+use anyhow::{Context, Result};
+
+use pthash::testing::*;
+use pthash::*;
+
+#[test]
+fn test_random_keys() {
+    let keys = random_u64_keys(1000, 42);
+    assert_eq!(keys.len(), 1000);
+    assert_eq!(keys, random_u64_keys(1000, 42));
+    assert_ne!(keys, random_u64_keys(1000, 43));
+
+    let keys = random_byte_keys(1000, 2, 42);
+    assert_eq!(keys.len(), 1000);
+    assert!(keys.iter().all(|key| key.len() <= 2));
+    let mut sorted_keys = keys.clone();
+    sorted_keys.sort();
+    sorted_keys.dedup();
+    assert_eq!(sorted_keys.len(), 1000);
+}
+
+#[test]
+fn test_assertions() -> Result<()> {
+    let temp_dir = tempfile::tempdir().context("Could not create temp dir")?;
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    let keys = random_byte_keys(1000, 16, 42);
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .context("Failed to build")?;
+    assert_perfect(&keys, &f);
+    let loaded = assert_roundtrip(&keys, &mut f);
+    assert_same_hashes(&keys, &f, &loaded);
+    assert_file_roundtrip(&keys, &mut f, temp_dir.path().join("f.bin"));
+
+    let mut other_keys = keys.clone();
+    other_keys.push(b"not a key".to_vec());
+    let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        assert_perfect(&other_keys, &f)
+    }));
+    let message = res.err().context("Colliding keys were not detected")?;
+    let message = message
+        .downcast_ref::<String>()
+        .context("Unexpected panic payload")?;
+    assert!(message.contains("not a key"), "{message}");
+
+    Ok(())
+}
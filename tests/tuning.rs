@@ -0,0 +1,224 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests helpers from [`pthash::tuning`]
+
+#[cfg(feature = "rand")]
+use rand::rngs::StdRng;
+#[cfg(feature = "rand")]
+use rand::SeedableRng;
+
+use pthash::tuning::*;
+use pthash::*;
+
+#[cfg(feature = "rand")]
+#[test]
+fn test_key_sample() {
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let sample = KeySample::new(0..1_000_000u64, 1000, &mut rng);
+    assert_eq!(sample.num_keys(), 1_000_000);
+    assert_eq!(sample.keys().len(), 1000);
+    assert_eq!(sample.average_key_len(), Some(8.0));
+    // Not just the first keys
+    assert!(sample.keys().iter().any(|&key| key >= 1000));
+
+    let sample = KeySample::new(["a", "bcd"].map(str::as_bytes), 1000, &mut rng);
+    assert_eq!(sample.num_keys(), 2);
+    assert_eq!(sample.average_key_len(), Some(2.0));
+
+    let sample = KeySample::new(std::iter::empty::<u64>(), 1000, &mut rng);
+    assert_eq!(sample.average_key_len(), None);
+}
+
+#[cfg(feature = "rand")]
+#[test]
+fn test_recommended_configuration() {
+    let config = BuildConfiguration::new(std::env::temp_dir());
+    let mut rng = StdRng::seed_from_u64(42);
+
+    let sample = KeySample::new(0..10_000_000u64, 100, &mut rng);
+    let recommended = sample.recommended_configuration(&config);
+    assert_eq!(recommended.num_partitions, 4);
+    assert_eq!(recommended.tmp_dir, config.tmp_dir);
+}
+
+#[test]
+fn test_tuned_for() {
+    let small = BuildConfiguration::tuned_for(1000, SpaceOrSpeed::Speed);
+    assert_eq!(small.num_partitions, 1);
+    assert_eq!(small.num_threads, 1);
+    assert_eq!((small.c, small.alpha), (7.0, 0.94));
+
+    let large = BuildConfiguration::tuned_for(10_000_000, SpaceOrSpeed::Space);
+    assert_eq!(large.num_partitions, 4);
+    assert!((1..=4).contains(&large.num_threads));
+    assert!(large.c < small.c);
+    assert!(large.alpha > small.alpha);
+}
+
+#[test]
+fn test_predict() {
+    assert_eq!(table_size(1000, 0.5), 2000);
+    // powers of two are avoided
+    assert_eq!(table_size(1024, 1.0), 1025);
+    assert_eq!(num_buckets(1024, 5.0), 512);
+
+    let single = predict(1_000_000, 5.0, 0.98, 1);
+    assert_eq!(single.table_size, table_size(1_000_000, 0.98));
+    assert_eq!(single.num_buckets, num_buckets(1_000_000, 5.0));
+
+    let partitioned = predict(1_000_000, 5.0, 0.98, 4);
+    assert_eq!(partitioned.table_size, 4 * table_size(250_000, 0.98));
+    assert_eq!(partitioned.num_buckets, 4 * num_buckets(250_000, 5.0));
+
+    // Search effort explodes as alpha gets close to 1
+    assert!(
+        predict(1_000_000, 5.0, 0.99, 1).expected_search_trials
+            > 1.5 * predict(1_000_000, 5.0, 0.9, 1).expected_search_trials
+    );
+}
+
+#[test]
+fn test_hashing_max_len() {
+    assert_eq!(hashing_max_len(100, 8), HASHING_MIN_CHUNK_LEN);
+    assert_eq!(
+        hashing_max_len(100_000_000, 8),
+        100_000_000 / (8 * HASHING_CHUNKS_PER_THREAD)
+    );
+    assert_eq!(
+        hashing_max_len(100_000_000, 0),
+        hashing_max_len(100_000_000, 1)
+    );
+}
+
+#[cfg(all(
+    feature = "build",
+    feature = "rayon",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_hashing_max_len_skewed_keys() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use rayon::prelude::*;
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+
+    // A few long keys, clustered at the start
+    let keys: Vec<Vec<u8>> = (0..100_000u64)
+        .map(|i| {
+            let mut key = i.to_ne_bytes().to_vec();
+            if i < 100 {
+                key.resize(100_000, 0);
+            }
+            key
+        })
+        .collect();
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+    let max_len = hashing_max_len(keys.len(), pool.current_num_threads());
+    // 100,000 keys are fewer than 64 chunks per thread of the minimal length
+    assert_eq!(max_len, HASHING_MIN_CHUNK_LEN);
+
+    // Each chunk initializes its own state
+    let num_chunks = AtomicUsize::new(0);
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, DictionaryDictionary>::new();
+    f.par_build_in_internal_memory_from_bytes_in_pool(
+        &pool,
+        || {
+            keys.par_iter()
+                .with_max_len(max_len)
+                .map_init(|| num_chunks.fetch_add(1, Ordering::Relaxed), |_, key| key)
+        },
+        &config,
+    )
+    .expect("Failed to build");
+    assert!(num_chunks.load(Ordering::Relaxed) >= keys.len().div_ceil(max_len));
+
+    let mut hashes: Vec<u64> = keys.iter().map(|key| f.hash(key)).collect();
+    hashes.sort();
+    assert_eq!(hashes, (0..100_000).collect::<Vec<_>>());
+}
+
+#[cfg(all(feature = "minimal", feature = "nonminimal", feature = "elias_fano"))]
+#[test]
+fn test_estimate_bits_per_key() {
+    let mut config = BuildConfiguration::new(std::env::temp_dir());
+    config.c = 6.0;
+    config.alpha = 0.94;
+
+    let minimal = estimate_bits_per_key::<Minimal>(1_000_000, &config, EncoderKind::EliasFano);
+    let nonminimal =
+        estimate_bits_per_key::<Nonminimal>(1_000_000, &config, EncoderKind::EliasFano);
+    assert!(minimal > nonminimal);
+    assert!(1.0 < nonminimal && nonminimal < 10.0, "{nonminimal}");
+}
+
+/// Checks [`estimate_bits_per_key`] is within 50% of the size of a function built
+/// with `E`
+#[cfg(all(feature = "build", feature = "minimal", feature = "hash64"))]
+fn test_estimate_bits_per_key_measured<E: Encoder>() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let mut config = BuildConfiguration::new(temp_dir.path().to_owned());
+    config.verbose_output = false;
+    config.seed = 42;
+    config.c = 6.0;
+    config.alpha = 0.94;
+
+    let keys: Vec<u64> = (0..1_000_000).collect();
+    let mut f = SinglePhf::<Minimal, MurmurHash2_64, E>::new();
+    f.build_in_internal_memory_from_bytes(|| &keys, &config)
+        .expect("Failed to build");
+
+    let measured = f.num_bits() as f64 / keys.len() as f64;
+    let kind = EncoderKind::from_name(E::NAME).unwrap();
+    let estimated = estimate_bits_per_key::<Minimal>(keys.len() as u64, &config, kind);
+    assert!(
+        measured / 1.5 < estimated && estimated < measured * 1.5,
+        "{} estimated {estimated} bits per key, measured {measured}",
+        E::NAME
+    );
+}
+
+#[cfg(all(
+    feature = "build",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "dictionary_dictionary"
+))]
+#[test]
+fn test_estimate_bits_per_key_dictionary_dictionary() {
+    test_estimate_bits_per_key_measured::<DictionaryDictionary>()
+}
+
+#[cfg(all(
+    feature = "build",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "partitioned_compact"
+))]
+#[test]
+fn test_estimate_bits_per_key_partitioned_compact() {
+    test_estimate_bits_per_key_measured::<PartitionedCompact>()
+}
+
+#[cfg(all(
+    feature = "build",
+    feature = "minimal",
+    feature = "hash64",
+    feature = "elias_fano"
+))]
+#[test]
+fn test_estimate_bits_per_key_elias_fano() {
+    test_estimate_bits_per_key_measured::<EliasFano>()
+}
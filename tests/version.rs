@@ -0,0 +1,17 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+use pthash::*;
+
+#[test]
+fn test_version() {
+    let version = version();
+    assert_eq!(version.crate_version, env!("CARGO_PKG_VERSION"));
+    assert!(!version.pthash_commit.is_empty());
+    assert_eq!(version.encoders.len(), EncoderKind::ALL.len());
+    #[cfg(feature = "minimal")]
+    assert!(version.features.contains(&"minimal"));
+    assert!(version.to_string().starts_with("pthash-rs "));
+}
@@ -0,0 +1,64 @@
+// Copyright (C) 2026 The Software Heritage developers
+// See the AUTHORS file at the top-level directory of this distribution
+// License: GNU General Public License version 3, or any later version
+// See top-level LICENSE file for more information
+
+//! Tests building and calling functions with the XXH3 hashers
+
+#![cfg(all(
+    feature = "build",
+    feature = "xxh3",
+    feature = "minimal",
+    feature = "hash128",
+    feature = "dictionary_dictionary"
+))]
+
+use anyhow::Result;
+
+use pthash::*;
+
+mod common;
+use common::*;
+
+/// Long keys, which XXH3 is meant for
+fn long_keys() -> Vec<Vec<u8>> {
+    (0..1000u64)
+        .map(|i| format!("swh:1:cnt:{:040x}/{}", i, "a".repeat(100)).into_bytes())
+        .collect()
+}
+
+#[test]
+fn test_xxh3_64() -> Result<()> {
+    test_hasher::<Minimal, Xxh3_64, _>(&long_keys())
+}
+
+#[test]
+fn test_xxh3_128() -> Result<()> {
+    test_hasher::<Minimal, Xxh3_128, _>(&long_keys())
+}
+
+#[test]
+fn test_xxh3_64_load_as_murmurhash() -> Result<()> {
+    test_load_other_hasher::<Xxh3_64, MurmurHash2_64>()
+}
+
+#[test]
+fn test_xxh3_128_load_as_murmurhash() -> Result<()> {
+    test_load_other_hasher::<Xxh3_128, MurmurHash2_128>()
+}
+
+#[test]
+fn test_xxh3_reference_values() {
+    // From xxHash's test vectors
+    assert_eq!(Xxh3_64::hash(b"".as_slice(), 0).first(), 0x2d06800538d394c2);
+    let hash = Xxh3_128::hash(b"".as_slice(), 0);
+    assert_eq!(
+        (hash.first(), hash.second()),
+        (0x99aa06d3014798d8, 0x6001c324468d497f)
+    );
+
+    assert_ne!(
+        Xxh3_64::hash(b"abc".as_slice(), 0),
+        Xxh3_64::hash(b"abc".as_slice(), 1)
+    );
+}